@@ -0,0 +1,68 @@
+use crate::win32;
+
+/// What `wndproc` and the main loop share while randword runs. It lives in `main` and the
+/// window points at it with `GWLP_USERDATA`; both run on the thread of the window, so cells
+/// are enough.
+pub struct App {
+    /// cleared by closing the window or `WM_QUIT`; the main loop and the modal popups end then
+    pub running: std::cell::Cell<bool>,
+    /// the hotkeys are unregistered for quiet hours or a snooze
+    pub paused: std::cell::Cell<bool>,
+    /// when the hotkeys come back from a snooze
+    pub snoozed_until: std::cell::Cell<Option<std::time::Instant>>,
+    /// a list received from a second instance, picked up by the main loop
+    pub pending_list: std::cell::RefCell<Option<String>>,
+    /// the window the thumbbar buttons hand the focus back to
+    pub target: std::cell::Cell<win32::HWND>,
+    /// the last words and the progress, drawn in `WM_PAINT`
+    pub history: std::cell::RefCell<crate::paint::History>,
+    /// shown when the tray icon is hovered; empty until it is added
+    pub tray_tip: std::cell::RefCell<String>,
+    /// the profile picked in the tray, or None for the options without one
+    pub profile: std::cell::RefCell<Option<String>>,
+    /// a profile picked in the tray, picked up by the main loop; Some(None) goes back to none
+    pub pending_profile: std::cell::RefCell<Option<Option<String>>>,
+    /// a Jump List task that needs the list or options, picked up by the main loop
+    pub pending_task: std::cell::Cell<Option<usize>>,
+    /// Copy again, Skip or Star picked in the tray, picked up by the main loop
+    pub pending_command: std::cell::Cell<Option<usize>>,
+    /// `osd`, once its window is created
+    pub overlay: std::cell::RefCell<Option<crate::osd::Overlay>>,
+}
+
+impl App {
+    pub fn new() -> Self {
+        return Self {
+            running: std::cell::Cell::new(true),
+            paused: std::cell::Cell::new(false),
+            snoozed_until: std::cell::Cell::new(None),
+            pending_list: std::cell::RefCell::new(None),
+            target: std::cell::Cell::new(0),
+            history: std::cell::RefCell::new(crate::paint::History::default()),
+            tray_tip: std::cell::RefCell::new(String::new()),
+            profile: std::cell::RefCell::new(None),
+            pending_profile: std::cell::RefCell::new(None),
+            pending_task: std::cell::Cell::new(None),
+            pending_command: std::cell::Cell::new(None),
+            overlay: std::cell::RefCell::new(None),
+        };
+    }
+
+    /// Makes the app reachable from `h_window`. It has to outlive the window, which
+    /// `Window::drop` makes sure of by detaching it first.
+    pub fn attach(&self, h_window: win32::HWND) {
+        let app = self as *const Self as isize;
+        unsafe { win32::SetWindowLongPtrW(h_window, win32::GWLP_USERDATA, app) };
+    }
+}
+
+/// The app `h_window` was attached to; None before `attach`, after `Window::drop`, or for
+/// another window.
+pub fn get<'a>(h_window: win32::HWND) -> Option<&'a App> {
+    let app = unsafe { win32::GetWindowLongPtrW(h_window, win32::GWLP_USERDATA) };
+    return unsafe { (app as *const App).as_ref() };
+}
+
+pub fn detach(h_window: win32::HWND) {
+    unsafe { win32::SetWindowLongPtrW(h_window, win32::GWLP_USERDATA, 0) };
+}
@@ -0,0 +1,131 @@
+use crate::win32;
+
+/// Typed by every backend; plain ASCII, so every layout can type it.
+const SAMPLE: &str = "The quick brown fox jumps over the lazy dog, 0123456789! ";
+const SAMPLE_REPEAT: usize = 4;
+/// A backend is done once the test window's text stopped changing for this long.
+const SETTLE: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// One way of getting text into the focused window.
+struct Backend {
+    name: &'static str,
+    send: fn(&str),
+}
+
+const BACKENDS: [Backend; 3] = [
+    Backend { name: "type", send: send_typed },
+    Backend { name: "unicode", send: send_unicode },
+    Backend { name: "paste", send: send_paste },
+];
+
+fn send_typed(text: &str) {
+    crate::type_out_characters(text);
+}
+
+fn send_unicode(text: &str) {
+    let mut inputs = Vec::with_capacity(text.len() * 2);
+    for chr in text.chars() {
+        crate::push_unicode(&mut inputs, chr);
+    }
+    crate::send_inputs(&inputs);
+}
+
+fn send_paste(text: &str) {
+    crate::set_clipboard_string(text);
+    crate::send_paste();
+}
+
+fn window_text(h_window: win32::HWND) -> String {
+    let mut text = vec![0u16; SAMPLE.len() * SAMPLE_REPEAT * 2 + 1];
+    let len = unsafe { win32::GetWindowTextW(h_window, text.as_mut_ptr(), text.len() as i32) };
+    return String::from_utf16_lossy(&text[..len.max(0) as usize]);
+}
+
+/// Handles the queued input until the text stops changing; returns it and when it last changed.
+pub fn settle(h_window: win32::HWND, started: std::time::Instant) -> (String, std::time::Duration) {
+    let mut text = String::new();
+    let mut changed = started.elapsed();
+    while started.elapsed() - changed < SETTLE {
+        let mut msg = unsafe { std::mem::zeroed() };
+        while unsafe { win32::PeekMessageW(&mut msg, 0, 0, 0, win32::PM_REMOVE) } != 0 {
+            unsafe {
+                win32::TranslateMessage(&msg);
+                win32::DispatchMessageW(&msg);
+            }
+        }
+        let now = window_text(h_window);
+        if now != text {
+            text = now;
+            changed = started.elapsed();
+        }
+        unsafe { win32::Sleep(1) };
+    }
+    return (text, changed);
+}
+
+/// How many chars of `expected` arrived, in order; the rest were dropped or garbled.
+fn arrived(expected: &str, received: &str) -> usize {
+    let mut received = received.chars();
+    return expected.chars().filter(|&c| received.by_ref().any(|r| r == c)).count();
+}
+
+/// A topmost edit box in the foreground to type into, or the error code.
+pub fn open_test_window() -> Result<win32::HWND, u32> {
+    let h_window = unsafe {
+        win32::CreateWindowExW(
+            win32::WS_EX_TOPMOST,
+            win32::w!("EDIT"),
+            win32::w!(""),
+            win32::WS_CAPTION | win32::WS_SYSMENU | win32::WS_VISIBLE,
+            100,
+            100,
+            600,
+            80,
+            0,
+            0,
+            0,
+            std::ptr::null(),
+        )
+    };
+    if h_window == 0 {
+        return Err(unsafe { win32::GetLastError() });
+    }
+    unsafe { win32::SetForegroundWindow(h_window) };
+    return Ok(h_window);
+}
+
+/// `randword bench`: types a sample into a test window with each backend and reports the
+/// speed and how much got lost, to pick the mode for a picky app.
+pub fn run() {
+    let h_window = match open_test_window() {
+        Ok(h) => h,
+        Err(error_code) => {
+            crate::message_box(
+                &crate::format_win32_error(error_code),
+                "Bench Error",
+                win32::MB_OK | win32::MB_ICONEXCLAMATION,
+            );
+            return;
+        }
+    };
+
+    let sample = SAMPLE.repeat(SAMPLE_REPEAT);
+    let expected = sample.chars().count();
+    let mut out = format!("{} chars into a test window\n", expected);
+    for backend in &BACKENDS {
+        unsafe { win32::SetWindowTextW(h_window, win32::w!("")) };
+        let started = std::time::Instant::now();
+        (backend.send)(&sample);
+        let (text, took) = settle(h_window, started);
+        let arrived = arrived(&sample, &text);
+        let per_second = arrived as f64 / took.as_secs_f64().max(0.001);
+        let dropped = (expected - arrived) as f64 * 100.0 / expected as f64;
+        out.push_str(&format!(
+            "\n{:<8} {:>8.0} chars/s  {:>5.1}% dropped",
+            backend.name, per_second, dropped
+        ));
+    }
+    out.push_str("\n\nThe paste test replaced the clipboard.");
+    unsafe { win32::DestroyWindow(h_window) };
+    crate::message_box(&out, "Injection Benchmark", win32::MB_OK | win32::MB_ICONINFORMATION);
+}
@@ -0,0 +1,28 @@
+/// Hebrew, Arabic, Syriac, Thaana, NKo and the other right-to-left scripts, with their
+/// presentation forms.
+pub fn is_rtl_char(chr: char) -> bool {
+    return matches!(
+        chr as u32,
+        0x0590..=0x08FF | 0xFB1D..=0xFDFF | 0xFE70..=0xFEFC | 0x10800..=0x10FFF | 0x1E800..=0x1EFFF
+    );
+}
+
+/// Whether `text` reads right to left, as the Unicode bidi algorithm decides for a paragraph:
+/// by its first letter, or by a leading RLM, RLE, RLO or RLI mark.
+pub fn is_rtl(text: &str) -> bool {
+    for chr in text.chars() {
+        if is_rtl_char(chr) || matches!(chr, '\u{200F}' | '\u{202B}' | '\u{202E}' | '\u{2067}') {
+            return true;
+        }
+        if chr.is_alphabetic() || matches!(chr, '\u{200E}' | '\u{202A}' | '\u{202D}' | '\u{2066}') {
+            return false;
+        }
+    }
+    return false;
+}
+
+/// `text` between FSI and PDI marks, for showing it in a line with other text: a Hebrew word
+/// before " • 12/300" would otherwise pull the counter to its left.
+pub fn isolate(text: &str) -> String {
+    return format!("\u{2068}{}\u{2069}", text);
+}
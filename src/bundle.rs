@@ -0,0 +1,108 @@
+use std::io::{Read, Write};
+
+const LIST_NAME: &str = "list.txt";
+const OPTIONS_NAME: &str = "options.txt";
+const POSITION_NAME: &str = "skipline.dat";
+/// The options a bundle may set, by name: how the words go out and in what order. Anything
+/// else could run commands, send the words elsewhere or write outside the bundle's folder, or
+/// is tied to the machine it was exported on, so it is neither exported nor opened.
+const BUNDLE_OPTIONS: [&str; 11] = [
+    "clip",
+    "paste",
+    "mode",
+    "delay",
+    "chardelay",
+    "osd",
+    "random",
+    "coverage",
+    "norepeat",
+    "shuffle",
+    "goal",
+];
+
+fn is_bundle_option(arg: &str) -> bool {
+    let name = arg.split_once('=').map_or(arg, |(name, _)| name.trim_end());
+    return BUNDLE_OPTIONS.contains(&name);
+}
+
+/// A `.rword` bundle is a zip holding `list.txt`, an optional `options.txt` with command line
+/// options one per line (`#` starts a comment; only `BUNDLE_OPTIONS`) and an optional
+/// `skipline.dat` to start from, so a whole drill can be handed out as one double-clickable
+/// file.
+///
+/// Opening one unpacks it into a folder next to it named after the bundle and returns the
+/// arguments to start with. The position is only unpacked the first time; later opens
+/// continue where the student left off.
+pub fn open(path: &str) -> anyhow::Result<Vec<String>> {
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(path)?)
+        .map_err(|e| anyhow::anyhow!("{}: {}", path, e))?;
+    let dir = std::path::Path::new(path).with_extension("");
+    std::fs::create_dir_all(&dir)?;
+
+    let list_path = dir.join(LIST_NAME).to_string_lossy().into_owned();
+    let mut list = Vec::new();
+    archive
+        .by_name(LIST_NAME)
+        .map_err(|e| anyhow::anyhow!("{}: {}: {}", path, LIST_NAME, e))?
+        .read_to_end(&mut list)?;
+    std::fs::write(&list_path, &list)?;
+
+    let position_path = crate::skipline_path(&list_path);
+    if !std::path::Path::new(&position_path).exists() {
+        if let Ok(mut file) = archive.by_name(POSITION_NAME) {
+            let mut position = Vec::new();
+            file.read_to_end(&mut position)?;
+            std::fs::write(&position_path, &position)?;
+        }
+    }
+
+    let mut args = Vec::new();
+    match archive.by_name(OPTIONS_NAME) {
+        Ok(mut file) => {
+            let mut text = String::new();
+            file.read_to_string(&mut text)?;
+            for line in text.lines().map(str::trim) {
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                if !is_bundle_option(line) {
+                    return Err(anyhow::anyhow!(
+                        "{}: {} can only set {}, not `{}`",
+                        path,
+                        OPTIONS_NAME,
+                        BUNDLE_OPTIONS.join(", "),
+                        line
+                    ));
+                }
+                args.push(line.to_owned());
+            }
+        }
+        Err(zip::result::ZipError::FileNotFound) => {}
+        Err(e) => return Err(anyhow::anyhow!("{}: {}: {}", path, OPTIONS_NAME, e)),
+    }
+    args.push(format!("list={}", list_path));
+    return Ok(args);
+}
+
+/// Packs the list at `list_path`, the options in `args` a bundle may set and `position` into
+/// a bundle at `path`.
+pub fn export(path: &str, list_path: &str, args: &[String], position: u64) -> anyhow::Result<()> {
+    let list = std::fs::read(list_path).map_err(|e| anyhow::anyhow!("{}: {}", list_path, e))?;
+    let file_options = zip::write::FileOptions::default();
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(path)?);
+
+    zip.start_file(LIST_NAME, file_options)?;
+    zip.write_all(&list)?;
+
+    zip.start_file(OPTIONS_NAME, file_options)?;
+    for arg in args {
+        if is_bundle_option(arg) {
+            writeln!(zip, "{}", arg)?;
+        }
+    }
+
+    zip.start_file(POSITION_NAME, file_options)?;
+    zip.write_all(&crate::u64_to_array::<{ crate::SKILINE_NUMBER_SIZE }>(position))?;
+    zip.finish()?;
+    return Ok(());
+}
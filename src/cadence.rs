@@ -0,0 +1,123 @@
+use std::io::Write;
+
+use crate::win32;
+
+/// An attempt not finished by then is given up; nobody takes this long over one word.
+const ATTEMPT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// One word being typed by hand: the key presses since it was dispensed.
+struct Attempt {
+    word: String,
+    timestamp: String,
+    started: std::time::Instant,
+    /// the window in front when it was dispensed, which the word is typed into
+    window: win32::HWND,
+    keys: Vec<(u16, std::time::Instant)>,
+}
+
+/// `practice` or `practice=<file>`: after each word the user types it themselves, e.g. from
+/// the `spell` mode or the overlay, and the time between each two key presses is appended to
+/// `file` (./cadence.csv by default) as `time,word,from,to,ms`, for finding slow key pairs in
+/// a spreadsheet.
+///
+/// Keys are read with raw input like `raw=`, from every keyboard, and written to `file` as
+/// plain text, so only the keys of the word are taken: the attempt ends with Enter or Tab,
+/// after as many keys as the word has chars, 30 seconds after the word was dispensed, when
+/// another window comes to the front, or when the next word is dispensed. No key is kept
+/// between attempts. The keys randword sends itself have no device and are left out.
+pub struct Practice {
+    pub path: String,
+    /// filled from `WM_INPUT`, which only gets the options
+    attempt: std::cell::RefCell<Option<Attempt>>,
+}
+
+/// The name of `vkey` in the export: the char for letters and digits, else the token name.
+fn key_name(vkey: u16) -> String {
+    return match vkey {
+        0x30..=0x39 | 0x41..=0x5A => char::from(vkey as u8).to_string(),
+        _ => match crate::tokens::key_name(vkey) {
+            Some(name) => name.to_owned(),
+            None => format!("0x{:02X}", vkey),
+        },
+    };
+}
+
+/// Shift, Ctrl, Alt and the Windows keys, left out so the pairs are of the keys typed.
+fn is_modifier(vkey: u16) -> bool {
+    return matches!(vkey, 0x10..=0x12 | 0xA0..=0xA5)
+        || vkey == win32::VK_LWIN
+        || vkey == win32::VK_RWIN;
+}
+
+impl Practice {
+    pub fn new(path: &str) -> Self {
+        return Self { path: path.to_owned(), attempt: std::cell::RefCell::new(None) };
+    }
+
+    /// Starts timing the keys for `word`, writing out the word before it.
+    pub fn start(&self, word: &str, timestamp: &str) -> std::io::Result<()> {
+        let done = self.attempt.replace(Some(Attempt {
+            word: word.to_owned(),
+            timestamp: timestamp.to_owned(),
+            started: std::time::Instant::now(),
+            window: unsafe { win32::GetForegroundWindow() },
+            keys: Vec::new(),
+        }));
+        return self.write(done);
+    }
+
+    /// Handles a `WM_INPUT` message while a word is being typed. A key that comes after the
+    /// attempt ended, see `Practice`, ends it without being taken.
+    pub fn handle(&self, lparam: win32::LPARAM) -> std::io::Result<()> {
+        let Some((h_device, vkey)) = crate::rawinput::read_key_down(lparam) else {
+            return Ok(());
+        };
+        if h_device == 0 || is_modifier(vkey) {
+            return Ok(());
+        }
+        let over = self.attempt.borrow().as_ref().is_some_and(|attempt| {
+            attempt.started.elapsed() > ATTEMPT_TIMEOUT
+                || unsafe { win32::GetForegroundWindow() } != attempt.window
+        });
+        if over || vkey == win32::VK_RETURN || vkey == win32::VK_TAB {
+            let done = self.attempt.take();
+            return self.write(done);
+        }
+        let mut attempt = self.attempt.borrow_mut();
+        let Some(typing) = attempt.as_mut() else {
+            return Ok(());
+        };
+        typing.keys.push((vkey, std::time::Instant::now()));
+        if typing.keys.len() >= crate::grapheme::count(&typing.word) {
+            let done = attempt.take();
+            drop(attempt);
+            return self.write(done);
+        }
+        return Ok(());
+    }
+
+    fn write(&self, attempt: Option<Attempt>) -> std::io::Result<()> {
+        let Some(attempt) = attempt.filter(|a| a.keys.len() > 1) else {
+            return Ok(());
+        };
+        let is_new = !std::path::Path::new(&self.path).exists();
+        let file = std::fs::OpenOptions::new().create(true).append(true).open(&self.path)?;
+        let mut out = std::io::BufWriter::new(file);
+        if is_new {
+            writeln!(out, "time,word,from,to,ms")?;
+        }
+        for pair in attempt.keys.windows(2) {
+            let ((from, pressed), (to, next)) = (pair[0], pair[1]);
+            writeln!(
+                out,
+                "{},{},{},{},{:.1}",
+                attempt.timestamp,
+                crate::session::csv_field(&attempt.word),
+                key_name(from),
+                key_name(to),
+                (next - pressed).as_secs_f64() * 1000.0
+            )?;
+        }
+        return out.flush();
+    }
+}
@@ -0,0 +1,292 @@
+use crate::win32;
+
+/// Lines longer than this (in chars as seen, an accented letter being one) are flagged;
+/// nobody drills a paragraph.
+const MAX_LINE_LEN: usize = 64;
+/// How many line numbers are listed per problem before the rest is summarised.
+const MAX_LISTED: usize = 8;
+
+#[derive(Default)]
+pub struct Report {
+    pub total_lines: usize,
+    pub invalid_utf8: Vec<usize>,
+    pub too_long: Vec<usize>,
+    /// (line, the line numbers it appears at), only for lines seen more than once
+    pub duplicates: Vec<(String, Vec<usize>)>,
+    /// (line number, offending char)
+    pub untypeable: Vec<(usize, char)>,
+}
+
+impl Report {
+    pub fn is_clean(&self) -> bool {
+        return self.invalid_utf8.is_empty()
+            && self.too_long.is_empty()
+            && self.duplicates.is_empty()
+            && self.untypeable.is_empty();
+    }
+
+    pub fn summary(&self) -> String {
+        let mut out = format!("{} lines checked\n", self.total_lines);
+        if self.is_clean() {
+            out.push_str("No problems found");
+            return out;
+        }
+
+        if !self.invalid_utf8.is_empty() {
+            out.push_str(&format!(
+                "\nInvalid UTF-8 ({}): lines {}",
+                self.invalid_utf8.len(),
+                list_lines(self.invalid_utf8.iter().copied())
+            ));
+        }
+        if !self.too_long.is_empty() {
+            out.push_str(&format!(
+                "\nLonger than {} chars ({}): lines {}",
+                MAX_LINE_LEN,
+                self.too_long.len(),
+                list_lines(self.too_long.iter().copied())
+            ));
+        }
+        if !self.duplicates.is_empty() {
+            out.push_str(&format!("\nDuplicated ({}):", self.duplicates.len()));
+            for (line, numbers) in self.duplicates.iter().take(MAX_LISTED) {
+                let numbers = list_lines(numbers.iter().copied());
+                out.push_str(&format!("\n    \"{}\" lines {}", line, numbers));
+            }
+            if self.duplicates.len() > MAX_LISTED {
+                out.push_str(&format!("\n    ... and {} more", self.duplicates.len() - MAX_LISTED));
+            }
+        }
+        if !self.untypeable.is_empty() {
+            out.push_str(&format!(
+                "\nNot typeable on the current layout ({}):",
+                self.untypeable.len()
+            ));
+            for (line, chr) in self.untypeable.iter().take(MAX_LISTED) {
+                out.push_str(&format!("\n    line {}: '{}' (U+{:04X})", line, chr, *chr as u32));
+            }
+            if self.untypeable.len() > MAX_LISTED {
+                out.push_str(&format!("\n    ... and {} more", self.untypeable.len() - MAX_LISTED));
+            }
+        }
+        return out;
+    }
+}
+
+fn list_lines(lines: impl ExactSizeIterator<Item = usize>) -> String {
+    let len = lines.len();
+    let mut out = lines.take(MAX_LISTED).map(|n| n.to_string()).collect::<Vec<_>>().join(", ");
+    if len > MAX_LISTED {
+        out.push_str(&format!(", ... (+{})", len - MAX_LISTED));
+    }
+    return out;
+}
+
+/// Layout of the window that will receive the keystrokes, not the one of our own thread.
+pub fn active_layout() -> win32::HKL {
+    unsafe {
+        let thread =
+            win32::GetWindowThreadProcessId(win32::GetForegroundWindow(), std::ptr::null_mut());
+        return win32::GetKeyboardLayout(thread);
+    }
+}
+
+/// Follows the foreground keyboard layout so the untypeable-line warning is redone after a switch.
+///
+/// `WM_INPUTLANGCHANGE` only reaches the focused window, and the words go to some other
+/// app, so the layout is polled instead.
+pub struct LayoutWatch {
+    current: win32::HKL,
+    seen: Vec<win32::HKL>,
+}
+
+impl LayoutWatch {
+    pub fn new() -> Self {
+        let current = active_layout();
+        return Self { current, seen: vec![current] };
+    }
+
+    pub fn current(&self) -> win32::HKL {
+        return self.current;
+    }
+
+    /// Returns the new layout when it changed to one not seen before in this session.
+    pub fn poll(&mut self) -> Option<win32::HKL> {
+        let layout = active_layout();
+        if layout == self.current {
+            return None;
+        }
+        self.current = layout;
+        if self.seen.contains(&layout) {
+            return None;
+        }
+        self.seen.push(layout);
+        return Some(layout);
+    }
+}
+
+/// The key that types `chr` on `layout` in the low byte and its modifiers in the high one, as
+/// `keymap=` or else `VkKeyScanExW` says. That returns -1 in both bytes when the char has no
+/// key on `layout`.
+pub fn key_for(chr: char, layout: win32::HKL) -> Option<u16> {
+    if let Some(key) = crate::keymap::lookup(chr) {
+        return Some(key);
+    }
+    if chr as u32 > 0xffff {
+        return None;
+    }
+    let key = unsafe { win32::VkKeyScanExW(chr as u16, layout) };
+    return (key != -1).then_some(key as u16);
+}
+
+pub fn is_typeable(chr: char, layout: win32::HKL) -> bool {
+    return key_for(chr, layout).is_some();
+}
+
+/// How long the IME of the focused app gets to answer; a hung app is taken to have none.
+const IME_TIMEOUT_MS: u32 = 100;
+/// The `WM_IME_CONTROL` request for whether the IME is on; windows-sys has no constant for it.
+const IMC_GETOPENSTATUS: usize = 0x0005;
+
+/// Whether the focused app has its IME open, e.g. the Japanese one in hiragana input. Typed
+/// keys would go into its composition then and come out as something else. `ImmGetContext`
+/// doesn't see into other processes, so the app's default IME window is asked instead.
+pub fn ime_is_open() -> bool {
+    let mut info: win32::GUITHREADINFO = unsafe { std::mem::zeroed() };
+    info.cbSize = std::mem::size_of::<win32::GUITHREADINFO>() as u32;
+    let focus = match unsafe { win32::GetGUIThreadInfo(0, &mut info) } {
+        0 => unsafe { win32::GetForegroundWindow() },
+        _ if info.hwndFocus == 0 => info.hwndActive,
+        _ => info.hwndFocus,
+    };
+    let ime = unsafe { win32::ImmGetDefaultIMEWnd(focus) };
+    if ime == 0 {
+        return false;
+    }
+    let mut open = 0;
+    let answered = unsafe {
+        win32::SendMessageTimeoutW(
+            ime,
+            win32::WM_IME_CONTROL,
+            IMC_GETOPENSTATUS,
+            0,
+            win32::SMTO_ABORTIFHUNG,
+            IME_TIMEOUT_MS,
+            &mut open,
+        )
+    };
+    return answered != 0 && open != 0;
+}
+
+pub fn first_untypeable(line: &str, layout: win32::HKL) -> Option<char> {
+    return line.chars().find(|&chr| !is_typeable(chr, layout));
+}
+
+/// Line numbers (1-based) of every valid line that `type` mode would not reproduce exactly.
+pub fn untypeable_lines(data: &[u8], layout: win32::HKL) -> Vec<usize> {
+    let data = data.strip_suffix(b"\n").unwrap_or(data);
+    return data
+        .split(|&b| b == b'\n')
+        .enumerate()
+        .filter_map(|(i, raw)| {
+            let raw = raw.strip_suffix(b"\r").unwrap_or(raw);
+            let line = std::str::from_utf8(raw).ok()?;
+            // lines sent through the clipboard are never typed
+            let (directive, line) = crate::list::split_directive(line);
+            if directive.is_some_and(|d| d != crate::rules::Mode::Type) {
+                return None;
+            }
+            first_untypeable(line, layout).map(|_| i + 1)
+        })
+        .collect();
+}
+
+/// The lines of `lines`, given with their line numbers, that appear more than once, each with
+/// the numbers it appears at, in the order they first repeat.
+pub fn duplicates<'a>(lines: impl Iterator<Item = (usize, &'a str)>) -> Vec<(String, Vec<usize>)> {
+    let mut seen: std::collections::HashMap<&str, Vec<usize>> = std::collections::HashMap::new();
+    let mut order: Vec<&str> = Vec::new();
+    for (line_number, line) in lines {
+        let numbers = seen.entry(line).or_default();
+        if numbers.len() == 1 {
+            order.push(line);
+        }
+        numbers.push(line_number);
+    }
+    return order.into_iter().map(|l| (l.to_owned(), seen[l].clone())).collect();
+}
+
+pub fn check_words(data: &[u8]) -> Report {
+    let mut report = Report::default();
+    let layout = active_layout();
+    let mut valid: Vec<(usize, &str)> = Vec::new();
+
+    if data.is_empty() {
+        return report;
+    }
+    // a trailing newline at the end of the file does not start another line
+    let data = data.strip_suffix(b"\n").unwrap_or(data);
+
+    for (i, raw) in data.split(|&b| b == b'\n').enumerate() {
+        let line_number = i + 1;
+        let raw = raw.strip_suffix(b"\r").unwrap_or(raw);
+        report.total_lines += 1;
+
+        let line = match std::str::from_utf8(raw) {
+            Ok(l) => l,
+            Err(_) => {
+                report.invalid_utf8.push(line_number);
+                continue;
+            }
+        };
+
+        if crate::grapheme::count(line) > MAX_LINE_LEN {
+            report.too_long.push(line_number);
+        }
+
+        if let Some(chr) = first_untypeable(line, layout) {
+            report.untypeable.push((line_number, chr));
+        }
+
+        valid.push((line_number, line));
+    }
+
+    report.duplicates = duplicates(valid.into_iter());
+    return report;
+}
+
+/// `randword check`: validate the word list and show the findings instead of starting a session.
+pub fn run(path: &str) {
+    let data = match std::fs::read(path) {
+        Ok(d) => d,
+        Err(e) => {
+            crate::message_box(
+                &format!("{}: {}", path, e),
+                "File Open Error",
+                win32::MB_OK | win32::MB_ICONEXCLAMATION,
+            );
+            return;
+        }
+    };
+
+    let report = check_words(&data);
+    let style = if report.is_clean() {
+        win32::MB_OK | win32::MB_ICONINFORMATION
+    } else {
+        win32::MB_OK | win32::MB_ICONEXCLAMATION
+    };
+    crate::message_box(&report.summary(), &format!("Check {}", path), style);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_repeated_lines_in_the_order_they_repeat() {
+        let lines = ["cat", "dog", "dog", "cat", "cow", "cat"];
+        let found = duplicates(lines.iter().enumerate().map(|(i, l)| (i + 1, *l)));
+        let expected = [("dog".to_owned(), vec![2, 3]), ("cat".to_owned(), vec![1, 4, 6])];
+        assert_eq!(found, expected);
+    }
+}
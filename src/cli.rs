@@ -0,0 +1,106 @@
+/// Shown for `--help`; the exe has no console to print it to.
+pub const USAGE: &str = "randword [<command>] [<flags>] [<options>]
+
+Flags:
+  --file, -f <path>    the list to dispense from, instead of ./words.txt
+  --skip <n>           start after the first n lines, instead of the saved position
+  --mode clip|paste|type
+                       put words on the clipboard, paste them with Ctrl+V, or type them
+                       (the default)
+  --hotkey <keys>      dispense with e.g. ctrl+shift+f5 instead of Ctrl+Alt+X
+  --hidden             run without the window; the hotkeys work as before
+  --headless           like --hidden, with a message-only window that has no taskbar button
+                       or menu either, only the tray icon, e.g. for Task Scheduler at logon;
+                       set its \"Start in\" to the folder of the list. `randword quit` ends it
+  --help, -h           show this
+
+Commands: tui, doctor, check, bench, shape, stats, install, uninstall, settings, update,
+quit, task mode|reset|open|pause (what the Jump List tasks run).
+
+Every option of the config files can be given too, e.g. clip, jump, hotkeys=undo,peek or
+list=words.txt; flags are a shorter way to write some of them.";
+
+/// Flags taking a value, and the option each one is written as.
+const VALUE_FLAGS: [(&str, &str); 5] = [
+    ("--file", "list"),
+    ("-f", "list"),
+    ("--skip", "skip"),
+    ("--mode", "mode"),
+    ("--hotkey", "hotkey"),
+];
+
+/// Flags without a value, and their option.
+const SWITCH_FLAGS: [(&str, &str); 2] = [("--hidden", "hidden"), ("--headless", "headless")];
+
+/// Commands handled after the options are read: those that start the app like no command
+/// does, and those that look at the list the options pick.
+const RUN_COMMANDS: [&str; 4] = ["tui", "doctor", "check", "shape"];
+
+pub fn wants_help() -> bool {
+    return std::env::args().skip(1).any(|a| matches!(a.as_str(), "--help" | "-h" | "/?"));
+}
+
+/// The command line with its flags written as options, e.g. `--file words.txt` or
+/// `--file=words.txt` as `list=words.txt`. Options pass through as they are; a leading `tui`,
+/// `doctor`, `check` or `shape` is left out.
+pub fn options(args: impl Iterator<Item = String>) -> anyhow::Result<Vec<String>> {
+    let mut args = args.peekable();
+    if args.peek().is_some_and(|a| RUN_COMMANDS.contains(&a.as_str())) {
+        args.next();
+    }
+    let mut options = Vec::new();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with('-') {
+            options.push(arg);
+            continue;
+        }
+        let (flag, value) = match arg.split_once('=') {
+            Some((flag, value)) => (flag, Some(value.to_owned())),
+            None => (arg.as_str(), None),
+        };
+        if let Some(&(_, name)) = SWITCH_FLAGS.iter().find(|(f, _)| *f == flag) {
+            if value.is_some() {
+                return Err(anyhow::anyhow!("{} takes no value, see randword --help", flag));
+            }
+            options.push(name.to_owned());
+            continue;
+        }
+        let Some(&(_, name)) = VALUE_FLAGS.iter().find(|(f, _)| *f == flag) else {
+            return Err(anyhow::anyhow!("unknown flag `{}`, see randword --help", arg));
+        };
+        let Some(value) = value.or_else(|| args.next()) else {
+            return Err(anyhow::anyhow!("{} needs a value, see randword --help", flag));
+        };
+        options.push(format!("{}={}", name, value));
+    }
+    return Ok(options);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(text: &str) -> impl Iterator<Item = String> + '_ {
+        return text.split_whitespace().map(str::to_owned);
+    }
+
+    #[test]
+    fn writes_flags_as_options() {
+        let options =
+            options(args("tui --file words.txt --skip=3 -f=a.txt --hidden clip")).unwrap();
+        assert_eq!(options, ["list=words.txt", "skip=3", "list=a.txt", "hidden", "clip"]);
+    }
+
+    #[test]
+    fn reads_the_list_of_check_and_shape() {
+        assert_eq!(options(args("check --file a.txt")).unwrap(), ["list=a.txt"]);
+        assert_eq!(options(args("shape list=b.txt")).unwrap(), ["list=b.txt"]);
+    }
+
+    #[test]
+    fn rejects_bad_flags() {
+        assert!(options(args("--nope")).is_err());
+        assert!(options(args("--hidden=yes")).is_err());
+        assert!(options(args("--skip")).is_err());
+    }
+}
@@ -0,0 +1,107 @@
+use crate::win32;
+
+/// How long the word stays on the clipboard with a bare `restoreclip`.
+pub const DEFAULT_RESTORE_SECS: u64 = 5;
+
+/// The user's text, taken off the clipboard when a word went on it.
+struct Saved {
+    /// without the terminating 0
+    text: Vec<u16>,
+    /// `GetClipboardSequenceNumber` once the word was on; anything copied after it changes it
+    sequence: u32,
+    due: std::time::Instant,
+}
+
+/// Set from `restoreclip`, the last one given winning; words are put on the clipboard where no
+/// options are at hand.
+static RESTORE_AFTER: std::sync::Mutex<Option<std::time::Duration>> = std::sync::Mutex::new(None);
+static SAVED: std::sync::Mutex<Option<Saved>> = std::sync::Mutex::new(None);
+
+/// `restoreclip` or `restoreclip=<seconds>`: in clip and paste mode the text that was on the
+/// clipboard comes back this long after a word replaced it, unless something else was copied
+/// meanwhile. Only text is kept; an image or files on the clipboard are lost as before.
+pub fn configure(after: std::time::Duration) {
+    *RESTORE_AFTER.lock().unwrap_or_else(|e| e.into_inner()) = Some(after);
+}
+
+/// Forgets `restoreclip`, before the options are read again for another profile. Text already
+/// saved still comes back.
+pub fn reset() {
+    *RESTORE_AFTER.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// The text on the clipboard, or None if there is none.
+fn read_text() -> Option<Vec<u16>> {
+    unsafe {
+        if win32::OpenClipboard(0) == 0 {
+            return None;
+        }
+        let mut text = None;
+        let handle = win32::GetClipboardData(win32::CF_UNICODETEXT as u32);
+        let data = if handle != 0 {
+            win32::GlobalLock(handle as _) as *const u16
+        } else {
+            std::ptr::null()
+        };
+        if !data.is_null() {
+            // text from another app may lack the terminating 0; no reading past the allocation
+            let size = win32::GlobalSize(handle as _) / 2;
+            let mut len = 0;
+            while len < size && *data.add(len) != 0 {
+                len += 1;
+            }
+            text = Some(std::slice::from_raw_parts(data, len).to_vec());
+            win32::GlobalUnlock(handle as _);
+        }
+        win32::CloseClipboard();
+        return text;
+    }
+}
+
+/// Puts `word` on the clipboard, with `restoreclip` keeping the text that was there. While an
+/// earlier word waits to be replaced, the text saved before it is kept instead.
+pub fn put(word: &str) {
+    let Some(after) = *RESTORE_AFTER.lock().unwrap_or_else(|e| e.into_inner()) else {
+        crate::set_clipboard_string(word);
+        return;
+    };
+    let mut saved = SAVED.lock().unwrap_or_else(|e| e.into_inner());
+    let text = match saved.take() {
+        Some(earlier) => Some(earlier.text),
+        None => read_text(),
+    };
+    crate::set_clipboard_string(word);
+    *saved = text.map(|text| Saved {
+        text,
+        sequence: unsafe { win32::GetClipboardSequenceNumber() },
+        due: std::time::Instant::now() + after,
+    });
+}
+
+fn restore(now: bool) {
+    let mut saved = SAVED.lock().unwrap_or_else(|e| e.into_inner());
+    if !saved.as_ref().is_some_and(|s| now || std::time::Instant::now() >= s.due) {
+        return;
+    }
+    let Some(saved) = saved.take() else {
+        return;
+    };
+    if unsafe { win32::GetClipboardSequenceNumber() } == saved.sequence {
+        crate::set_clipboard_string(&String::from_utf16_lossy(&saved.text));
+    }
+}
+
+/// When the saved text is due to be put back, if there is any.
+pub fn due() -> Option<std::time::Instant> {
+    return SAVED.lock().unwrap_or_else(|e| e.into_inner()).as_ref().map(|s| s.due);
+}
+
+/// Puts the saved text back once it is due; called from the main loop.
+pub fn poll() {
+    restore(false);
+}
+
+/// Puts the saved text back right away, as the app closes.
+pub fn restore_now() {
+    restore(true);
+}
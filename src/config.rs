@@ -0,0 +1,121 @@
+/// Options in a config file are written like on the command line, one per line; `#` and `;`
+/// start a comment. Spaces around the `=` and `[section]` lines are allowed, so the files read
+/// like INI files. A portable `randword.ini` next to the exe comes first, then the
+/// machine-wide file, `%ProgramData%\randword\randword.conf`, then the user's
+/// `%APPDATA%\randword\randword.conf`, then the command line, so later ones win.
+///
+/// A profile picked in the tray, `profiles\<name>.ini`, goes on top of them all.
+///
+/// The machine-wide file may also hold `lock=<name>,<name>`, e.g. `lock=list,mode,statedir`:
+/// those options are then dropped from the other files and the command line, which lets IT
+/// fix the list and mode for a whole lab. `mode`, `clip` and `paste` lock each other.
+const FILE_NAME: &str = "randword\\randword.conf";
+const PORTABLE_FILE_NAME: &str = "randword.ini";
+const PROFILES_DIR: &str = "./profiles";
+
+/// An option and where it was given, for errors: `<file>:<line>:<column>` or `command line`.
+pub struct Arg {
+    pub text: String,
+    pub origin: String,
+}
+
+/// The config file under the folder in the `dir_variable` environment variable.
+pub fn path(dir_variable: &str) -> Option<std::path::PathBuf> {
+    let dir = std::env::var_os(dir_variable)?;
+    return Some(std::path::Path::new(&dir).join(FILE_NAME));
+}
+
+fn portable_path() -> Option<std::path::PathBuf> {
+    return Some(std::env::current_exe().ok()?.with_file_name(PORTABLE_FILE_NAME));
+}
+
+/// `list = words.txt` as `list=words.txt`.
+fn normalize(line: &str) -> String {
+    return match line.split_once('=') {
+        Some((name, value)) => format!("{}={}", name.trim_end(), value.trim_start()),
+        None => line.to_owned(),
+    };
+}
+
+fn read_options(path: Option<std::path::PathBuf>) -> anyhow::Result<Vec<Arg>> {
+    let Some(path) = path else {
+        return Ok(Vec::new());
+    };
+    let text = match std::fs::read_to_string(&path) {
+        Ok(t) => t,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(anyhow::anyhow!("{}: {}", path.display(), e)),
+    };
+    let mut options = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let option = line.trim();
+        if option.is_empty() || option.starts_with(['#', ';', '[']) {
+            continue;
+        }
+        let column = line.len() - line.trim_start().len() + 1;
+        let origin = format!("{}:{}:{}", path.display(), i + 1, column);
+        options.push(Arg { text: normalize(option), origin });
+    }
+    return Ok(options);
+}
+
+/// The profiles in `profiles\`, by name and sorted; none without the folder.
+pub fn profile_names() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(PROFILES_DIR) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("ini")))
+        .filter_map(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+        .collect();
+    names.sort();
+    return names;
+}
+
+/// `list` for both `list` and `list=words.txt`.
+fn option_name(arg: &str) -> &str {
+    return arg.split_once('=').map_or(arg, |(name, _)| name);
+}
+
+/// What locking `name` fixes: options with the same effect lock together, so `lock=clip`
+/// can't be undone with `mode=type` or `paste`.
+fn effect(name: &str) -> &str {
+    return match name {
+        "clip" | "paste" => "mode",
+        name => name,
+    };
+}
+
+/// The portable, machine-wide and user options followed by `command_line` and `profile`,
+/// minus the locked ones.
+pub fn layered(command_line: Vec<String>, profile: Option<&str>) -> anyhow::Result<Vec<Arg>> {
+    let mut machine = Vec::new();
+    let mut locked = Vec::new();
+    for arg in read_options(path("ProgramData"))? {
+        match arg.text.strip_prefix("lock=") {
+            Some(names) => locked.extend(names.split(',').map(|n| effect(n.trim()).to_owned())),
+            None => machine.push(arg),
+        }
+    }
+    let is_allowed = |arg: &Arg| {
+        let name = option_name(&arg.text);
+        name != "lock" && !locked.iter().any(|l| l == effect(name))
+    };
+
+    let mut args: Vec<Arg> =
+        read_options(portable_path())?.into_iter().filter(is_allowed).collect();
+    args.extend(machine);
+    let command_line =
+        command_line.into_iter().map(|text| Arg { text, origin: "command line".to_owned() });
+    let user = read_options(path("APPDATA"))?.into_iter().chain(command_line);
+    args.extend(user.filter(is_allowed));
+    if let Some(name) = profile {
+        let path = std::path::Path::new(PROFILES_DIR).join(format!("{}.ini", name));
+        if !path.is_file() {
+            return Err(anyhow::anyhow!("profile {}: {} is gone", name, path.display()));
+        }
+        args.extend(read_options(Some(path))?.into_iter().filter(is_allowed));
+    }
+    return Ok(args);
+}
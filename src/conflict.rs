@@ -0,0 +1,47 @@
+/// Copies a sync client leaves next to `path` when two machines changed it at the same time:
+/// `skipline (Jiko's conflicted copy 2026-10-15).dat` from Dropbox, `skipline-LAPTOP.dat`
+/// from OneDrive or `skipline (1).dat` from Google Drive.
+pub fn conflicted_copies(path: &str) -> Vec<std::path::PathBuf> {
+    let path = std::path::Path::new(path);
+    let (Some(stem), Some(extension)) =
+        (path.file_stem().and_then(|s| s.to_str()), path.extension().and_then(|s| s.to_str()))
+    else {
+        return Vec::new();
+    };
+    let dir = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => std::path::Path::new("."),
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut copies = Vec::new();
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        let middle = name
+            .strip_prefix(stem)
+            .and_then(|n| n.strip_suffix(extension))
+            .and_then(|n| n.strip_suffix('.'));
+        if middle.is_some_and(|m| m.starts_with(" (") || m.starts_with('-')) {
+            copies.push(entry.path());
+        }
+    }
+    copies.sort();
+    return copies;
+}
+
+/// Last write time of `path`, to notice when a sync client replaced it while randword ran.
+pub fn modified(path: &str) -> Option<std::time::SystemTime> {
+    return std::fs::metadata(path).and_then(|m| m.modified()).ok();
+}
+
+/// Deletes copies whose content was merged into the main file.
+pub fn remove(copies: &[std::path::PathBuf]) {
+    for copy in copies {
+        let _ = std::fs::remove_file(copy);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::win32;
+
+/// Distance in pixels at which a dragged window is pulled onto a screen edge.
+const SNAP_DISTANCE: i32 = 16;
+/// How much of an auto-hidden window stays on screen to be hovered.
+const PEEK: i32 = 4;
+/// Pixels moved per poll while sliding in or out.
+const SLIDE_STEP: i32 = 40;
+
+/// The work area of the monitor `rect` is mostly on.
+pub fn work_area(rect: &win32::RECT) -> win32::RECT {
+    unsafe {
+        let monitor = win32::MonitorFromRect(rect, win32::MONITOR_DEFAULTTONEAREST);
+        let mut info: win32::MONITORINFO = std::mem::zeroed();
+        info.cbSize = std::mem::size_of::<win32::MONITORINFO>() as u32;
+        win32::GetMonitorInfoW(monitor, &mut info);
+        return info.rcWork;
+    }
+}
+
+/// The text caret of the app with the focus, in screen coordinates, if it shows a standard
+/// Win32 one; most edit fields and browsers do.
+pub fn caret() -> Option<win32::RECT> {
+    let mut info: win32::GUITHREADINFO = unsafe { std::mem::zeroed() };
+    info.cbSize = std::mem::size_of::<win32::GUITHREADINFO>() as u32;
+    if unsafe { win32::GetGUIThreadInfo(0, &mut info) } == 0 || info.hwndCaret == 0 {
+        return None;
+    }
+    let caret = info.rcCaret;
+    let mut top_left = win32::POINT { x: caret.left, y: caret.top };
+    unsafe { win32::ClientToScreen(info.hwndCaret, &mut top_left) };
+    return Some(win32::RECT {
+        left: top_left.x,
+        top: top_left.y,
+        right: top_left.x + caret.right - caret.left,
+        bottom: top_left.y + caret.bottom - caret.top,
+    });
+}
+
+/// Called from `WM_MOVING`: moves the proposed window rect onto any work-area edge it is near.
+pub fn snap(rect: &mut win32::RECT) {
+    let work = work_area(rect);
+    let width = rect.right - rect.left;
+    let height = rect.bottom - rect.top;
+
+    if (rect.left - work.left).abs() < SNAP_DISTANCE {
+        rect.left = work.left;
+    } else if (work.right - rect.right).abs() < SNAP_DISTANCE {
+        rect.left = work.right - width;
+    }
+    if (rect.top - work.top).abs() < SNAP_DISTANCE {
+        rect.top = work.top;
+    } else if (work.bottom - rect.bottom).abs() < SNAP_DISTANCE {
+        rect.top = work.bottom - height;
+    }
+    rect.right = rect.left + width;
+    rect.bottom = rect.top + height;
+}
+
+/// `autohide`: a window docked to a screen edge slides off it, leaving a `PEEK` strip,
+/// until the cursor comes back over it. Moves one step per call, so it is called every poll.
+pub fn auto_hide(h_window: win32::HWND) {
+    let mut rect: win32::RECT = unsafe { std::mem::zeroed() };
+    let mut cursor: win32::POINT = unsafe { std::mem::zeroed() };
+    unsafe {
+        if win32::GetWindowRect(h_window, &mut rect) == 0 || win32::GetCursorPos(&mut cursor) == 0 {
+            return;
+        }
+    }
+    let work = work_area(&rect);
+    let width = rect.right - rect.left;
+    let height = rect.bottom - rect.top;
+    let hovered = cursor.x >= rect.left
+        && cursor.x < rect.right
+        && cursor.y >= rect.top
+        && cursor.y < rect.bottom;
+
+    // current, shown and hidden positions along the axis of the docked edge
+    let (current, shown, hidden, horizontal) = if rect.left <= work.left {
+        (rect.left, work.left, work.left - width + PEEK, true)
+    } else if rect.right >= work.right {
+        (rect.left, work.right - width, work.right - PEEK, true)
+    } else if rect.top <= work.top {
+        (rect.top, work.top, work.top - height + PEEK, false)
+    } else if rect.bottom >= work.bottom {
+        (rect.top, work.bottom - height, work.bottom - PEEK, false)
+    } else {
+        return;
+    };
+
+    let target = if hovered { shown } else { hidden };
+    if current == target {
+        return;
+    }
+    let next = current + (target - current).clamp(-SLIDE_STEP, SLIDE_STEP);
+    let (x, y) = if horizontal { (next, rect.top) } else { (rect.left, next) };
+    unsafe {
+        win32::SetWindowPos(
+            h_window,
+            0,
+            x,
+            y,
+            0,
+            0,
+            win32::SWP_NOSIZE | win32::SWP_NOZORDER | win32::SWP_NOACTIVATE,
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use crate::win32;
+
+/// Typed into the test window to see whether injected input arrives.
+const PROBE_TEXT: &str = "randword";
+
+/// One line of the report.
+fn line(out: &mut String, ok: bool, text: &str) {
+    out.push_str(&format!("\n{}  {}", if ok { "ok " } else { "!! " }, text));
+}
+
+fn check_hotkeys(out: &mut String, options: &crate::Options) {
+    let mut hotkeys = vec![(options.dispense_keys, "dispense the next word".to_owned())];
+    for (keys, path, _) in &options.bindings {
+        hotkeys.push((*keys, format!("dispense from {}", path)));
+    }
+    for (_, keys, id, purpose) in crate::HOTKEY_EXTRAS {
+        if options.extra_hotkeys.contains(&id) {
+            hotkeys.push((keys, purpose.to_owned()));
+        }
+    }
+    for (keys, purpose) in hotkeys {
+        let free = keys.is_free(0);
+        let state = if free { "can be registered" } else { "is taken" };
+        line(out, free, &format!("{} ({}) {}", keys.name(), purpose, state));
+    }
+}
+
+fn check_clipboard(out: &mut String) {
+    let opened = unsafe { win32::OpenClipboard(0) } != 0;
+    if opened {
+        unsafe { win32::CloseClipboard() };
+        line(out, true, "The clipboard can be opened");
+    } else {
+        let error_code = unsafe { win32::GetLastError() };
+        let error = crate::format_win32_error(error_code);
+        line(out, false, &format!("The clipboard can't be opened: {}", error.trim_end()));
+    }
+}
+
+fn check_input(out: &mut String) {
+    let h_window = match crate::bench::open_test_window() {
+        Ok(h) => h,
+        Err(error_code) => {
+            let error = crate::format_win32_error(error_code);
+            line(out, false, &format!("No test window for typing: {}", error.trim_end()));
+            return;
+        }
+    };
+    let mut inputs = Vec::new();
+    for chr in PROBE_TEXT.chars() {
+        crate::push_unicode(&mut inputs, chr);
+    }
+    let sent = crate::send_inputs(&inputs) as usize;
+    let (text, _) = crate::bench::settle(h_window, std::time::Instant::now());
+    unsafe { win32::DestroyWindow(h_window) };
+    if sent < inputs.len() {
+        line(
+            out,
+            false,
+            "SendInput was blocked; an elevated window in front or security software can do that",
+        );
+    } else if text != PROBE_TEXT {
+        line(out, false, &format!("Typed `{}` into a test window, it got `{}`", PROBE_TEXT, text));
+    } else {
+        line(out, true, "Typing into a test window works");
+    }
+}
+
+/// Opens `path` for appending without changing it; a file that wasn't there is removed again.
+fn is_writable(path: &str) -> std::io::Result<()> {
+    let existed = std::path::Path::new(path).exists();
+    std::fs::OpenOptions::new().append(true).create(true).open(path)?;
+    if !existed {
+        std::fs::remove_file(path)?;
+    }
+    return Ok(());
+}
+
+fn check_state_files(out: &mut String, options: &crate::Options) {
+    let mut paths =
+        vec![options.state_path(crate::STATS_PATH), options.state_path(crate::LIST_TIMES_PATH)];
+    match &options.state_dir {
+        Some(dir) => match crate::shared::SharedCursor::new(dir).with_lock(|_| ()) {
+            Ok(()) => line(out, true, &format!("The shared position in {} can be locked", dir)),
+            Err(e) => line(out, false, &format!("The shared position in {}: {}", dir, e)),
+        },
+        None => {
+            let positions = options.state_path(crate::positions::PATH);
+            let path = options.skipline.clone().unwrap_or(positions);
+            paths.insert(0, path);
+        }
+    }
+    for path in paths {
+        match is_writable(&path) {
+            Ok(()) => line(out, true, &format!("{} can be written", path)),
+            Err(e) => line(out, false, &format!("{}: {}", path, e)),
+        }
+    }
+}
+
+fn check_list(out: &mut String, options: &crate::Options) {
+    let path = options.words_path();
+    match crate::list::WordList::open(path, 0) {
+        Ok(list) if list.total == 0 => line(out, false, &format!("{} is empty", path)),
+        Ok(list) => line(out, true, &format!("{} has {} lines", path, list.total)),
+        Err(e) => line(out, false, &format!("{}: {}", path, e)),
+    }
+}
+
+/// `randword doctor`: checks what randword needs from the system, with the options given,
+/// since most problems are in the environment rather than the app.
+pub fn run(options: &crate::Options) {
+    let mut out = String::from("Hotkeys");
+    check_hotkeys(&mut out, options);
+    out.push_str("\n\nClipboard and input");
+    check_clipboard(&mut out);
+    check_input(&mut out);
+    out.push_str("\n\nFiles");
+    check_list(&mut out, options);
+    check_state_files(&mut out, options);
+    if crate::shell::find_running() != 0 {
+        out.push_str("\n\nAnother randword is running, the hotkeys it holds show up as taken.");
+    }
+    crate::message_box(&out, "randword doctor", win32::MB_OK | win32::MB_ICONINFORMATION);
+}
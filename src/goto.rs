@@ -0,0 +1,128 @@
+use crate::win32;
+
+const WIDTH: i32 = 260;
+const HEIGHT: i32 = 90;
+const MARGIN: i32 = 10;
+const EDIT_HEIGHT: i32 = 24;
+
+/// The number typed into `h_edit`, if it is a line of the list.
+fn typed_line(h_edit: win32::HWND, total: u64) -> Option<u64> {
+    let mut text = [0u16; 24];
+    let len = unsafe { win32::GetWindowTextW(h_edit, text.as_mut_ptr(), text.len() as i32) };
+    let line = String::from_utf16_lossy(&text[..len.max(0) as usize]).trim().parse().ok()?;
+    return (1..=total).contains(&line).then_some(line);
+}
+
+/// A small dialog asking which line comes next, starting out with `next`. Returns the line,
+/// counted from 1, or None on Esc, on closing it or when it loses the focus. The app that had
+/// the focus gets it back either way. Closing `owner`, the randword window, ends it too.
+pub fn ask(owner: win32::HWND, next: u64, total: u64) -> Option<u64> {
+    if total == 0 {
+        return None;
+    }
+    let previous = unsafe { win32::GetForegroundWindow() };
+    let mut cursor = win32::POINT { x: 0, y: 0 };
+    unsafe { win32::GetCursorPos(&mut cursor) };
+    // centered on the screen the cursor is on
+    let work = crate::dock::work_area(&win32::RECT {
+        left: cursor.x,
+        top: cursor.y,
+        right: cursor.x,
+        bottom: cursor.y,
+    });
+    let x = (work.left + work.right - WIDTH) / 2;
+    let y = (work.top + work.bottom - HEIGHT) / 2;
+
+    let title: Vec<u16> =
+        format!("Go to line (1-{})", total).encode_utf16().chain(std::iter::once(0)).collect();
+    let h_dialog = unsafe {
+        win32::CreateWindowExW(
+            win32::WS_EX_TOPMOST | win32::WS_EX_TOOLWINDOW,
+            win32::w!("STATIC"),
+            title.as_ptr(),
+            win32::WS_POPUP | win32::WS_CAPTION | win32::WS_SYSMENU | win32::WS_VISIBLE,
+            x,
+            y,
+            WIDTH,
+            HEIGHT,
+            0,
+            0,
+            0,
+            std::ptr::null(),
+        )
+    };
+    if h_dialog == 0 {
+        return None;
+    }
+    let mut client: win32::RECT = unsafe { std::mem::zeroed() };
+    unsafe { win32::GetClientRect(h_dialog, &mut client) };
+    let text: Vec<u16> = next.to_string().encode_utf16().chain(std::iter::once(0)).collect();
+    let h_edit = unsafe {
+        win32::CreateWindowExW(
+            0,
+            win32::w!("EDIT"),
+            text.as_ptr(),
+            win32::WS_CHILD
+                | win32::WS_VISIBLE
+                | win32::WS_BORDER
+                | win32::ES_NUMBER as u32
+                | win32::ES_AUTOHSCROLL as u32,
+            MARGIN,
+            (client.bottom - EDIT_HEIGHT) / 2,
+            client.right - 2 * MARGIN,
+            EDIT_HEIGHT,
+            h_dialog,
+            0,
+            0,
+            std::ptr::null(),
+        )
+    };
+
+    let line = unsafe {
+        if h_edit != 0 && win32::SetForegroundWindow(h_dialog) != 0 {
+            let font = win32::GetStockObject(win32::DEFAULT_GUI_FONT);
+            win32::SendMessageW(h_edit, win32::WM_SETFONT, font as usize, 0);
+            win32::SendMessageW(h_edit, win32::EM_SETSEL, 0, -1);
+            win32::SetFocus(h_edit);
+            run(owner, h_dialog, h_edit, total)
+        } else {
+            None
+        }
+    };
+    unsafe {
+        win32::DestroyWindow(h_dialog);
+        win32::SetForegroundWindow(previous);
+    }
+    return line;
+}
+
+/// Handles the dialog's input until a line is entered or it is given up. Only its own
+/// messages are taken from the queue, hotkeys wait for afterwards.
+fn run(owner: win32::HWND, h_dialog: win32::HWND, h_edit: win32::HWND, total: u64) -> Option<u64> {
+    loop {
+        for h_window in [h_dialog, h_edit] {
+            let mut msg: win32::MSG = unsafe { std::mem::zeroed() };
+            while unsafe { win32::PeekMessageW(&mut msg, h_window, 0, 0, win32::PM_REMOVE) } != 0 {
+                if msg.message == win32::WM_KEYDOWN && msg.wParam == win32::VK_ESCAPE as usize {
+                    return None;
+                }
+                if msg.message == win32::WM_KEYDOWN && msg.wParam == win32::VK_RETURN as usize {
+                    if let Some(line) = typed_line(h_edit, total) {
+                        return Some(line);
+                    }
+                    // not a line of the list; select it to type over
+                    unsafe { win32::SendMessageW(h_edit, win32::EM_SETSEL, 0, -1) };
+                    continue;
+                }
+                unsafe {
+                    win32::TranslateMessage(&msg);
+                    win32::DispatchMessageW(&msg);
+                }
+            }
+        }
+        if !crate::is_running(owner) || unsafe { win32::GetForegroundWindow() } != h_dialog {
+            return None;
+        }
+        unsafe { win32::Sleep(10) };
+    }
+}
@@ -0,0 +1,31 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Splits `text` where a reader sees one char end and the next begin, the extended grapheme
+/// clusters of Unicode: a letter stays with its combining marks, an emoji with its skin tone
+/// and the ones it is joined to with ZWJ, and a flag or a Hangul syllable in jamo is one.
+pub fn clusters(text: &str) -> Vec<&str> {
+    return text.graphemes(true).collect();
+}
+
+/// The length of `text` as a reader counts it.
+pub fn count(text: &str) -> usize {
+    return clusters(text).len();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_what_reads_as_one_char_together() {
+        // e with a combining acute accent
+        assert_eq!(clusters("cafe\u{301}!"), ["c", "a", "f", "e\u{301}", "!"]);
+        // a thumbs up with a skin tone, a family joined with ZWJ, two flags
+        let thumb = "\u{1F44D}\u{1F3FD}";
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        let flags = "\u{1F1E9}\u{1F1EA}\u{1F1EB}\u{1F1F7}";
+        assert_eq!(count(&format!("{}{}{}", thumb, family, flags)), 4);
+        assert_eq!(clusters(thumb), [thumb]);
+        assert_eq!(count(""), 0);
+    }
+}
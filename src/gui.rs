@@ -0,0 +1,257 @@
+use native_windows_gui as nwg;
+
+use crate::win32;
+
+/// How many words the history tab lists.
+const HISTORY_COUNT: usize = 100;
+/// The options the Settings tab has a field for, with their labels; the rest of the config
+/// file goes in the box under them as it is.
+const FIELDS: [(&str, &str); 3] =
+    [("list", "Word file"), ("hotkey", "Hotkey"), ("delay", "Delay (ms)")];
+const MODES: [&str; 3] = ["type", "clip", "paste"];
+
+/// The config file as the Settings tab shows it.
+struct Settings {
+    /// the values of `FIELDS`, empty when the file doesn't set them
+    fields: [String; 3],
+    /// into `MODES`; None when the file doesn't set it
+    mode: Option<usize>,
+    other: String,
+}
+
+impl Settings {
+    /// Takes the options with a field out of `config`; the last one given wins, as when the
+    /// file is read.
+    fn parse(config: &str) -> Self {
+        let mut settings = Self { fields: Default::default(), mode: None, other: String::new() };
+        for line in config.lines() {
+            let option = line.trim();
+            let field = option.split_once('=').and_then(|(name, value)| {
+                let i = FIELDS.iter().position(|&(field, _)| field == name.trim_end())?;
+                Some((i, value.trim_start()))
+            });
+            let mode = match option.split_once('=') {
+                Some((name, value)) if name.trim_end() == "mode" => Some(value.trim_start()),
+                // `clip` and `paste` alone set it too
+                None if option == "clip" || option == "paste" => Some(option),
+                _ => None,
+            };
+            if let Some((i, value)) = field {
+                settings.fields[i] = value.to_owned();
+            } else if let Some(i) = mode.and_then(|m| MODES.iter().position(|&mode| mode == m)) {
+                settings.mode = Some(i);
+            } else {
+                settings.other.push_str(line);
+                settings.other.push('\n');
+            }
+        }
+        return settings;
+    }
+
+    /// The fields that are filled in, checked, then the other options.
+    fn text(&self) -> Result<String, String> {
+        let mut text = String::new();
+        for ((name, _), value) in FIELDS.iter().zip(&self.fields) {
+            let value = value.trim();
+            if value.is_empty() {
+                continue;
+            }
+            match *name {
+                "hotkey" => {
+                    crate::hotkey::Hotkey::parse(value).map_err(|e| e.to_string())?;
+                }
+                "delay" => {
+                    if !value.parse().is_ok_and(|ms: u32| ms <= crate::MAX_DELAY_MS) {
+                        return Err(format!(
+                            "Delay: expected 0 to {} milliseconds, got `{}`",
+                            crate::MAX_DELAY_MS,
+                            value
+                        ));
+                    }
+                }
+                _ => {}
+            }
+            text.push_str(&format!("{}={}\n", name, value));
+        }
+        if let Some(mode) = self.mode {
+            text.push_str(&format!("mode={}\n", MODES[mode]));
+        }
+        text.push_str(&self.other);
+        return Ok(text);
+    }
+}
+
+fn error(e: &dyn std::fmt::Display) {
+    crate::message_box(&e.to_string(), "Settings Error", win32::MB_OK | win32::MB_ICONEXCLAMATION);
+}
+
+fn stats_view(
+    parent: &nwg::Tab,
+    columns: &[&str],
+    rows: &[[String; 3]],
+) -> Result<nwg::ListView, nwg::NwgError> {
+    let mut view = nwg::ListView::default();
+    nwg::ListView::builder()
+        .list_style(nwg::ListViewStyle::Detailed)
+        .ex_flags(nwg::ListViewExFlags::GRID | nwg::ListViewExFlags::FULL_ROW_SELECT)
+        .position((5, 5))
+        .size((450, 310))
+        .parent(parent)
+        .build(&mut view)?;
+    view.set_headers_enabled(true);
+    for column in columns {
+        view.insert_column(*column);
+    }
+    for row in rows {
+        view.insert_items_row(None, &row[..]);
+    }
+    return Ok(view);
+}
+
+/// `randword settings`, built with the `gui` feature: a window with the user config file to
+/// edit, the word file, hotkey, mode and delay in fields of their own, the most drilled words
+/// and the recently dispensed ones. The dispensing window stays
+/// the plain Win32 one.
+pub fn run() {
+    if let Err(e) = show() {
+        error(&e);
+    }
+}
+
+fn show() -> Result<(), nwg::NwgError> {
+    let stats = match crate::stats::Stats::load(crate::STATS_PATH) {
+        Ok(s) => s,
+        Err(e) => {
+            error(&format!("{}: {}", crate::STATS_PATH, e));
+            return Ok(());
+        }
+    };
+    let config_path = crate::config::path("APPDATA");
+    let config = match &config_path {
+        Some(path) => std::fs::read_to_string(path).unwrap_or_default(),
+        None => String::new(),
+    };
+
+    nwg::init()?;
+    nwg::Font::set_global_family("Segoe UI")?;
+
+    let mut window = nwg::Window::default();
+    nwg::Window::builder().size((480, 400)).title("randword").build(&mut window)?;
+    let mut tabs = nwg::TabsContainer::default();
+    nwg::TabsContainer::builder()
+        .position((5, 5))
+        .size((470, 390))
+        .parent(&window)
+        .build(&mut tabs)?;
+
+    let mut settings_tab = nwg::Tab::default();
+    nwg::Tab::builder().text("Settings").parent(&tabs).build(&mut settings_tab)?;
+    let current = Settings::parse(&config);
+    let mut labels = Vec::new();
+    let mut label = |text: &str, y: i32| -> Result<(), nwg::NwgError> {
+        let mut label = nwg::Label::default();
+        nwg::Label::builder()
+            .text(text)
+            .position((5, y + 3))
+            .size((100, 25))
+            .parent(&settings_tab)
+            .build(&mut label)?;
+        labels.push(label);
+        return Ok(());
+    };
+    let mut fields: [nwg::TextInput; 3] = Default::default();
+    for (i, ((_, text), field)) in FIELDS.iter().zip(&mut fields).enumerate() {
+        let y = 5 + 30 * i as i32;
+        label(text, y)?;
+        nwg::TextInput::builder()
+            .text(&current.fields[i])
+            .position((110, y))
+            .size((345, 25))
+            .parent(&settings_tab)
+            .build(field)?;
+    }
+    label("Mode", 95)?;
+    let mut mode = nwg::ComboBox::default();
+    nwg::ComboBox::builder()
+        .collection(MODES.to_vec())
+        .selected_index(current.mode)
+        .position((110, 95))
+        .size((345, 25))
+        .parent(&settings_tab)
+        .build(&mut mode)?;
+    label("Other options", 125)?;
+    let mut other = nwg::TextBox::default();
+    nwg::TextBox::builder()
+        .text(&current.other.replace('\n', "\r\n"))
+        .position((5, 150))
+        .size((450, 125))
+        .parent(&settings_tab)
+        .build(&mut other)?;
+    let mut save = nwg::Button::default();
+    nwg::Button::builder()
+        .text("Save")
+        .position((355, 285))
+        .size((100, 30))
+        .parent(&settings_tab)
+        .build(&mut save)?;
+
+    let mut drilled_tab = nwg::Tab::default();
+    nwg::Tab::builder().text("Most drilled").parent(&tabs).build(&mut drilled_tab)?;
+    let mut words = stats.most_drilled();
+    let rows: Vec<_> = words
+        .iter()
+        .map(|(word, s)| [s.times.to_string(), format!("{:+}", s.rating), word.to_string()])
+        .collect();
+    let _drilled = stats_view(&drilled_tab, &["Times", "Rating", "Word"], &rows)?;
+
+    let mut history_tab = nwg::Tab::default();
+    nwg::Tab::builder().text("History").parent(&tabs).build(&mut history_tab)?;
+    words.retain(|(_, s)| !s.last.is_empty());
+    words.sort_by(|a, b| b.1.last.cmp(&a.1.last));
+    let rows: Vec<_> = words
+        .iter()
+        .take(HISTORY_COUNT)
+        .map(|(word, s)| [s.last.clone(), s.times.to_string(), word.to_string()])
+        .collect();
+    let _history = stats_view(&history_tab, &["Last", "Times", "Word"], &rows)?;
+
+    let handler = {
+        let window = window.handle;
+        let save = save.handle;
+        nwg::full_bind_event_handler(&window, move |event, _, handle| match event {
+            nwg::Event::OnWindowClose if handle == window => nwg::stop_thread_dispatch(),
+            nwg::Event::OnButtonClick if handle == save => {
+                let Some(path) = &config_path else {
+                    return error(&"APPDATA is not set");
+                };
+                let settings = Settings {
+                    fields: [fields[0].text(), fields[1].text(), fields[2].text()],
+                    mode: mode.selection(),
+                    other: other.text().replace("\r\n", "\n"),
+                };
+                let text = match settings.text() {
+                    Ok(text) => text,
+                    Err(e) => return error(&e),
+                };
+                let written = match path.parent() {
+                    Some(dir) => {
+                        std::fs::create_dir_all(dir).and_then(|_| std::fs::write(path, text))
+                    }
+                    None => std::fs::write(path, text),
+                };
+                match written {
+                    Ok(()) => crate::message_box(
+                        "Saved, restart randword to use the new settings",
+                        "Settings",
+                        win32::MB_OK | win32::MB_ICONINFORMATION,
+                    ),
+                    Err(e) => error(&format!("{}: {}", path.display(), e)),
+                }
+            }
+            _ => {}
+        })
+    };
+    nwg::dispatch_thread_events();
+    nwg::unbind_event_handler(&handler);
+    return Ok(());
+}
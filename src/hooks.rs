@@ -0,0 +1,47 @@
+use std::io::Write;
+
+/// `before=<command>` / `after=<command>`: a command run through `cmd /C` around every
+/// dispense. It gets the word on stdin and in `RANDWORD_WORD`, its line number in
+/// `RANDWORD_LINE` and in `RANDWORD_MODE` how it went out, the mode the session records:
+/// `type`, `clip`, `paste`, `speak`, `spell`, `nato`, or `file` with only `out=<file>`.
+///
+/// The before command is waited for, so its side effects land ahead of the word; the after
+/// command runs on its own.
+pub struct Hook {
+    command: String,
+}
+
+impl Hook {
+    pub fn new(command: &str) -> Self {
+        return Self { command: command.to_owned() };
+    }
+
+    fn spawn(&self, word: &str, line: u64, mode: &str) -> std::io::Result<std::process::Child> {
+        use std::os::windows::process::CommandExt;
+
+        let mut child = std::process::Command::new("cmd")
+            .arg("/C")
+            .raw_arg(&self.command)
+            .env("RANDWORD_WORD", word)
+            .env("RANDWORD_LINE", line.to_string())
+            .env("RANDWORD_MODE", mode)
+            .stdin(std::process::Stdio::piped())
+            .creation_flags(crate::CREATE_NO_WINDOW)
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // a command that doesn't read stdin closes it early, that is fine
+            let _ = writeln!(stdin, "{}", word);
+        }
+        return Ok(child);
+    }
+
+    pub fn run(&self, word: &str, line: u64, mode: &str) -> std::io::Result<()> {
+        self.spawn(word, line, mode)?.wait()?;
+        return Ok(());
+    }
+
+    pub fn start(&self, word: &str, line: u64, mode: &str) -> std::io::Result<()> {
+        self.spawn(word, line, mode)?;
+        return Ok(());
+    }
+}
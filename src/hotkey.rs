@@ -0,0 +1,162 @@
+use crate::win32;
+
+/// Id used to probe whether a combination is free; never kept registered.
+const PROBE_ID: i32 = 0xbfff;
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Hotkey {
+    pub modifiers: u32,
+    pub key: u32,
+}
+
+/// Combinations commonly taken by Windows itself or by drivers shipped with it.
+const KNOWN_OWNERS: &[(u32, u32, &str)] = &[
+    (win32::MOD_CONTROL | win32::MOD_ALT, win32::VK_DELETE as u32, "Windows security screen"),
+    (win32::MOD_CONTROL | win32::MOD_SHIFT, win32::VK_ESCAPE as u32, "Task Manager"),
+    (win32::MOD_CONTROL | win32::MOD_ALT, win32::VK_UP as u32, "Intel graphics screen rotation"),
+    (win32::MOD_CONTROL | win32::MOD_ALT, win32::VK_DOWN as u32, "Intel graphics screen rotation"),
+    (win32::MOD_CONTROL | win32::MOD_ALT, win32::VK_LEFT as u32, "Intel graphics screen rotation"),
+    (win32::MOD_CONTROL | win32::MOD_ALT, win32::VK_RIGHT as u32, "Intel graphics screen rotation"),
+];
+
+impl Hotkey {
+    pub const fn ctrl_alt(key: u8) -> Self {
+        return Self { modifiers: win32::MOD_CONTROL | win32::MOD_ALT, key: key as u32 };
+    }
+
+    /// `ctrl+alt+x`, `Win+Shift+W`, `ctrl+shift+f5` and the like: modifiers, then a letter, a
+    /// digit or a key name as in `{...}` tokens. Case doesn't matter.
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+        let key = parts.pop().unwrap_or_default();
+        let mut modifiers = 0;
+        for part in parts {
+            modifiers |= match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => win32::MOD_CONTROL,
+                "alt" => win32::MOD_ALT,
+                "shift" => win32::MOD_SHIFT,
+                "win" | "windows" => win32::MOD_WIN,
+                _ => return Err(anyhow::anyhow!("hotkey: unknown modifier `{}`", part)),
+            };
+        }
+        if modifiers == 0 {
+            return Err(anyhow::anyhow!("hotkey: `{}` needs at least one modifier", text));
+        }
+        let key = match key.as_bytes() {
+            [c] if c.is_ascii_alphanumeric() => c.to_ascii_uppercase() as u32,
+            _ => match crate::tokens::key(key) {
+                Some(vkey) => vkey as u32,
+                None => return Err(anyhow::anyhow!("hotkey: unknown key `{}`", key)),
+            },
+        };
+        return Ok(Self { modifiers, key });
+    }
+
+    pub fn register(&self, h_window: win32::HWND, id: i32) -> bool {
+        return unsafe { win32::RegisterHotKey(h_window, id, self.modifiers, self.key) } != 0;
+    }
+
+    pub fn name(&self) -> String {
+        let mut name = String::new();
+        for (flag, label) in [
+            (win32::MOD_CONTROL, "Ctrl+"),
+            (win32::MOD_ALT, "Alt+"),
+            (win32::MOD_SHIFT, "Shift+"),
+            (win32::MOD_WIN, "Win+"),
+        ] {
+            if self.modifiers & flag != 0 {
+                name.push_str(label);
+            }
+        }
+        match self.key {
+            // the virtual-key codes of digits and letters are their ASCII values
+            0x30..=0x39 | 0x41..=0x5a => name.push(self.key as u8 as char),
+            k if (win32::VK_F1 as u32..=win32::VK_F24 as u32).contains(&k) => {
+                name.push_str(&format!("F{}", k - win32::VK_F1 as u32 + 1));
+            }
+            k => match crate::tokens::key_name(k as u16) {
+                Some(key) => name.push_str(key),
+                None => name.push_str(&format!("VK 0x{:02X}", k)),
+            },
+        }
+        return name;
+    }
+
+    pub fn is_free(&self, h_window: win32::HWND) -> bool {
+        if !self.register(h_window, PROBE_ID) {
+            return false;
+        }
+        unsafe { win32::UnregisterHotKey(h_window, PROBE_ID) };
+        return true;
+    }
+
+    /// The same key with other modifiers, to suggest or fall back to when `self` is taken.
+    pub fn alternatives(&self) -> impl Iterator<Item = Self> + '_ {
+        return [
+            self.modifiers | win32::MOD_SHIFT,
+            win32::MOD_CONTROL | win32::MOD_SHIFT,
+            win32::MOD_ALT | win32::MOD_SHIFT,
+            win32::MOD_CONTROL | win32::MOD_WIN,
+        ]
+        .into_iter()
+        .map(|modifiers| Self { modifiers, key: self.key })
+        .filter(move |candidate| candidate != self);
+    }
+
+    /// Builds the text shown when registering `self` failed with `error_code`.
+    pub fn diagnose(&self, h_window: win32::HWND, error_code: u32, purpose: &str) -> String {
+        let mut out = format!(
+            "Unable to register {} ({}).\n\n{}\n",
+            self.name(),
+            purpose,
+            crate::format_win32_error(error_code).trim_end()
+        );
+
+        let known = KNOWN_OWNERS
+            .iter()
+            .find(|&&(modifiers, key, _)| modifiers == self.modifiers && key == self.key);
+        match known {
+            Some((_, _, owner)) => {
+                out.push_str(&format!("\nThis combination belongs to: {}.\n", owner));
+            }
+            None => out.push_str(
+                "\nLikely held by another running program: a second randword instance, \
+                 a clipboard manager, a screenshot tool or a graphics driver utility.\n",
+            ),
+        }
+
+        let alternatives: Vec<String> = self
+            .alternatives()
+            .filter(|candidate| candidate.is_free(h_window))
+            .map(|candidate| candidate.name())
+            .collect();
+        if alternatives.is_empty() {
+            out.push_str("\nNo free alternative with the same key was found.");
+        } else {
+            out.push_str(&format!("\nFree right now: {}", alternatives.join(", ")));
+        }
+        return out;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_modifiers_and_keys() {
+        let hotkey = Hotkey::parse("Ctrl + shift+f5").unwrap();
+        assert_eq!(hotkey.modifiers, win32::MOD_CONTROL | win32::MOD_SHIFT);
+        assert_eq!(hotkey.key, (win32::VK_F1 + 4) as u32);
+        assert!(Hotkey::parse("ctrl+alt+x").unwrap() == Hotkey::ctrl_alt(b'X'));
+        assert_eq!(Hotkey::parse("win+tab").unwrap().key, win32::VK_TAB as u32);
+    }
+
+    #[test]
+    fn rejects_bad_hotkeys() {
+        assert!(Hotkey::parse("x").is_err());
+        assert!(Hotkey::parse("hyper+x").is_err());
+        assert!(Hotkey::parse("ctrl+nokey").is_err());
+        assert!(Hotkey::parse("ctrl+").is_err());
+    }
+}
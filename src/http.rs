@@ -0,0 +1,116 @@
+use std::io::{Read, Write};
+
+/// How long a client gets to send its request head before it is dropped.
+const READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+const MAX_REQUEST: usize = 8 * 1024;
+
+/// `serve=<port>`: a small HTTP listener on 127.0.0.1 for scripts and dashboards. A worker
+/// thread accepts and reads the requests, so a slow client never holds up the main loop,
+/// which answers them between messages.
+pub struct Listener {
+    requests: std::sync::mpsc::Receiver<Request>,
+}
+
+pub struct Request {
+    stream: std::net::TcpStream,
+    pub method: String,
+    pub path: String,
+}
+
+impl Listener {
+    pub fn bind(port: u16) -> std::io::Result<Self> {
+        let socket = std::net::TcpListener::bind(("127.0.0.1", port))?;
+        let (sender, requests) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for stream in socket.incoming() {
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                // the main loop is gone once nobody receives
+                if let Some(request) = read_request(stream, port) {
+                    if sender.send(request).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+        return Ok(Self { requests });
+    }
+
+    /// The next request read, if any. Never blocks.
+    pub fn accept(&self) -> Option<Request> {
+        return self.requests.try_recv().ok();
+    }
+}
+
+/// The request on `stream`, or None if it is malformed, too slow or too big. One whose Host
+/// isn't this listener is refused: a web page could otherwise reach it through a name that
+/// resolves to 127.0.0.1 (DNS rebinding).
+fn read_request(mut stream: std::net::TcpStream, port: u16) -> Option<Request> {
+    stream.set_read_timeout(Some(READ_TIMEOUT)).ok()?;
+    let mut buffer = Vec::with_capacity(512);
+    let mut chunk = [0_u8; 512];
+    while !buffer.windows(4).any(|w| w == b"\r\n\r\n") {
+        let read = stream.read(&mut chunk).ok()?;
+        if read == 0 || buffer.len() + read > MAX_REQUEST {
+            return None;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+
+    let head = String::from_utf8_lossy(&buffer);
+    let mut lines = head.lines();
+    let mut request_line = lines.next()?.split(' ');
+    let method = request_line.next()?.to_owned();
+    let target = request_line.next()?;
+    // the query string is not used by any endpoint
+    let path = target.split('?').next().unwrap_or(target).to_owned();
+    let request = Request { stream, method, path };
+
+    let host = lines
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("host"))
+        .map(|(_, value)| value.trim());
+    let allowed = [format!("127.0.0.1:{}", port), format!("localhost:{}", port)];
+    if !host.is_some_and(|host| allowed.iter().any(|a| a.eq_ignore_ascii_case(host))) {
+        request.respond("403 Forbidden", "text/plain", "forbidden host\n");
+        return None;
+    }
+    return Some(request);
+}
+
+impl Request {
+    pub fn respond(mut self, status: &str, content_type: &str, body: &str) {
+        let _ = write!(
+            self.stream,
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            content_type,
+            body.len(),
+            body
+        );
+    }
+
+    pub fn not_found(self) {
+        self.respond("404 Not Found", "text/plain", "not found\n");
+    }
+}
+
+/// `text` as a quoted JSON string.
+pub fn json_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    return out;
+}
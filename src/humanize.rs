@@ -0,0 +1,60 @@
+struct Humanize {
+    min_ms: u64,
+    max_ms: u64,
+    /// up to this much more after a space, as between words
+    pause_ms: u64,
+    rng: crate::list::Rng,
+}
+
+/// Set from `humanize=`, the last one given winning; the typing code has no options at hand.
+static HUMANIZE: std::sync::Mutex<Option<Humanize>> = std::sync::Mutex::new(None);
+
+fn parse_ms(text: &str) -> anyhow::Result<u64> {
+    return text
+        .trim()
+        .parse()
+        .map_err(|_| anyhow::anyhow!("humanize: `{}` is not milliseconds", text));
+}
+
+/// `humanize=<min>-<max>` or `humanize=<min>-<max>,<pause>`: typed chars go out with a random
+/// wait of `min` to `max` ms between them, instead of all at once or the even `chardelay=`;
+/// with `pause`, up to that many ms more after each space. For apps that throttle or drop
+/// input arriving evenly fast, e.g. `humanize=40-120,250`.
+pub fn configure(text: &str) -> anyhow::Result<()> {
+    let (range, pause) = match text.split_once(',') {
+        Some((range, pause)) => (range, parse_ms(pause)?),
+        None => (text, 0),
+    };
+    let Some((min, max)) = range.split_once('-') else {
+        return Err(anyhow::anyhow!("humanize: expected `<min>-<max>[,<pause>]`, got `{}`", text));
+    };
+    let (min_ms, max_ms) = (parse_ms(min)?, parse_ms(max)?);
+    if min_ms > max_ms {
+        return Err(anyhow::anyhow!("humanize: {} is more than {}", min_ms, max_ms));
+    }
+    let humanize = Humanize { min_ms, max_ms, pause_ms: pause, rng: crate::list::Rng::new() };
+    *HUMANIZE.lock().unwrap_or_else(|e| e.into_inner()) = Some(humanize);
+    return Ok(());
+}
+
+/// Forgets `humanize=`, before the options are read again for another profile.
+pub fn reset() {
+    *HUMANIZE.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+pub fn is_on() -> bool {
+    return HUMANIZE.lock().unwrap_or_else(|e| e.into_inner()).is_some();
+}
+
+/// A random wait for after `typed`, a char or "" for a key, or None without `humanize=`.
+pub fn delay(typed: &str) -> Option<std::time::Duration> {
+    let mut guard = HUMANIZE.lock().unwrap_or_else(|e| e.into_inner());
+    let humanize = guard.as_mut()?;
+    let spread = (humanize.max_ms - humanize.min_ms) as usize;
+    let mut ms = humanize.min_ms + humanize.rng.below(spread + 1) as u64;
+    if humanize.pause_ms > 0 && !typed.is_empty() && typed.chars().all(char::is_whitespace) {
+        let pause = humanize.pause_ms as usize;
+        ms += humanize.rng.below(pause + 1) as u64;
+    }
+    return Some(std::time::Duration::from_millis(ms));
+}
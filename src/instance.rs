@@ -0,0 +1,34 @@
+use crate::win32;
+
+/// Instances after this many all count as the last one.
+const MAX_INSTANCES: usize = 8;
+
+/// Which of the running randwords this one is, 0 for the first. Each holds the first of the
+/// `Local\randword-<n>` mutexes that was free when it started, until it exits, so a slot is
+/// free again as soon as its instance is gone, crashed or not.
+pub fn claim() -> usize {
+    for n in 0..MAX_INSTANCES {
+        let name: Vec<u16> =
+            format!("Local\\randword-{}", n).encode_utf16().chain(std::iter::once(0)).collect();
+        let handle = unsafe { win32::CreateMutexW(std::ptr::null(), 0, name.as_ptr()) };
+        if handle == 0 {
+            continue;
+        }
+        if unsafe { win32::GetLastError() } != win32::ERROR_ALREADY_EXISTS {
+            // left open on purpose, it is closed as the process exits
+            return n;
+        }
+        unsafe { win32::CloseHandle(handle) };
+    }
+    return MAX_INSTANCES - 1;
+}
+
+/// The dispense hotkey of an instance after the first that didn't set `hotkey=`: `keys` if
+/// they are free, else the first free alternative, so two instances don't fight over
+/// Ctrl+Alt+X. `keys` if nothing is free, for the usual error.
+pub fn negotiate(h_window: win32::HWND, keys: crate::hotkey::Hotkey) -> crate::hotkey::Hotkey {
+    if keys.is_free(h_window) {
+        return keys;
+    }
+    return keys.alternatives().find(|candidate| candidate.is_free(h_window)).unwrap_or(keys);
+}
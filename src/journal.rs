@@ -0,0 +1,157 @@
+use std::io::Write;
+
+use crate::win32;
+
+/// What changed since the last checkpoint, one tab separated row per change with the word or
+/// list path last, since those may contain tabs:
+/// `record\t<timestamp>\t<word>`, `unrecord\t<word>`, `rate\t<delta>\t<word>` and
+/// `position\t<lines>\t<list>`.
+pub enum Change {
+    Record { timestamp: String, word: String },
+    Unrecord { word: String },
+    Rate { delta: i32, word: String },
+    Position { lines: u64, list: String },
+}
+
+impl Change {
+    fn parse(row: &str) -> Option<Self> {
+        let (kind, rest) = row.split_once('\t')?;
+        return match kind {
+            "record" => {
+                let (timestamp, word) = rest.split_once('\t')?;
+                Some(Self::Record { timestamp: timestamp.to_owned(), word: word.to_owned() })
+            }
+            "unrecord" => Some(Self::Unrecord { word: rest.to_owned() }),
+            "rate" => {
+                let (delta, word) = rest.split_once('\t')?;
+                Some(Self::Rate { delta: delta.parse().ok()?, word: word.to_owned() })
+            }
+            "position" => {
+                let (lines, list) = rest.split_once('\t')?;
+                Some(Self::Position { lines: lines.parse().ok()?, list: list.to_owned() })
+            }
+            _ => None,
+        };
+    }
+}
+
+/// Takes `path` for this process until it exits, with a `Local\` mutex named after the file;
+/// false while another randword holds it. Only the holder replays the journal or writes to it,
+/// so a second instance doesn't replay the rows of a running one as if it had crashed, or
+/// empty them after its own save.
+pub fn claim(path: &str) -> bool {
+    use sha2::Digest;
+
+    let Ok(dir) = std::env::current_dir() else {
+        return false;
+    };
+    let file = dir.join(path).display().to_string().to_lowercase();
+    // the path may be longer than a mutex name can be, or hold a `\`
+    let name = format!("Local\\randword-journal-{:x}", sha2::Sha256::digest(file));
+    let name: Vec<u16> = name.encode_utf16().chain(std::iter::once(0)).collect();
+    let handle = unsafe { win32::CreateMutexW(std::ptr::null(), 0, name.as_ptr()) };
+    if handle == 0 {
+        return false;
+    }
+    if unsafe { win32::GetLastError() } == win32::ERROR_ALREADY_EXISTS {
+        unsafe { win32::CloseHandle(handle) };
+        return false;
+    }
+    // left open on purpose, it is closed as the process exits
+    return true;
+}
+
+/// A write-ahead log of the changes to the stats and list positions, which are otherwise only
+/// saved when the app closes. Every change is on disk before the app goes on, and the
+/// journal is emptied once everything is saved, so a journal with rows in it at startup was
+/// left by a crash or power loss and is replayed with `leftover`.
+pub struct Journal {
+    file: std::fs::File,
+}
+
+impl Journal {
+    pub fn open(path: &str) -> std::io::Result<Self> {
+        let file = std::fs::OpenOptions::new().append(true).create(true).open(path)?;
+        return Ok(Self { file });
+    }
+
+    /// The changes left in `path` by an unclean shutdown. A last row cut off mid-write is
+    /// dropped; a missing file has none.
+    pub fn leftover(path: &str) -> std::io::Result<Vec<Change>> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(t) => t,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        return Ok(text
+            .split_inclusive('\n')
+            .filter_map(|row| Change::parse(row.strip_suffix('\n')?))
+            .collect());
+    }
+
+    /// Errors are ignored: a change that can't be journaled is still saved on a clean exit.
+    fn append(&mut self, row: std::fmt::Arguments) {
+        if writeln!(self.file, "{}", row).is_ok() {
+            let _ = self.file.sync_data();
+        }
+    }
+
+    pub fn record(&mut self, word: &str, timestamp: &str) {
+        self.append(format_args!("record\t{}\t{}", timestamp, word));
+    }
+
+    pub fn unrecord(&mut self, word: &str) {
+        self.append(format_args!("unrecord\t{}", word));
+    }
+
+    pub fn rate(&mut self, word: &str, delta: i32) {
+        self.append(format_args!("rate\t{}\t{}", delta, word));
+    }
+
+    pub fn position(&mut self, list: &crate::list::WordList) {
+        self.append(format_args!("position\t{}\t{}", list.position, list.path));
+    }
+
+    /// Empties the journal after everything in it was saved.
+    pub fn checkpoint(&mut self) -> std::io::Result<()> {
+        self.file.set_len(0)?;
+        return self.file.sync_data();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rows() {
+        let Some(Change::Record { timestamp, word }) =
+            Change::parse("record\t2024-01-02 10:00:00\ta\tb")
+        else {
+            panic!("not a record");
+        };
+        assert_eq!((timestamp.as_str(), word.as_str()), ("2024-01-02 10:00:00", "a\tb"));
+        let Some(Change::Unrecord { word }) = Change::parse("unrecord\tcat") else {
+            panic!("not an unrecord");
+        };
+        assert_eq!(word, "cat");
+        let Some(Change::Rate { delta, word }) = Change::parse("rate\t-1\tcat") else {
+            panic!("not a rate");
+        };
+        assert_eq!((delta, word.as_str()), (-1, "cat"));
+        let Some(Change::Position { lines, list }) = Change::parse("position\t42\twords.txt")
+        else {
+            panic!("not a position");
+        };
+        assert_eq!((lines, list.as_str()), (42, "words.txt"));
+    }
+
+    #[test]
+    fn skips_broken_rows() {
+        assert!(Change::parse("record\tno word").is_none());
+        assert!(Change::parse("rate\tlots\tcat").is_none());
+        assert!(Change::parse("position\t-1\twords.txt").is_none());
+        assert!(Change::parse("forget\tcat").is_none());
+        assert!(Change::parse("").is_none());
+    }
+}
@@ -0,0 +1,287 @@
+use crate::win32;
+
+/// The message `randword task <name>` posts to the running randword, with the task in `wParam`.
+pub const TASK: u32 = win32::WM_APP + 2;
+/// The tasks, by the name they have on the command line.
+pub const TASKS: [(&str, &str); 4] = [
+    ("mode", "Toggle mode"),
+    ("reset", "Reset progress"),
+    ("open", "Open word file"),
+    ("pause", "Pause"),
+];
+pub const MODE: usize = 0;
+pub const RESET: usize = 1;
+pub const OPEN: usize = 2;
+pub const PAUSE: usize = 3;
+
+/// The interfaces and classes used; windows-sys has none of them.
+const CLSID_DESTINATION_LIST: win32::GUID =
+    win32::GUID::from_u128(0x77f10cf0_3db5_4966_b520_b7c54fd35ed6);
+const IID_CUSTOM_DESTINATION_LIST: win32::GUID =
+    win32::GUID::from_u128(0x6332debf_87b5_4670_90c0_5e57b408a49e);
+const CLSID_OBJECT_COLLECTION: win32::GUID =
+    win32::GUID::from_u128(0x2d3468c1_36a7_43b6_ac24_d3f02fd9607a);
+const IID_OBJECT_COLLECTION: win32::GUID =
+    win32::GUID::from_u128(0x5632b1a4_e38a_400a_928a_d4cd63230295);
+const IID_OBJECT_ARRAY: win32::GUID =
+    win32::GUID::from_u128(0x92ca9dcd_5622_4bba_a805_5e9f541bd8c9);
+const CLSID_SHELL_LINK: win32::GUID =
+    win32::GUID::from_u128(0x00021401_0000_0000_c000_000000000046);
+const IID_SHELL_LINK: win32::GUID = win32::GUID::from_u128(0x000214f9_0000_0000_c000_000000000046);
+const IID_PROPERTY_STORE: win32::GUID =
+    win32::GUID::from_u128(0x886d8eeb_8cf2_4446_8d02_cdba1dbdcf99);
+
+/// `PKEY_Title`, the text of a task.
+#[repr(C)]
+struct PropertyKey {
+    fmtid: win32::GUID,
+    pid: u32,
+}
+
+const PKEY_TITLE: PropertyKey =
+    PropertyKey { fmtid: win32::GUID::from_u128(0xf29f85e0_4ff9_1068_ab91_08002b27b3d9), pid: 2 };
+
+/// A `PROPVARIANT` holding a `VT_LPWSTR`, the only kind set here.
+#[repr(C)]
+struct PropVariant {
+    vt: u16,
+    _reserved: [u16; 3],
+    text: *const u16,
+    _rest: usize,
+}
+
+const VT_LPWSTR: u16 = 31;
+
+/// What every interface starts with.
+#[repr(C)]
+struct UnknownVtbl {
+    query_interface: unsafe extern "system" fn(
+        *mut Com,
+        *const win32::GUID,
+        *mut *mut std::ffi::c_void,
+    ) -> win32::HRESULT,
+    _add_ref: usize,
+    release: unsafe extern "system" fn(*mut Com) -> u32,
+}
+
+/// The methods of `ICustomDestinationList` up to `CommitList`, in vtable order.
+#[repr(C)]
+struct DestinationListVtbl {
+    unknown: UnknownVtbl,
+    _set_app_id: usize,
+    begin_list: unsafe extern "system" fn(
+        *mut Com,
+        *mut u32,
+        *const win32::GUID,
+        *mut *mut std::ffi::c_void,
+    ) -> win32::HRESULT,
+    // AppendCategory, AppendKnownCategory
+    _other: [usize; 2],
+    add_user_tasks: unsafe extern "system" fn(*mut Com, *mut Com) -> win32::HRESULT,
+    commit_list: unsafe extern "system" fn(*mut Com) -> win32::HRESULT,
+}
+
+/// The methods of `IObjectCollection` up to `AddObject`.
+#[repr(C)]
+struct ObjectCollectionVtbl {
+    unknown: UnknownVtbl,
+    // GetCount, GetAt of IObjectArray
+    _other: [usize; 2],
+    add_object: unsafe extern "system" fn(*mut Com, *mut Com) -> win32::HRESULT,
+}
+
+/// The methods of `IShellLinkW` up to `SetPath`; the ones not called are only counted.
+#[repr(C)]
+struct ShellLinkVtbl {
+    unknown: UnknownVtbl,
+    // GetPath to SetDescription
+    _other: [usize; 5],
+    _get_working_directory: usize,
+    set_working_directory: unsafe extern "system" fn(*mut Com, *const u16) -> win32::HRESULT,
+    _get_arguments: usize,
+    set_arguments: unsafe extern "system" fn(*mut Com, *const u16) -> win32::HRESULT,
+    // GetHotkey to SetIconLocation
+    _more: [usize; 6],
+    set_icon_location: unsafe extern "system" fn(*mut Com, *const u16, i32) -> win32::HRESULT,
+    // SetRelativePath, Resolve
+    _rest: [usize; 2],
+    set_path: unsafe extern "system" fn(*mut Com, *const u16) -> win32::HRESULT,
+}
+
+/// The methods of `IPropertyStore` from `SetValue`.
+#[repr(C)]
+struct PropertyStoreVtbl {
+    unknown: UnknownVtbl,
+    // GetCount, GetAt, GetValue
+    _other: [usize; 3],
+    set_value: unsafe extern "system" fn(
+        *mut Com,
+        *const PropertyKey,
+        *const PropVariant,
+    ) -> win32::HRESULT,
+    commit: unsafe extern "system" fn(*mut Com) -> win32::HRESULT,
+}
+
+/// Any of the objects; `vtbl` is cast to the vtable of the interface it was asked for as.
+#[repr(C)]
+struct Com {
+    vtbl: *const std::ffi::c_void,
+}
+
+/// Releases the object when dropped.
+struct Object(*mut Com);
+
+impl Object {
+    fn create(class: &win32::GUID, interface: &win32::GUID) -> Option<Self> {
+        let mut object: *mut Com = std::ptr::null_mut();
+        let hr = unsafe {
+            win32::CoCreateInstance(
+                class,
+                std::ptr::null_mut(),
+                win32::CLSCTX_INPROC_SERVER,
+                interface,
+                &mut object as *mut *mut Com as *mut *mut std::ffi::c_void,
+            )
+        };
+        return (hr >= 0 && !object.is_null()).then_some(Self(object));
+    }
+
+    /// The vtable, as `T` of the interface the object was created or queried as.
+    unsafe fn vtbl<T>(&self) -> &T {
+        return &*((*self.0).vtbl as *const T);
+    }
+
+    fn query(&self, interface: &win32::GUID) -> Option<Self> {
+        let mut object: *mut Com = std::ptr::null_mut();
+        let hr = unsafe {
+            (self.vtbl::<UnknownVtbl>().query_interface)(
+                self.0,
+                interface,
+                &mut object as *mut *mut Com as *mut *mut std::ffi::c_void,
+            )
+        };
+        return (hr >= 0 && !object.is_null()).then_some(Self(object));
+    }
+}
+
+impl Drop for Object {
+    fn drop(&mut self) {
+        unsafe { (self.vtbl::<UnknownVtbl>().release)(self.0) };
+    }
+}
+
+fn wide(text: &str) -> Vec<u16> {
+    return text.encode_utf16().chain(std::iter::once(0)).collect();
+}
+
+/// A link running `randword task <name>`, titled `title`.
+fn task_link(exe: &str, name: &str, title: &str) -> Option<Object> {
+    let link = Object::create(&CLSID_SHELL_LINK, &IID_SHELL_LINK)?;
+    let title = wide(title);
+    unsafe {
+        let vtbl = link.vtbl::<ShellLinkVtbl>();
+        (vtbl.set_path)(link.0, wide(exe).as_ptr());
+        (vtbl.set_arguments)(link.0, wide(&format!("task {}", name)).as_ptr());
+        (vtbl.set_icon_location)(link.0, wide(exe).as_ptr(), 0);
+        // the task only talks to the running randword, wherever it was started
+        let dir =
+            std::path::Path::new(exe).parent().map_or(String::new(), |d| d.display().to_string());
+        (vtbl.set_working_directory)(link.0, wide(&dir).as_ptr());
+
+        let store = link.query(&IID_PROPERTY_STORE)?;
+        let vtbl = store.vtbl::<PropertyStoreVtbl>();
+        let value =
+            PropVariant { vt: VT_LPWSTR, _reserved: [0; 3], text: title.as_ptr(), _rest: 0 };
+        if (vtbl.set_value)(store.0, &PKEY_TITLE, &value) < 0 || (vtbl.commit)(store.0) < 0 {
+            return None;
+        }
+    }
+    return Some(link);
+}
+
+/// Fills the Tasks of the Jump List, what right-clicking the taskbar button shows: Toggle
+/// mode, Reset progress, Open word file and Pause, each starting `randword task <name>`,
+/// which hands it to this randword. The list stays after randword ends, where the tasks
+/// only say that none is running. Without it, because COM or the shell failed, the hotkeys
+/// and the tray work as before.
+pub fn add_tasks() {
+    let Ok(exe) = std::env::current_exe() else {
+        return;
+    };
+    let exe = exe.display().to_string();
+    unsafe {
+        // fails harmlessly if it was done before
+        win32::CoInitializeEx(std::ptr::null(), win32::COINIT_APARTMENTTHREADED as u32);
+    }
+    let Some(list) = Object::create(&CLSID_DESTINATION_LIST, &IID_CUSTOM_DESTINATION_LIST) else {
+        return;
+    };
+    let Some(tasks) = Object::create(&CLSID_OBJECT_COLLECTION, &IID_OBJECT_COLLECTION) else {
+        return;
+    };
+    unsafe {
+        let vtbl = list.vtbl::<DestinationListVtbl>();
+        let mut min_slots = 0;
+        let mut removed: *mut std::ffi::c_void = std::ptr::null_mut();
+        if (vtbl.begin_list)(list.0, &mut min_slots, &IID_OBJECT_ARRAY, &mut removed) < 0 {
+            return;
+        }
+        // the links the user removed; tasks can't be removed, so nothing to leave out
+        if !removed.is_null() {
+            drop(Object(removed as *mut Com));
+        }
+
+        let collection = tasks.vtbl::<ObjectCollectionVtbl>();
+        for (name, title) in TASKS {
+            if let Some(link) = task_link(&exe, name, title) {
+                (collection.add_object)(tasks.0, link.0);
+            }
+        }
+        let Some(array) = tasks.query(&IID_OBJECT_ARRAY) else {
+            return;
+        };
+        if (vtbl.add_user_tasks)(list.0, array.0) >= 0 {
+            (vtbl.commit_list)(list.0);
+        }
+    }
+}
+
+/// `randword task <name>`: has the running randword do one of the Jump List tasks.
+pub fn send(name: Option<&str>) {
+    let Some(task) = TASKS.iter().position(|&(n, _)| Some(n) == name) else {
+        let names: Vec<&str> = TASKS.iter().map(|&(n, _)| n).collect();
+        crate::message_box(
+            &format!("Expected a task: {}", names.join(", ")),
+            "Argument Error",
+            win32::MB_OK | win32::MB_ICONEXCLAMATION,
+        );
+        return;
+    };
+    let h_window = crate::shell::find_running();
+    if h_window == 0 {
+        crate::message_box(
+            "No randword is running.",
+            "randword",
+            win32::MB_OK | win32::MB_ICONINFORMATION,
+        );
+        return;
+    }
+    unsafe { win32::PostMessageW(h_window, TASK, task, 0) };
+}
+
+/// A task from `send`, in `wndproc`: Pause snoozes and Reset goes the way of its hotkey;
+/// the others need the list and options, so the main loop picks them up.
+pub fn handle(h_window: win32::HWND, task: usize) {
+    match task {
+        PAUSE => crate::snooze::toggle(h_window),
+        RESET => unsafe {
+            win32::PostMessageW(h_window, win32::WM_HOTKEY, crate::HOTKEY_RESET as usize, 0);
+        },
+        MODE | OPEN => {
+            if let Some(app) = crate::app::get(h_window) {
+                app.pending_task.set(Some(task));
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,109 @@
+/// Set from `keymap=`, the last one given winning; the typing code and the layout checks have
+/// no options at hand.
+static KEYMAP: std::sync::Mutex<Option<std::collections::HashMap<char, u16>>> =
+    std::sync::Mutex::new(None);
+
+/// Modifier names and their bits in the high byte, as `VkKeyScanExW` returns them.
+const MODIFIERS: [(&str, u16); 4] = [("shift", 1), ("ctrl", 2), ("alt", 4), ("altgr", 6)];
+
+/// A char of the file's first column: the char itself, or `U+XXXX` for ones that are
+/// awkward to write there, like `#` or a space.
+fn parse_char(text: &str) -> Option<char> {
+    if let Some(hex) = text.strip_prefix("U+") {
+        return u32::from_str_radix(hex, 16).ok().and_then(char::from_u32);
+    }
+    let mut chars = text.chars();
+    return match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    };
+}
+
+/// A virtual key: `0x51`, a letter or digit, or a key name like `SPACE`.
+fn parse_key(text: &str) -> Option<u16> {
+    if let Some(hex) = text.strip_prefix("0x") {
+        return u16::from_str_radix(hex, 16).ok().filter(|&k| k <= 0xFF);
+    }
+    if text.len() == 1 && text.as_bytes()[0].is_ascii_alphanumeric() {
+        return Some(text.as_bytes()[0].to_ascii_uppercase() as u16);
+    }
+    return crate::tokens::key(text);
+}
+
+/// Parses lines of `<char> <key> [<modifiers>]`, e.g. `ä 0x51 altgr` or `U+0023 3 shift`,
+/// with modifiers joined by `+` from shift, ctrl, alt and altgr. Lines starting with `#` and
+/// a space are comments.
+fn parse(text: &str) -> anyhow::Result<std::collections::HashMap<char, u16>> {
+    let mut keymap = std::collections::HashMap::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line == "#" || line.starts_with("# ") {
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let (chr, key) = match fields[..] {
+            [chr, key] | [chr, key, _] => (parse_char(chr), parse_key(key)),
+            _ => (None, None),
+        };
+        let (Some(chr), Some(key)) = (chr, key) else {
+            return Err(anyhow::anyhow!(
+                "line {}: expected `<char> <key> [<modifiers>]`, got `{}`",
+                i + 1,
+                line
+            ));
+        };
+        let mut modifiers = 0;
+        for name in fields.get(2).map_or("", |m| m).split('+').filter(|m| !m.is_empty()) {
+            let Some(&(_, bits)) = MODIFIERS.iter().find(|(n, _)| name.eq_ignore_ascii_case(n))
+            else {
+                return Err(anyhow::anyhow!("line {}: unknown modifier `{}`", i + 1, name));
+            };
+            modifiers |= bits;
+        }
+        keymap.insert(chr, modifiers << 8 | key);
+    }
+    return Ok(keymap);
+}
+
+/// `keymap=<file>`: which key and modifiers type a char, for layouts that Windows reports
+/// wrong, like a remapped Colemak with its own AltGr layer. Chars in the file are typed that
+/// way instead of as `VkKeyScanExW` says; the rest as before.
+pub fn load(path: &str) -> anyhow::Result<()> {
+    let text = std::fs::read_to_string(path).map_err(|e| anyhow::anyhow!("{}: {}", path, e))?;
+    let keymap = parse(&text).map_err(|e| anyhow::anyhow!("keymap {}, {}", path, e))?;
+    *KEYMAP.lock().unwrap_or_else(|e| e.into_inner()) = Some(keymap);
+    return Ok(());
+}
+
+/// Forgets `keymap=`, before the options are read again for another profile.
+pub fn reset() {
+    *KEYMAP.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// The key of `chr` from `keymap=`, like `VkKeyScanExW`: the virtual key in the low byte,
+/// the modifiers in the high one.
+pub fn lookup(chr: char) -> Option<u16> {
+    return KEYMAP.lock().unwrap_or_else(|e| e.into_inner()).as_ref()?.get(&chr).copied();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_keys_and_modifiers() {
+        let keymap = parse("# a comment\n\nä 0x51 altgr\nU+0023 3 shift\nq Q\n").unwrap();
+        assert_eq!(keymap.len(), 3);
+        assert_eq!(keymap[&'ä'], 6 << 8 | 0x51);
+        assert_eq!(keymap[&'#'], 1 << 8 | b'3' as u16);
+        assert_eq!(keymap[&'q'], b'Q' as u16);
+    }
+
+    #[test]
+    fn rejects_bad_lines() {
+        assert!(parse("ab 0x51").is_err());
+        assert!(parse("a 0x151").is_err());
+        assert!(parse("a").is_err());
+        assert!(parse("a q hyper").is_err());
+    }
+}
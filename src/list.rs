@@ -0,0 +1,571 @@
+use std::hash::{BuildHasher, Hasher};
+use std::io::{BufRead, Read, Seek, Write};
+
+/// The words file read forward one line per dispense, with the position kept in lines.
+/// With `random` a line is picked anywhere instead, without moving the position; with
+/// `shuffle` the lines are dealt from a shuffled deck, the position counting the dealt ones.
+pub struct WordList {
+    pub path: String,
+    reader: std::io::BufReader<std::fs::File>,
+    buffer: String,
+    /// where the line starts in `buffer`, after a directive
+    start: usize,
+    directive: Option<crate::rules::Mode>,
+    /// lines already consumed, what skipline.dat stores
+    pub position: u64,
+    pub total: u64,
+    /// where each line starts in the file, to seek straight to it
+    line_starts: Vec<u64>,
+    /// picks the lines in random mode
+    random: Option<Rng>,
+    /// how many of the last random picks aren't picked again, with `norepeat=`
+    no_repeat: usize,
+    /// the last random picks, oldest first
+    recent: std::collections::VecDeque<usize>,
+    /// `coverage`: random picks favor the lines dispensed least
+    coverage: bool,
+    /// how often each line was dispensed, for `coverage`; None until `weigh`
+    counts: Option<Vec<u32>>,
+    deck: Option<Deck>,
+    /// with `statedir=`, the position is synced with other instances on every move
+    shared: Option<crate::shared::SharedCursor>,
+    /// when the file was last written as of opening it, to notice edits
+    modified: Option<std::time::SystemTime>,
+}
+
+impl WordList {
+    /// Opens `path` and skips the first `position` lines.
+    pub fn open(path: &str, position: u64) -> std::io::Result<Self> {
+        let mut file = std::fs::File::open(path)?;
+        let line_starts = find_line_starts(&mut file);
+        let modified = file.metadata().and_then(|m| m.modified()).ok();
+        let mut list = Self {
+            path: path.to_owned(),
+            reader: std::io::BufReader::new(file),
+            buffer: String::with_capacity(128),
+            start: 0,
+            directive: None,
+            position: 0,
+            total: line_starts.len() as u64,
+            line_starts,
+            random: None,
+            no_repeat: 0,
+            recent: std::collections::VecDeque::new(),
+            coverage: false,
+            counts: None,
+            deck: None,
+            shared: None,
+            modified,
+        };
+        list.seek_local(position);
+        return Ok(list);
+    }
+
+    pub fn share(&mut self, cursor: crate::shared::SharedCursor) {
+        self.shared = Some(cursor);
+    }
+
+    /// Switches to picking uniformly random lines, none of the last `no_repeat` picked again.
+    pub fn randomize(&mut self, no_repeat: usize) {
+        self.random = Some(Rng::new());
+        self.no_repeat = no_repeat;
+    }
+
+    /// Switches to picking random lines like `randomize`, but each line half as likely as one
+    /// dispensed once less, so the list gets covered while the order stays random.
+    pub fn cover(&mut self, no_repeat: usize) {
+        self.randomize(no_repeat);
+        self.coverage = true;
+    }
+
+    /// Takes how often each line was dispensed from `times`, after `cover` and again after a
+    /// reload; until then the lines are picked uniformly.
+    pub fn weigh(&mut self, times: impl Fn(&str) -> u32) -> std::io::Result<()> {
+        if !self.coverage || self.counts.is_some() {
+            return Ok(());
+        }
+        let text = std::fs::read_to_string(&self.path)?;
+        let mut counts: Vec<u32> = text.lines().map(|l| times(split_directive(l).1)).collect();
+        counts.resize(self.line_starts.len(), 0);
+        self.counts = Some(counts);
+        return Ok(());
+    }
+
+    /// Switches to dealing the lines in the order kept in `path`, which is shuffled anew when
+    /// missing or made for a list with another number of lines. With `statedir=` every
+    /// instance shares the deck file, and reads it again once another one dealt anew.
+    pub fn deal(&mut self, path: std::path::PathBuf) -> std::io::Result<()> {
+        let mut deck = Deck { order: Vec::new(), path, rng: Rng::new(), modified: None };
+        match deck.load(self.total) {
+            Ok(true) => {}
+            Ok(false) => deck.shuffle(self.total)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => deck.shuffle(self.total)?,
+            Err(e) => return Err(e),
+        }
+        self.deck = Some(deck);
+        return Ok(());
+    }
+
+    pub fn is_random(&self) -> bool {
+        return self.random.is_some();
+    }
+
+    /// Runs `f` on this list; in shared mode first catching up with the shared position and
+    /// storing the new one afterwards, all under the lock.
+    fn locked<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> std::io::Result<T> {
+        let Some(shared) = self.shared.clone() else {
+            return Ok(f(self));
+        };
+        return shared.with_lock(|cursor| {
+            if let Some(deck) = self.deck.as_mut() {
+                deck.refresh(self.total);
+            }
+            if *cursor != self.position {
+                self.seek_local(*cursor);
+            }
+            let result = f(self);
+            *cursor = self.position;
+            result
+        });
+    }
+
+    /// Reads the next line into `line()`. At the end of the file the list starts over and
+    /// false is returned, without a line. In random mode any line may come next and the
+    /// list never ends, unless it is empty.
+    pub fn advance(&mut self) -> std::io::Result<bool> {
+        if self.random.is_some() {
+            return self.read_random();
+        }
+        return self.locked(|list| list.read_next())?;
+    }
+
+    fn read_next(&mut self) -> std::io::Result<bool> {
+        if let Some(deck) = self.deck.as_mut() {
+            let Some(&index) = deck.order.get(self.position as usize) else {
+                self.position = 0;
+                // on failure the deck on disk stays the old one, dealt again after a restart
+                let _ = deck.shuffle(self.total);
+                return Ok(false);
+            };
+            if !(self.seek_to(self.line_starts[index as usize]) && self.read_line()?) {
+                return Ok(false);
+            }
+            self.position += 1;
+            return Ok(true);
+        }
+        if !self.read_line()? {
+            self.position = 0;
+            self.reader.rewind()?;
+            return Ok(false);
+        }
+        self.position += 1;
+        return Ok(true);
+    }
+
+    /// Reads a random line, then goes back to where the sequential reading was.
+    fn read_random(&mut self) -> std::io::Result<bool> {
+        let Some(rng) = self.random.as_mut() else {
+            return Ok(false);
+        };
+        if self.line_starts.is_empty() {
+            return Ok(false);
+        }
+        // a list no longer than the window would run out of lines to pick
+        let window = self.no_repeat.min(self.line_starts.len() - 1);
+        let index = loop {
+            let index = match &self.counts {
+                Some(counts) => pick_weighted(rng, counts),
+                None => rng.below(self.line_starts.len()),
+            };
+            if !self.recent.iter().rev().take(window).any(|&i| i == index) {
+                break index;
+            }
+        };
+        self.recent.push_back(index);
+        if self.recent.len() > self.no_repeat {
+            self.recent.pop_front();
+        }
+        // the stats count it too, but are only read again after a reload
+        if let Some(count) = self.counts.as_mut().and_then(|c| c.get_mut(index)) {
+            *count += 1;
+        }
+        let resume = self.line_starts.get(self.position as usize).copied();
+        let read = if self.seek_to(self.line_starts[index]) { self.read_line() } else { Ok(false) };
+        match resume {
+            Some(offset) => self.seek_to(offset),
+            None => self.reader.seek(std::io::SeekFrom::End(0)).is_ok(),
+        };
+        return read;
+    }
+
+    /// Reads a line into the buffer and splits off its directive; false at the end. An error
+    /// if the line isn't UTF-8.
+    fn read_line(&mut self) -> std::io::Result<bool> {
+        self.buffer.clear();
+        if self.reader.read_line(&mut self.buffer)? == 0 {
+            return Ok(false);
+        }
+        let len = self.buffer.trim_end_matches(['\r', '\n']).len();
+        self.buffer.truncate(len);
+        let (directive, line) = split_directive(&self.buffer);
+        self.start = self.buffer.len() - line.len();
+        self.directive = directive;
+        return Ok(true);
+    }
+
+    fn seek_to(&mut self, offset: u64) -> bool {
+        return self.reader.seek(std::io::SeekFrom::Start(offset)).is_ok();
+    }
+
+    /// Opens the file again after it was rewritten, keeping the place in it: the position
+    /// moves to where the line that was next is now, or after the one dispensed last if the
+    /// next one is gone. A shuffled deck keeps its order, see `Deck::rearrange`. With
+    /// `statedir=` the shared position moves along.
+    pub fn reload(&mut self) -> std::io::Result<()> {
+        // taken first, so a file that can't be read is only reported once per change
+        self.modified = modified(&self.path);
+        let text = std::fs::read_to_string(&self.path)?;
+        let file = std::fs::File::open(&self.path)?;
+        return self.locked(|list| list.reopen(&text, file))?;
+    }
+
+    /// `reload` once the new `text` was read from `file`.
+    fn reopen(&mut self, text: &str, mut file: std::fs::File) -> std::io::Result<()> {
+        let old: Vec<String> = match &self.deck {
+            Some(_) => (0..self.total).map_while(|i| self.line_at(i)).collect(),
+            None => Vec::new(),
+        };
+        let next = self.line_at(self.position);
+        let last = self.position.checked_sub(1).and_then(|p| self.line_at(p));
+        self.line_starts = find_line_starts(&mut file);
+        self.total = self.line_starts.len() as u64;
+        self.reader = std::io::BufReader::new(file);
+        // the picks and counts were of the old lines
+        self.recent.clear();
+        self.counts = None;
+        if let Some(deck) = self.deck.as_mut() {
+            // another instance sharing the deck got to it first
+            let position = if deck.refresh(self.total) {
+                self.position
+            } else {
+                deck.rearrange(&old, &text.lines().collect::<Vec<_>>(), self.position)?
+            };
+            self.seek_local(position);
+            return Ok(());
+        }
+
+        // of repeated lines, the copy closest to the old position is meant
+        let old = self.position as usize;
+        let find = |line: Option<String>| {
+            text.lines()
+                .enumerate()
+                .filter(|(_, l)| Some(*l) == line.as_deref())
+                .map(|(i, _)| i)
+                .min_by_key(|&i| i.abs_diff(old))
+        };
+        let position = find(next).or_else(|| find(last).map(|i| i + 1)).unwrap_or(old);
+        self.seek_local(position as u64);
+        return Ok(());
+    }
+
+    /// Whether the file was written since it was opened or reloaded. False while it is gone,
+    /// as it is for a moment when some editors save.
+    pub fn changed_on_disk(&self) -> bool {
+        return modified(&self.path).is_some_and(|m| Some(m) != self.modified);
+    }
+
+    /// The text of line `index`, without reading past it for `advance`.
+    fn line_at(&mut self, index: u64) -> Option<String> {
+        let start = *self.line_starts.get(index as usize)?;
+        let mut line = String::new();
+        self.seek_to(start).then_some(())?;
+        self.reader.read_line(&mut line).ok()?;
+        return Some(line.trim_end_matches(['\n', '\r']).to_owned());
+    }
+
+    /// The line the next `advance` reads, without its directive, leaving the position where
+    /// it is. None at the end of the list, and in random mode where it isn't known yet.
+    pub fn peek(&mut self) -> std::io::Result<Option<String>> {
+        return self.upcoming(1).map(|lines| lines.into_iter().next());
+    }
+
+    /// Up to `count` of the lines the next `advance`s read, like `peek`; empty in random mode.
+    pub fn upcoming(&mut self, count: usize) -> std::io::Result<Vec<String>> {
+        if self.random.is_some() {
+            return Ok(Vec::new());
+        }
+        return self.locked(|list| {
+            let start = list.position as usize;
+            let indices: Vec<u64> = match &list.deck {
+                Some(deck) => {
+                    deck.order.iter().skip(start).take(count).map(|&i| i as u64).collect()
+                }
+                None => (list.position..list.total).take(count).collect(),
+            };
+            let lines = indices
+                .into_iter()
+                .map_while(|i| list.line_at(i))
+                .map(|l| split_directive(&l).1.to_owned())
+                .collect();
+            list.seek_local(list.position);
+            return lines;
+        });
+    }
+
+    /// Every line of the file in its order, without directives, read through the index of
+    /// line starts; the position stays.
+    pub fn lines(&mut self) -> Vec<String> {
+        let lines = (0..self.total)
+            .map_while(|i| self.line_at(i))
+            .map(|l| split_directive(&l).1.to_owned())
+            .collect();
+        self.seek_local(self.position);
+        return lines;
+    }
+
+    /// The line read by the last successful `advance`, without its directive.
+    pub fn line(&self) -> &str {
+        return &self.buffer[self.start..];
+    }
+
+    /// The mode the line asked for with a directive, if any.
+    pub fn directive(&self) -> Option<crate::rules::Mode> {
+        return self.directive;
+    }
+
+    /// Moves the read position by `delta` lines, clamped to the list.
+    pub fn jump(&mut self, delta: i64) -> std::io::Result<()> {
+        return self.locked(|list| list.seek_local((list.position as i64 + delta).max(0) as u64));
+    }
+
+    /// Moves the read position so line `index` of the file comes next, or with `shuffle` to
+    /// where the deck deals it.
+    pub fn go_to_line(&mut self, index: u64) -> std::io::Result<()> {
+        return self.locked(|list| {
+            let target = match &list.deck {
+                Some(deck) => deck.order.iter().position(|&i| i as u64 == index).unwrap_or(0),
+                None => index as usize,
+            };
+            list.seek_local(target as u64);
+        });
+    }
+
+    fn seek_local(&mut self, target: u64) {
+        let target = target.min(self.total);
+        match self.line_starts.get(target as usize) {
+            Some(&offset) => self.seek_to(offset),
+            None => self.reader.seek(std::io::SeekFrom::End(0)).is_ok(),
+        };
+        self.position = target;
+    }
+}
+
+/// A xorshift64 generator; plenty for picking and shuffling lines.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new() -> Self {
+        // a fresh `RandomState` is seeded by the OS, different every run
+        let seed = std::collections::hash_map::RandomState::new().build_hasher().finish();
+        return Self(seed | 1);
+    }
+
+    /// A number in `0..n`, uniform enough for any `n` a list has.
+    pub fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        return ((self.0 as u128 * n as u128) >> 64) as usize;
+    }
+}
+
+/// Picks an index of `counts`, each half as likely as one counted once less, so the lines
+/// seen least come up most while any line still can.
+fn pick_weighted(rng: &mut Rng, counts: &[u32]) -> usize {
+    let least = counts.iter().copied().min().unwrap_or(0);
+    // 20 counts behind the least seen, a line is about as good as never picked
+    let weight = |count: u32| 1usize << (20 - (count - least).min(20));
+    let mut pick = rng.below(counts.iter().map(|&c| weight(c)).sum());
+    for (i, &count) in counts.iter().enumerate() {
+        if pick < weight(count) {
+            return i;
+        }
+        pick -= weight(count);
+    }
+    return counts.len() - 1;
+}
+
+/// The order lines are dealt in with `shuffle`, stored as little-endian u32 line indexes.
+struct Deck {
+    order: Vec<u32>,
+    path: std::path::PathBuf,
+    rng: Rng,
+    /// when the file was last written as of reading or writing it here
+    modified: Option<std::time::SystemTime>,
+}
+
+impl Deck {
+    /// Reads the stored order; false if it doesn't fit a list of `total` lines.
+    fn load(&mut self, total: u64) -> std::io::Result<bool> {
+        let mut bytes = Vec::new();
+        let mut file = std::fs::File::open(&self.path)?;
+        let modified = file.metadata().and_then(|m| m.modified()).ok();
+        file.read_to_end(&mut bytes)?;
+        let order: Vec<u32> =
+            bytes.chunks_exact(4).map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]])).collect();
+        let mut seen = vec![false; total as usize];
+        for &index in &order {
+            match seen.get_mut(index as usize) {
+                Some(seen) if !*seen => *seen = true,
+                _ => return Ok(false),
+            }
+        }
+        if bytes.len() % 4 != 0 || order.len() as u64 != total {
+            return Ok(false);
+        }
+        self.order = order;
+        self.modified = modified;
+        return Ok(true);
+    }
+
+    /// Deals a new order of `total` lines and stores it.
+    fn shuffle(&mut self, total: u64) -> std::io::Result<()> {
+        self.order = (0..total as u32).collect();
+        // Fisher-Yates
+        for i in (1..self.order.len()).rev() {
+            let j = self.rng.below(i + 1);
+            self.order.swap(i, j);
+        }
+        return self.store();
+    }
+
+    /// Deals the lines of a list that changed from `old` to `new` in the order they had, of
+    /// which `dealt` are dealt: lines gone drop out and lines added are shuffled in among the
+    /// ones still to come. Returns how many of the new order are dealt.
+    fn rearrange(&mut self, old: &[String], new: &[&str], dealt: u64) -> std::io::Result<u64> {
+        let mut at: std::collections::HashMap<&str, std::collections::VecDeque<u32>> =
+            std::collections::HashMap::new();
+        for (i, &line) in new.iter().enumerate() {
+            at.entry(line).or_default().push_back(i as u32);
+        }
+        let mut order = Vec::with_capacity(new.len());
+        let mut still_dealt = 0;
+        for (n, &index) in self.order.iter().enumerate() {
+            let Some(line) = old.get(index as usize) else {
+                continue;
+            };
+            if let Some(i) = at.get_mut(line.as_str()).and_then(|q| q.pop_front()) {
+                order.push(i);
+                if (n as u64) < dealt {
+                    still_dealt += 1;
+                }
+            }
+        }
+        // what is left was added, or is one more copy of a line than before
+        let mut added: Vec<u32> = at.into_values().flatten().collect();
+        added.sort_unstable();
+        for i in added {
+            let place = still_dealt + self.rng.below(order.len() - still_dealt + 1);
+            order.insert(place, i);
+        }
+        self.order = order;
+        self.store()?;
+        return Ok(still_dealt as u64);
+    }
+
+    /// Writes the order to the deck file.
+    fn store(&mut self) -> std::io::Result<()> {
+        let bytes: Vec<u8> = self.order.iter().flat_map(|i| i.to_le_bytes()).collect();
+        // written next to it and moved over it, so a crash leaves either the old or the new
+        // deck; named after the process, as other instances may deal at the same time
+        let mut temp = self.path.clone().into_os_string();
+        temp.push(format!(".{}.tmp", std::process::id()));
+        let mut file = std::fs::File::create(&temp)?;
+        file.write_all(&bytes)?;
+        file.sync_all()?;
+        drop(file);
+        std::fs::rename(&temp, &self.path)?;
+        self.modified = std::fs::metadata(&self.path).and_then(|m| m.modified()).ok();
+        return Ok(());
+    }
+
+    /// Reads the order again when another instance sharing the deck dealt anew, returning
+    /// whether it did. A deck that can't be read, or not for `total` lines, leaves the order
+    /// as it was.
+    fn refresh(&mut self, total: u64) -> bool {
+        let modified = std::fs::metadata(&self.path).and_then(|m| m.modified()).ok();
+        if modified.is_none() || modified == self.modified {
+            return false;
+        }
+        return matches!(self.load(total), Ok(true));
+    }
+}
+
+/// A line starting with `!type `, `!clip `, `!paste ` or one of the speech modes like
+/// `!spell ` is emitted that way whatever the options and rules say, e.g.
+/// `!clip https://example.com/a/very/long/url`.
+pub fn split_directive(line: &str) -> (Option<crate::rules::Mode>, &str) {
+    let Some((name, rest)) = line.strip_prefix('!').and_then(|l| l.split_once(' ')) else {
+        return (None, line);
+    };
+    return match crate::rules::Mode::parse(name) {
+        Some(mode) => (Some(mode), rest),
+        None => (None, line),
+    };
+}
+
+fn modified(path: &str) -> Option<std::time::SystemTime> {
+    return std::fs::metadata(path).and_then(|m| m.modified()).ok();
+}
+
+/// The offsets where the lines of `file` start; leaves it rewound to the start.
+fn find_line_starts(file: &mut std::fs::File) -> Vec<u64> {
+    let mut starts = Vec::new();
+    let mut offset = 0;
+    let mut reader = std::io::BufReader::new(&mut *file);
+    let mut buffer = Vec::with_capacity(128);
+    while let Ok(size) = reader.read_until(b'\n', &mut buffer) {
+        if size == 0 {
+            break;
+        }
+        starts.push(offset);
+        offset += size as u64;
+        buffer.clear();
+    }
+    let _ = file.rewind();
+    return starts;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rearranges_the_deck_keeping_its_order() {
+        let path = std::env::temp_dir().join(format!("randword-{}.deck", std::process::id()));
+        let mut deck = Deck { order: vec![2, 0, 3, 1], path, rng: Rng::new(), modified: None };
+        let old: Vec<String> = ["a", "b", "c", "d"].map(str::to_owned).to_vec();
+        // b is gone and e added; c and a were dealt
+        let dealt = deck.rearrange(&old, &["a", "c", "d", "e"], 2).unwrap();
+        let _ = std::fs::remove_file(&deck.path);
+        assert_eq!(dealt, 2);
+        assert_eq!(deck.order[..2], [1, 0]);
+        let mut rest = deck.order[2..].to_vec();
+        rest.sort();
+        assert_eq!(rest, [2, 3]);
+    }
+
+    #[test]
+    fn reads_lines_without_their_endings() {
+        let path = std::env::temp_dir().join(format!("randword-{}.txt", std::process::id()));
+        // CRLF, and a last line without one ending in a char of two bytes
+        std::fs::write(&path, "one\r\ntwo\ncaf\u{e9}").unwrap();
+        let mut list = WordList::open(path.to_str().unwrap(), 0).unwrap();
+        let mut lines = Vec::new();
+        while list.advance().unwrap() {
+            lines.push(list.line().to_owned());
+        }
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(lines, ["one", "two", "caf\u{e9}"]);
+    }
+}
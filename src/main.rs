@@ -1,9 +1,63 @@
 #![windows_subsystem = "windows"]
 
-use std::io::{BufRead, Read, Seek, Write};
+use std::io::{Read, Seek, Write};
 
 use anyhow;
 
+mod app;
+mod bench;
+mod bidi;
+mod bundle;
+mod cadence;
+mod check;
+mod cli;
+mod clipboard;
+mod config;
+mod conflict;
+mod dock;
+mod doctor;
+mod goto;
+mod grapheme;
+#[cfg(feature = "gui")]
+mod gui;
+mod hooks;
+mod hotkey;
+mod http;
+mod humanize;
+mod instance;
+mod journal;
+mod jumplist;
+mod keymap;
+mod list;
+mod migrate;
+mod osd;
+mod output;
+mod paint;
+mod picker;
+mod positions;
+mod power;
+mod rawinput;
+mod remote;
+mod replay;
+mod rules;
+mod schedule;
+mod session;
+mod shape;
+mod shared;
+mod shell;
+mod signal;
+mod snooze;
+mod speech;
+mod stats;
+mod thumbbar;
+mod timing;
+mod tokens;
+mod tray;
+mod tui;
+mod update;
+mod watch;
+mod webhook;
+
 pub mod win32 {
     pub use windows_sys::Win32::Foundation::{HINSTANCE, HWND};
     pub use windows_sys::Win32::Foundation::{LPARAM, LRESULT, WPARAM};
@@ -20,10 +74,15 @@ pub mod win32 {
             Threading::Sleep,
         },
         UI::{
-            Input::KeyboardAndMouse::{RegisterHotKey, UnregisterHotKey, MOD_ALT, MOD_CONTROL},
+            Input::KeyboardAndMouse::{
+                RegisterHotKey, UnregisterHotKey, MOD_ALT, MOD_CONTROL, MOD_SHIFT, MOD_WIN,
+                VK_BACK, VK_CAPITAL, VK_CONTROL, VK_DELETE, VK_DOWN, VK_END, VK_ESCAPE, VK_F1,
+                VK_F24, VK_HOME, VK_INSERT, VK_LEFT, VK_NEXT, VK_NUMLOCK, VK_OEM_MINUS,
+                VK_OEM_PLUS, VK_PRIOR, VK_RETURN, VK_RIGHT, VK_SCROLL, VK_SPACE, VK_TAB, VK_UP,
+            },
             WindowsAndMessaging::{
-                DispatchMessageW, PeekMessageW, MB_ICONEXCLAMATION, MSG, PM_REMOVE, WM_CLOSE,
-                WM_DESTROY, WM_HOTKEY, WM_PAINT, WM_QUIT,
+                DispatchMessageW, MsgWaitForMultipleObjects, PeekMessageW, MB_ICONEXCLAMATION, MSG,
+                PM_REMOVE, QS_ALLINPUT, WM_CLOSE, WM_DESTROY, WM_HOTKEY, WM_PAINT, WM_QUIT,
             },
         },
     };
@@ -31,14 +90,18 @@ pub mod win32 {
         s, w,
         Win32::{
             System::{
-                DataExchange::{CloseClipboard, EmptyClipboard, OpenClipboard, SetClipboardData},
-                Memory::{GlobalLock, GlobalUnlock},
-                Ole::CF_TEXT,
+                DataExchange::{
+                    CloseClipboard, EmptyClipboard, GetClipboardData, GetClipboardSequenceNumber,
+                    OpenClipboard, SetClipboardData,
+                },
+                Memory::{GlobalLock, GlobalSize, GlobalUnlock},
+                Ole::CF_UNICODETEXT,
             },
             UI::{
                 Input::KeyboardAndMouse::{
-                    MapVirtualKeyW, SendInput, VkKeyScanA, VkKeyScanW, INPUT, INPUT_0,
-                    INPUT_KEYBOARD, KEYBDINPUT, KEYEVENTF_KEYUP, MAPVK_VK_TO_VSC,
+                    GetAsyncKeyState, MapVirtualKeyW, SendInput, VkKeyScanA, VkKeyScanW, INPUT,
+                    INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYEVENTF_KEYUP, KEYEVENTF_UNICODE,
+                    MAPVK_VK_TO_VSC, VK_LWIN, VK_MENU, VK_RWIN, VK_SHIFT,
                 },
                 WindowsAndMessaging::WM_KEYUP,
             },
@@ -55,18 +118,200 @@ pub mod win32 {
             WS_SYSMENU,
         },
     };
+
+    pub use windows_sys::Win32::{
+        Foundation::SYSTEMTIME,
+        System::SystemInformation::GetLocalTime,
+        UI::WindowsAndMessaging::{
+            GetForegroundWindow, GetWindowTextW, MessageBoxW, MB_ICONINFORMATION,
+        },
+    };
+
+    pub use windows_sys::Win32::UI::{
+        Input::KeyboardAndMouse::{GetKeyboardLayout, VkKeyScanExW},
+        TextServices::HKL,
+        WindowsAndMessaging::GetWindowThreadProcessId,
+    };
+
+    pub use windows_sys::Win32::{
+        Graphics::Gdi::{
+            BeginPaint, BitBlt, CreateCompatibleBitmap, CreateCompatibleDC, DeleteDC, DeleteObject,
+            EndPaint, FillRect, GetSysColorBrush, SelectObject, COLOR_WINDOW, HDC, PAINTSTRUCT,
+            SRCCOPY,
+        },
+        UI::WindowsAndMessaging::{GetClientRect, SetWindowTextW, WM_ERASEBKGND},
+    };
+
+    pub use windows_sys::Win32::Graphics::Gdi::{
+        DrawTextW, GetStockObject, GetSysColor, InvalidateRect, SetBkMode, SetTextColor,
+        COLOR_GRAYTEXT, COLOR_WINDOWTEXT, DEFAULT_GUI_FONT, DT_END_ELLIPSIS, DT_LEFT, DT_NOPREFIX,
+        DT_RIGHT, DT_RTLREADING, DT_SINGLELINE, DT_VCENTER, TRANSPARENT,
+    };
+
+    pub use windows_sys::Win32::{
+        Foundation::HANDLE,
+        UI::{
+            Input::{
+                GetRawInputData, GetRawInputDeviceInfoW, RegisterRawInputDevices, RAWINPUT,
+                RAWINPUTDEVICE, RAWINPUTHEADER, RIDEV_INPUTSINK, RIDI_DEVICENAME, RID_INPUT,
+                RIM_TYPEKEYBOARD,
+            },
+            WindowsAndMessaging::{RI_KEY_BREAK, WM_INPUT},
+        },
+    };
+
+    pub use windows_sys::Win32::{
+        Graphics::Gdi::{GetMonitorInfoW, MonitorFromRect, MONITORINFO, MONITOR_DEFAULTTONEAREST},
+        UI::WindowsAndMessaging::{
+            GetCursorPos, GetWindowRect, SWP_NOACTIVATE, SWP_NOZORDER, WM_MOVING,
+        },
+    };
+
+    pub use windows_sys::Win32::{
+        Foundation::{ERROR_ALREADY_EXISTS, ERROR_FILE_NOT_FOUND},
+        System::{
+            DataExchange::COPYDATASTRUCT,
+            Registry::{
+                RegCloseKey, RegCreateKeyExW, RegDeleteTreeW, RegSetValueExW, HKEY_CURRENT_USER,
+                KEY_WRITE, REG_OPTION_NON_VOLATILE, REG_SZ,
+            },
+        },
+        UI::WindowsAndMessaging::{FindWindowExW, FindWindowW, SendMessageW, WM_COPYDATA},
+    };
+
+    pub use windows_sys::Win32::UI::WindowsAndMessaging::{
+        SetForegroundWindow, HWND_MESSAGE, IDYES, MB_TOPMOST, MB_YESNO,
+    };
+
+    pub use windows_sys::Win32::UI::WindowsAndMessaging::{TranslateMessage, WS_VISIBLE};
+
+    pub use windows_sys::Win32::{
+        System::SystemInformation::GetTickCount,
+        UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO},
+    };
+
+    pub use windows_sys::Win32::{
+        System::Power::GetSystemPowerStatus,
+        UI::Shell::{
+            SHQueryUserNotificationState, QUNS_BUSY, QUNS_PRESENTATION_MODE, QUNS_QUIET_TIME,
+            QUNS_RUNNING_D3D_FULL_SCREEN,
+        },
+    };
+
+    pub use windows_sys::Win32::{
+        Foundation::CloseHandle,
+        System::Threading::{
+            CreateMutexW, CreateWaitableTimerExW, SetWaitableTimer, WaitForSingleObject,
+            CREATE_WAITABLE_TIMER_HIGH_RESOLUTION, INFINITE, TIMER_ALL_ACCESS,
+        },
+    };
+
+    pub use windows_sys::Win32::{
+        Foundation::SIZE,
+        Graphics::Gdi::{
+            GetDC, GetTextExtentPoint32W, ReleaseDC, COLOR_INFOBK, COLOR_INFOTEXT, DT_CENTER,
+        },
+        UI::WindowsAndMessaging::{
+            DefWindowProcW, SetLayeredWindowAttributes, LWA_ALPHA, SWP_SHOWWINDOW, SW_HIDE,
+            WS_EX_LAYERED, WS_EX_NOACTIVATE, WS_EX_TOOLWINDOW, WS_EX_TRANSPARENT, WS_POPUP,
+        },
+    };
+
+    pub use windows_sys::Win32::{
+        Graphics::Gdi::ClientToScreen,
+        UI::WindowsAndMessaging::{
+            GetGUIThreadInfo, GUITHREADINFO, LB_ADDSTRING, LB_GETCURSEL, LB_GETITEMHEIGHT,
+            LB_SETCURSEL, WM_KEYDOWN, WM_LBUTTONDBLCLK, WS_BORDER, WS_VSCROLL,
+        },
+    };
+
+    pub use windows_sys::Win32::UI::{
+        Input::Ime::ImmGetDefaultIMEWnd,
+        WindowsAndMessaging::{SendMessageTimeoutW, SMTO_ABORTIFHUNG, WM_IME_CONTROL},
+    };
+
+    pub use windows_sys::Win32::UI::{
+        Controls::EM_SETSEL,
+        Input::KeyboardAndMouse::SetFocus,
+        WindowsAndMessaging::{ES_AUTOHSCROLL, ES_NUMBER, WM_SETFONT, WS_CHILD},
+    };
+
+    pub use windows_sys::Win32::UI::WindowsAndMessaging::{
+        AppendMenuW, CheckMenuItem, GetSystemMenu, MF_BYCOMMAND, MF_CHECKED, MF_SEPARATOR,
+        MF_STRING, MF_UNCHECKED, WM_SYSCOMMAND,
+    };
+
+    pub use windows_sys::Win32::System::Console::{
+        AllocConsole, AttachConsole, GetConsoleMode, SetConsoleMode, ATTACH_PARENT_PROCESS,
+        ENABLE_VIRTUAL_TERMINAL_PROCESSING,
+    };
+
+    pub use windows_sys::{
+        core::{GUID, HRESULT},
+        Win32::{
+            Graphics::Gdi::{CreateBitmap, BLACK_BRUSH, WHITE_BRUSH},
+            System::Com::{
+                CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED,
+            },
+            UI::{
+                Shell::{
+                    THBF_ENABLED, THBN_CLICKED, THB_FLAGS, THB_ICON, THB_TOOLTIP, THUMBBUTTON,
+                },
+                WindowsAndMessaging::{
+                    CreateIconIndirect, DestroyIcon, GetClassNameW, GetSystemMetrics, PostMessageW,
+                    RegisterWindowMessageW, HICON, ICONINFO, SM_CXSMICON, WM_COMMAND,
+                },
+            },
+        },
+    };
+
+    pub use windows_sys::Win32::UI::WindowsAndMessaging::{
+        GetWindowLongPtrW, SetWindowLongPtrW, GWLP_USERDATA,
+    };
+
+    pub use windows_sys::Win32::UI::{Shell::ShellExecuteW, WindowsAndMessaging::SW_SHOWNORMAL};
+
+    pub use windows_sys::Win32::UI::{
+        Shell::{
+            Shell_NotifyIconW, NIF_ICON, NIF_MESSAGE, NIF_TIP, NIM_ADD, NIM_DELETE, NIM_MODIFY,
+            NOTIFYICONDATAW,
+        },
+        WindowsAndMessaging::{
+            CheckMenuRadioItem, CreatePopupMenu, DestroyMenu, LoadIconW, TrackPopupMenu,
+            IDI_APPLICATION, MF_POPUP, TPM_RETURNCMD, TPM_RIGHTBUTTON, WM_APP, WM_CONTEXTMENU,
+            WM_NULL, WM_RBUTTONUP,
+        },
+    };
+
+    pub use windows_sys::Win32::{
+        Foundation::{INVALID_HANDLE_VALUE, WAIT_OBJECT_0},
+        Storage::FileSystem::{
+            FindCloseChangeNotification, FindFirstChangeNotificationW, FindNextChangeNotification,
+            FILE_NOTIFY_CHANGE_FILE_NAME, FILE_NOTIFY_CHANGE_LAST_WRITE,
+        },
+        UI::WindowsAndMessaging::{MsgWaitForMultipleObjectsEx, MWMO_INPUTAVAILABLE},
+    };
+
+    pub use windows_sys::Win32::UI::{
+        Accessibility::{SetWinEventHook, UnhookWinEvent, HWINEVENTHOOK},
+        WindowsAndMessaging::{
+            EVENT_SYSTEM_FOREGROUND, WINEVENT_OUTOFCONTEXT, WINEVENT_SKIPOWNPROCESS,
+        },
+    };
 }
 
-static mut IS_RUNNING: bool = true;
+/// `chardelay=`, in microseconds; 0 types a line all at once
+static CHAR_DELAY_MICROS: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
 
-#[inline(always)]
-fn is_runnig() -> bool {
-    unsafe { IS_RUNNING }
+/// Whether the app attached to `h_window` is still running; false once it is gone.
+fn is_running(h_window: win32::HWND) -> bool {
+    return app::get(h_window).is_some_and(|app| app.running.get());
 }
 
-#[inline(always)]
-fn set_is_running(to: bool) {
-    unsafe { IS_RUNNING = to };
+fn stop_running(h_window: win32::HWND) {
+    if let Some(app) = app::get(h_window) {
+        app.running.set(false);
+    }
 }
 
 extern "system" fn wndproc(
@@ -78,7 +323,42 @@ extern "system" fn wndproc(
     unsafe {
         match message {
             win32::WM_DESTROY | win32::WM_CLOSE => {
-                set_is_running(false);
+                stop_running(window);
+                return 0;
+            }
+            win32::WM_PAINT => {
+                paint::paint(window);
+                return 0;
+            }
+            // everything is drawn in WM_PAINT, erasing first would only flicker
+            win32::WM_ERASEBKGND => 1,
+            win32::WM_MOVING => {
+                dock::snap(&mut *(lparam as *mut win32::RECT));
+                return 1;
+            }
+            win32::WM_COPYDATA => shell::receive(window, lparam) as win32::LRESULT,
+            win32::WM_SYSCOMMAND if wparam & 0xFFF0 == snooze::MENU_ID => {
+                snooze::toggle(window);
+                return 0;
+            }
+            win32::WM_COMMAND if (wparam >> 16) & 0xFFFF == win32::THBN_CLICKED as usize => {
+                thumbbar::clicked(window, wparam & 0xFFFF);
+                return 0;
+            }
+            tray::CALLBACK => {
+                tray::handle(window, lparam);
+                return 0;
+            }
+            jumplist::TASK => {
+                jumplist::handle(window, wparam);
+                return 0;
+            }
+            _ if message == thumbbar::created_message() => {
+                thumbbar::add_buttons(window);
+                return 0;
+            }
+            _ if message == tray::created_message() => {
+                tray::restore(window);
                 return 0;
             }
             _ => win32::DefWindowProcA(window, message, wparam, lparam),
@@ -118,7 +398,13 @@ static WINDOW_CLASS_NAME: &'static [u16; 9] = wider_string();
 
 impl Window {
 
-    pub fn new(instance: win32::HINSTANCE, width: i32, height: i32) -> anyhow::Result<Self> {
+    pub fn new(
+        instance: win32::HINSTANCE,
+        width: i32,
+        height: i32,
+        visible: bool,
+        headless: bool,
+    ) -> anyhow::Result<Self> {
         let wc = win32::WNDCLASSW {
             style: win32::CS_OWNDC | win32::CS_VREDRAW | win32::CS_HREDRAW,
             lpfnWndProc: Some(wndproc),
@@ -159,6 +445,8 @@ impl Window {
             }
         }
 
+        // a message-only window gets hotkeys and WM_COPYDATA, and is never shown
+        let parent = if headless { win32::HWND_MESSAGE } else { 0 };
         let window = unsafe {
             Self {
                 h_window: win32::CreateWindowExW(
@@ -170,7 +458,7 @@ impl Window {
                     rect.top,
                     rect.right - rect.left,
                     rect.bottom - rect.top,
-                    0,
+                    parent,
                     0,
                     instance,
                     std::ptr::null_mut(),
@@ -187,6 +475,9 @@ impl Window {
             ));
         }
 
+        if headless {
+            return Ok(window);
+        }
         unsafe {
             win32::SetWindowPos(
                 window.h_window,
@@ -197,7 +488,9 @@ impl Window {
                 rect.bottom - rect.top,
                 win32::SWP_NOMOVE | win32::SWP_NOSIZE,
             );
-            let _ = win32::ShowWindow(window.h_window, win32::SW_SHOW);
+            if visible {
+                let _ = win32::ShowWindow(window.h_window, win32::SW_SHOW);
+            }
         };
 
         return Ok(window);
@@ -207,247 +500,2819 @@ impl Window {
 impl Drop for Window {
     fn drop(&mut self) {
         unsafe {
+            // wndproc must not reach the app from here on, it may be gone before the window
+            app::detach(self.h_window);
             win32::UnregisterClassW(WINDOW_CLASS_NAME.as_ptr(), self.h_instance);
             win32::DestroyWindow(self.h_window);
         }
     }
 }
 const SKILINE_NUMBER_SIZE: usize = 8;
+const WORDS_PATH: &str = "./words.txt";
+const SKIPLINE_PATH: &str = "./skipline.dat";
+const SESSION_CSV_PATH: &str = "./session.csv";
+const STATS_PATH: &str = "./wordstats.tsv";
+const LIST_TIMES_PATH: &str = "./listtime.tsv";
+const JOURNAL_PATH: &str = "./journal.tsv";
+const BANNED_PATH: &str = "./banned.txt";
+const FAVORITES_PATH: &str = "./favorites.txt";
+const BUNDLE_EXPORT_PATH: &str = "./drill.rword";
+const CADENCE_PATH: &str = "./cadence.csv";
+/// The day the daily goal was last met, as `YYYY-MM-DD`.
+const GOAL_PATH: &str = "./goal.dat";
+/// Its lists are cycled through without any `lists=`.
+const LISTS_DIR: &str = "./lists";
+/// Keeps console programs started by randword from flashing a window, randword has none.
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
 
-fn main() {
-    let instance = unsafe { win32::GetModuleHandleW(std::ptr::null()) };
-    debug_assert!(instance != 0);
-
-    let window = match Window::new(instance, 330, 100) {
-        Ok(k) => k,
-        Err(e) => unsafe {
-            let err = e.to_string();
-            win32::MessageBoxA(
-                0,
-                err.as_str().as_ptr(),
-                win32::s!("Window Error"),
-                win32::MB_OK | win32::MB_ICONEXCLAMATION,
-            );
-            return;
-        },
-    };
+const HOTKEY_DISPENSE: i32 = 1;
+const HOTKEY_EXPORT_SESSION: i32 = 2;
+const HOTKEY_RATE_UP: i32 = 3;
+const HOTKEY_RATE_DOWN: i32 = 4;
+const HOTKEY_EXPORT_BUNDLE: i32 = 5;
+const HOTKEY_UNDO: i32 = 6;
+const HOTKEY_PREVIOUS: i32 = 7;
+const HOTKEY_PEEK: i32 = 8;
+const HOTKEY_PICK: i32 = 9;
+/// past the ids of the jump hotkeys
+const HOTKEY_RESET: i32 = 30;
+const HOTKEY_GOTO: i32 = 31;
+const HOTKEY_NEXT_LIST: i32 = 32;
+const HOTKEY_DUPLICATES: i32 = 33;
+/// the default of `hotkey=`
+const HOTKEY_DISPENSE_KEYS: hotkey::Hotkey = hotkey::Hotkey::ctrl_alt(b'X');
+const HOTKEY_EXPORT_SESSION_KEYS: hotkey::Hotkey = hotkey::Hotkey::ctrl_alt(b'E');
+const HOTKEY_RATE_UP_KEYS: hotkey::Hotkey = hotkey::Hotkey::ctrl_alt(win32::VK_OEM_PLUS as u8);
+const HOTKEY_RATE_DOWN_KEYS: hotkey::Hotkey = hotkey::Hotkey::ctrl_alt(win32::VK_OEM_MINUS as u8);
+const HOTKEY_EXPORT_BUNDLE_KEYS: hotkey::Hotkey = hotkey::Hotkey::ctrl_alt(b'B');
+const HOTKEY_UNDO_KEYS: hotkey::Hotkey = hotkey::Hotkey::ctrl_alt(b'Z');
+const HOTKEY_PREVIOUS_KEYS: hotkey::Hotkey = hotkey::Hotkey {
+    modifiers: win32::MOD_CONTROL | win32::MOD_ALT | win32::MOD_SHIFT,
+    key: b'Z' as u32,
+};
+const HOTKEY_PEEK_KEYS: hotkey::Hotkey = hotkey::Hotkey::ctrl_alt(b'P');
+const HOTKEY_PICK_KEYS: hotkey::Hotkey = hotkey::Hotkey::ctrl_alt(b'V');
+const HOTKEY_RESET_KEYS: hotkey::Hotkey = hotkey::Hotkey::ctrl_alt(b'R');
+const HOTKEY_GOTO_KEYS: hotkey::Hotkey = hotkey::Hotkey::ctrl_alt(b'G');
+const HOTKEY_NEXT_LIST_KEYS: hotkey::Hotkey = hotkey::Hotkey::ctrl_alt(b'L');
+const HOTKEY_DUPLICATES_KEYS: hotkey::Hotkey = hotkey::Hotkey::ctrl_alt(b'D');
+/// The hotkeys besides dispensing: the name `hotkeys=` turns each on with, and what it is for.
+const HOTKEY_EXTRAS: [(&str, hotkey::Hotkey, i32, &str); 11] = [
+    ("export", HOTKEY_EXPORT_SESSION_KEYS, HOTKEY_EXPORT_SESSION, "export the session"),
+    ("rateup", HOTKEY_RATE_UP_KEYS, HOTKEY_RATE_UP, "rate the last word up"),
+    ("ratedown", HOTKEY_RATE_DOWN_KEYS, HOTKEY_RATE_DOWN, "rate the last word down"),
+    ("bundle", HOTKEY_EXPORT_BUNDLE_KEYS, HOTKEY_EXPORT_BUNDLE, "export the drill as a bundle"),
+    ("undo", HOTKEY_UNDO_KEYS, HOTKEY_UNDO, "undo the last word"),
+    ("previous", HOTKEY_PREVIOUS_KEYS, HOTKEY_PREVIOUS, "dispense the previous word again"),
+    ("peek", HOTKEY_PEEK_KEYS, HOTKEY_PEEK, "show the next word"),
+    ("pick", HOTKEY_PICK_KEYS, HOTKEY_PICK, "pick one of the next words"),
+    ("reset", HOTKEY_RESET_KEYS, HOTKEY_RESET, "start the list over"),
+    ("goto", HOTKEY_GOTO_KEYS, HOTKEY_GOTO, "go to a line"),
+    ("duplicates", HOTKEY_DUPLICATES_KEYS, HOTKEY_DUPLICATES, "go to a repeated line"),
+];
+/// The extras `kiosk` leaves unregistered: moving around, rating and handing out the list.
+const KIOSK_HIDDEN: [i32; 10] = [
+    HOTKEY_UNDO,
+    HOTKEY_RATE_UP,
+    HOTKEY_RATE_DOWN,
+    HOTKEY_EXPORT_BUNDLE,
+    HOTKEY_PREVIOUS,
+    HOTKEY_PEEK,
+    HOTKEY_PICK,
+    HOTKEY_RESET,
+    HOTKEY_GOTO,
+    HOTKEY_DUPLICATES,
+];
+/// Ctrl+Alt+n jumps forward 10^n lines, Ctrl+Alt+Shift+n back, for n in 1..=JUMP_DIGITS.
+const HOTKEY_JUMP_FORWARD: i32 = 10;
+const HOTKEY_JUMP_BACK: i32 = 20;
+const JUMP_DIGITS: i32 = 6;
+/// `bind=` hotkeys get the ids from here on, in the order given.
+const HOTKEY_BINDINGS: i32 = 100;
+/// the default of `delay=`
+const DISPENSE_DELAY_MS: u32 = 400;
+/// `delay=` is capped here; longer, and the focus is better moved before pressing the hotkey.
+const MAX_DELAY_MS: u32 = 5000;
+/// How often the countdown of the delay is updated and Esc looked at.
+const COUNTDOWN_TICK_MS: u32 = 50;
+/// Inputs sent at once when typing without `chardelay=`, so Esc can stop a long line between
+/// them.
+const TYPING_CHUNK: usize = 64;
+/// the default of `idle=`
+const IDLE_AFTER_MINUTES: u64 = 5;
+/// the default of `refresh=`
+const REFRESH_MINUTES: u64 = 10;
+/// How often lists in a folder that can't be watched are checked for edits made while running,
+/// and a download of the remote lists for being done.
+const LIST_WATCH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+/// Main loop period while something on the screen moves or a replay or `serve=` runs, and the
+/// longer one while the battery saver is on. Otherwise the loop sleeps until a message comes,
+/// a list is written or `idle_timeout` is up.
+const POLL_MS: u32 = 38;
+const BATTERY_SAVER_POLL_MS: u32 = 150;
 
-    unsafe {
-        // Register Ctrl+Alt+X as a global hotkey
-        if win32::RegisterHotKey(
-            window.h_window,
-            1,
-            win32::MOD_CONTROL | win32::MOD_ALT,
-            b'X' as u32,
-        ) == 0
-        {
-            win32::MessageBoxA(
-                0,
-                win32::s!("Unable to register a global hotkey"),
-                win32::s!("RegisterHotKey Error"),
-                win32::MB_OK | win32::MB_ICONEXCLAMATION,
-            );
-            return;
-        }
-    }
+struct Options {
+    /// `clip`: put words on the clipboard instead of typing them
+    use_clipboard: bool,
+    /// `paste`: put words on the clipboard and press Ctrl+V, much faster than typing a long
+    /// line and with no keyboard layout in the way
+    paste: bool,
+    /// `autoclip`: in type mode, send lines the layout can't type via the clipboard
+    auto_clip: bool,
+    /// `compact`: collapse the window to its caption and show "word • 123/4567" there
+    compact: bool,
+    /// `countdown`: count `delay=` down in the window title too, for when there is no overlay
+    countdown: bool,
+    /// `hidden` or `--hidden`: no window at all, only the hotkeys
+    hidden: bool,
+    /// `headless` or `--headless`: a message-only window, for starting from Task Scheduler at
+    /// logon; only the hotkeys and `randword <list>` reach it
+    headless: bool,
+    /// `autohide`: slide the window off the screen edge it is docked to until hovered
+    auto_hide: bool,
+    /// `quiet=9:00-17:00[/weekdays]`: hotkeys are unregistered during these hours
+    quiet_hours: Option<schedule::QuietHours>,
+    /// `raw=<device>,<key>`: a key on one specific keyboard also dispenses
+    raw_trigger: Option<rawinput::RawTrigger>,
+    /// `practice` or `practice=<file>`: time the keys of each word typed by hand; only the
+    /// keys of the word are read, see `cadence::Practice`
+    practice: Option<cadence::Practice>,
+    /// `jump`: register the Ctrl+Alt+digit hotkeys that skip through the list
+    jump: bool,
+    /// `hotkeys=<name>,...`, repeatable: also register these of `HOTKEY_EXTRAS`, e.g.
+    /// `hotkeys=undo,peek`, or `hotkeys=all`; only the dispense hotkey is taken without it
+    extra_hotkeys: Vec<i32>,
+    /// `kiosk`: only sequential dispensing; no jumping, undoing, rating, exporting or switching
+    /// lists, and no `raw=`, for exams
+    kiosk: bool,
+    /// `random`: dispense a random line each time; the saved position stays where it was
+    random: bool,
+    /// `coverage`: like `random`, but the lines dispensed least, per wordstats.tsv, more
+    /// likely
+    coverage: bool,
+    /// `norepeat=<n>`: in random mode, none of the last `n` lines picked comes again
+    no_repeat: usize,
+    /// `shuffle`: deal every line once in a shuffled order, kept in `skipline.deck` next to
+    /// the position, then reshuffle
+    shuffle: bool,
+    /// `statedir=<dir>`: share skipline.dat in `dir` with other instances, and a
+    /// `<file>.skipline.dat` for each `bind=` list
+    state_dir: Option<String>,
+    /// `list=<file>`: use `file` instead of ./words.txt; each list has its own position
+    list: Option<String>,
+    /// `skip=<n>` or `--skip <n>`: start after the first `n` lines instead of at the saved
+    /// position
+    skip: Option<u64>,
+    /// `lists=<file or folder>`, repeatable: the lists Ctrl+Alt+L cycles through, starting
+    /// with the one in use; a folder adds its .txt files
+    lists: Vec<String>,
+    /// the `list=` and `bind=` lists given as URLs; the options refer to their local copies
+    remotes: Vec<remote::Remote>,
+    /// `refresh=<minutes>`: how often the lists given as URLs are downloaded again
+    refresh: std::time::Duration,
+    /// `skipline=<file>`: keep the position of the starting list in `file` instead
+    skipline: Option<String>,
+    /// `hotkey=<keys>`: dispense with e.g. `ctrl+shift+f5` instead of Ctrl+Alt+X
+    dispense_keys: hotkey::Hotkey,
+    /// whether `hotkey=` was given; a later instance only looks for free keys without it
+    hotkey_given: bool,
+    /// `instance=<name>`: run next to another randword on purpose, e.g. one per config; a
+    /// `list=` is not handed to the running one then, the name goes in the title, and the
+    /// stats, positions and journal are files of its own. A second instance needs one.
+    instance_name: Option<String>,
+    /// which of the running randwords this is, 0 for the first; set at startup
+    instance: usize,
+    /// `delay=<ms>`, 0 to 5000: wait this long after the hotkey, for its modifiers to be
+    /// released and the focus to be moved to a slow app; the overlay counts it down and Esc
+    /// cancels the word meanwhile
+    delay: u32,
+    /// `chardelay=<ms>`: wait this long between typed chars, e.g. `chardelay=2.5` for a
+    /// terminal that drops fast input
+    char_delay: std::time::Duration,
+    /// `then=<key>`: press this key after every typed or pasted word, e.g. `then=enter` to
+    /// submit a form or `then=tab` for the next field; undo doesn't erase words then
+    then_key: Option<u16>,
+    /// `idle=<minutes>`: without input for this long the user is away and the time per list
+    /// stops counting; `idle=0` counts everything
+    idle_after: Option<std::time::Duration>,
+    /// `presentation=normal`: show warnings that weren't asked for even while presenting
+    quiet_when_presenting: bool,
+    /// `dnd=normal`: show them even while Focus Assist is on; the words go out either way
+    quiet_when_dnd: bool,
+    /// `ime=type`: type words into an open IME too, instead of pasting them
+    paste_into_ime: bool,
+    /// `batterysaver=normal`: keep polling at the full rate on battery saver
+    slow_on_battery_saver: bool,
+    /// `bind=<keys>,<file>[,<mode>]`, repeatable: a channel, another hotkey dispensing from
+    /// its own list with its own position, in its own mode if given, e.g.
+    /// `bind=ctrl+alt+c,templates.txt,clip` or `bind=ctrl+alt+s,spelling.txt,nato`
+    bindings: Vec<(hotkey::Hotkey, String, Option<rules::Mode>)>,
+    /// `serve=<port>`: answer `GET /status` and `GET /metrics` on 127.0.0.1:<port>
+    serve: Option<u16>,
+    /// `record=<file>`: write every dispense with its timing to `file`
+    record: Option<String>,
+    /// `replay=<file>`: the dispense hotkey plays back a recording instead of the list
+    replay: Option<String>,
+    /// `before=<command>`: run and waited for before each word is emitted
+    before_hook: Option<hooks::Hook>,
+    /// `after=<command>`: started after each word is emitted
+    after_hook: Option<hooks::Hook>,
+    /// `rule=<title>|<mode>|<template>`: emit differently depending on the focused window
+    rules: Vec<rules::Rule>,
+    /// `out=emit|type|clip|paste|<file>[,time|,json]`, repeatable: where each word goes, in order
+    outputs: output::Pipeline,
+    /// `confirmkeys`: ask before typing a line that presses keys like {ENTER} or {F5}
+    confirm_keys: bool,
+    /// `osd`, `osd=caret` or `osd=corner`: flash each word in an overlay near the cursor, under
+    /// the text caret or in the corner
+    osd: Option<osd::Placement>,
+    /// `webhook=<https url>`: POST finished lists, the daily goal and errors there as JSON
+    webhook: Option<String>,
+    /// `goal=<words>`: the daily goal is met once this many different words were dispensed
+    /// on a day
+    goal: Option<usize>,
+    /// every option in effect, from the config files and the command line with bundles
+    /// unpacked, for exporting a bundle
+    args: Vec<String>,
+}
 
-    let mut file_line =
-        match std::fs::OpenOptions::new().read(true).write(true).open("./skipline.dat") {
-            Ok(f) => f,
-            Err(e) => {
-                let err = e.to_string();
-                unsafe {
-                    win32::MessageBoxA(
-                        0,
-                        err.as_str().as_ptr() as _,
-                        win32::s!("File Open Error"),
-                        win32::MB_OK | win32::MB_ICONEXCLAMATION,
-                    )
-                };
-                return;
-            }
+impl Options {
+    fn from_args(profile: Option<&str>) -> anyhow::Result<Self> {
+        let mut options = Self {
+            use_clipboard: false,
+            paste: false,
+            auto_clip: false,
+            compact: false,
+            countdown: false,
+            hidden: false,
+            headless: false,
+            auto_hide: false,
+            quiet_hours: None,
+            raw_trigger: None,
+            practice: None,
+            jump: false,
+            extra_hotkeys: Vec::new(),
+            kiosk: false,
+            random: false,
+            coverage: false,
+            no_repeat: 0,
+            shuffle: false,
+            state_dir: None,
+            list: None,
+            skip: None,
+            lists: Vec::new(),
+            remotes: Vec::new(),
+            refresh: std::time::Duration::from_secs(REFRESH_MINUTES * 60),
+            skipline: None,
+            dispense_keys: HOTKEY_DISPENSE_KEYS,
+            hotkey_given: false,
+            instance_name: None,
+            then_key: None,
+            instance: 0,
+            delay: DISPENSE_DELAY_MS,
+            char_delay: std::time::Duration::ZERO,
+            idle_after: Some(std::time::Duration::from_secs(IDLE_AFTER_MINUTES * 60)),
+            quiet_when_presenting: true,
+            quiet_when_dnd: true,
+            paste_into_ime: true,
+            slow_on_battery_saver: true,
+            bindings: Vec::new(),
+            serve: None,
+            record: None,
+            replay: None,
+            before_hook: None,
+            after_hook: None,
+            rules: Vec::new(),
+            outputs: output::Pipeline::default(),
+            confirm_keys: false,
+            osd: None,
+            webhook: None,
+            goal: None,
+            args: Vec::new(),
         };
-
-    let mut lines_to_skip = {
-        let mut file_line_buff = [b'0'; SKILINE_NUMBER_SIZE];
-        if let Err(e) = file_line.read_exact(&mut file_line_buff) {
-            let err = e.to_string();
-            unsafe {
-                win32::MessageBoxA(
-                    0,
-                    err.as_str().as_ptr() as _,
-                    win32::s!("File Read Error"),
-                    win32::MB_OK | win32::MB_ICONEXCLAMATION,
-                )
-            };
+        let mut command_line = Vec::new();
+        for arg in cli::options(std::env::args().skip(1))? {
+            if arg.ends_with(".rword") {
+                command_line.extend(bundle::open(&arg)?);
+            } else {
+                command_line.push(arg);
+            }
         }
-        // eprintln!("{:?}", std::str::from_utf8(&file_line_buff));
-        parse_lines_to_skip(&file_line_buff)
-    };
-    // dbg!(&lines_to_skip);
-    let mut ifile = match std::fs::File::open("./words.txt") {
-        Ok(f) => std::io::BufReader::new(f),
-        Err(e) => {
-            let err = e.to_string();
-            unsafe {
-                win32::MessageBoxA(
-                    0,
-                    err.as_str().as_ptr() as _,
-                    win32::s!("File Open Error"),
-                    win32::MB_OK | win32::MB_ICONEXCLAMATION,
-                )
-            };
-            return;
+        // what the options set outside of them starts over, for a profile switch
+        keymap::reset();
+        signal::reset();
+        humanize::reset();
+        clipboard::reset();
+        let args = config::layered(command_line, profile)?;
+        for arg in &args {
+            options.apply(&arg.text).map_err(|e| anyhow::anyhow!("{}: {}", arg.origin, e))?;
         }
-    };
-    let mut buffer = String::with_capacity(128);
-    {
-        let mut buffer = Vec::with_capacity(128);
-        for _ in 0..lines_to_skip {
-            buffer.clear();
-            let _ = ifile.read_until(b'\n', &mut buffer);
+        if options.kiosk {
+            options.jump = false;
+            options.random = false;
+            options.coverage = false;
+            // only the dispense hotkey dispenses, not a second keyboard slipped in
+            options.raw_trigger = None;
+            options.shuffle = false;
+        }
+        if options.lists.is_empty() && std::path::Path::new(LISTS_DIR).is_dir() {
+            options.lists = lists_in(LISTS_DIR)?;
         }
+        if !options.lists.is_empty() && !options.lists.iter().any(|l| l == options.words_path()) {
+            options.lists.insert(0, options.words_path().to_owned());
+        }
+        options.args = args.into_iter().map(|arg| arg.text).collect();
+        return Ok(options);
     }
 
-    let mut use_clipboard = false;
+    /// Sets the option `arg`, e.g. `clip` or `list=words.txt`.
+    fn apply(&mut self, arg: &str) -> anyhow::Result<()> {
+        match arg {
+            "clip" => self.use_clipboard = true,
+            "paste" => self.paste = true,
+            "restoreclip" => clipboard::configure(std::time::Duration::from_secs(
+                clipboard::DEFAULT_RESTORE_SECS,
+            )),
+            "autoclip" => self.auto_clip = true,
+            "compact" => self.compact = true,
+            "countdown" => self.countdown = true,
+            "hidden" => self.hidden = true,
+            "headless" => self.headless = true,
+            "autohide" => self.auto_hide = true,
+            "jump" => self.jump = true,
+            "kiosk" => self.kiosk = true,
+            "random" => self.random = true,
+            "coverage" => self.coverage = true,
+            "shuffle" => self.shuffle = true,
+            "confirmkeys" => self.confirm_keys = true,
+            "osd" => self.osd = Some(osd::Placement::Cursor),
+            "practice" => self.practice = Some(cadence::Practice::new(CADENCE_PATH)),
+            "osd=caret" => self.osd = Some(osd::Placement::Caret),
+            "osd=corner" => self.osd = Some(osd::Placement::Corner),
+            "presentation=normal" => self.quiet_when_presenting = false,
+            "dnd=normal" => self.quiet_when_dnd = false,
+            "ime=type" => self.paste_into_ime = false,
+            "batterysaver=normal" => self.slow_on_battery_saver = false,
+            _ => {
+                if let Some(quiet) = arg.strip_prefix("quiet=") {
+                    self.quiet_hours = Some(schedule::QuietHours::parse(quiet)?);
+                } else if let Some(raw) = arg.strip_prefix("raw=") {
+                    self.raw_trigger = Some(rawinput::RawTrigger::parse(raw)?);
+                } else if let Some(path) = arg.strip_prefix("practice=") {
+                    self.practice = Some(cadence::Practice::new(path));
+                } else if let Some(path) = arg.strip_prefix("keymap=") {
+                    keymap::load(path)?;
+                } else if let Some(range) = arg.strip_prefix("humanize=") {
+                    humanize::configure(range)?;
+                } else if let Some(seconds) = arg.strip_prefix("restoreclip=") {
+                    let Ok(seconds) = seconds.parse() else {
+                        return Err(anyhow::anyhow!("restoreclip: `{}` is not seconds", seconds));
+                    };
+                    clipboard::configure(std::time::Duration::from_secs(seconds));
+                } else if let Some(key) = arg.strip_prefix("signal=") {
+                    signal::configure(key)?;
+                } else if let Some(dir) = arg.strip_prefix("statedir=") {
+                    self.state_dir = Some(dir.to_owned());
+                } else if let Some(list) = arg.strip_prefix("list=") {
+                    self.list = Some(self.local_list(list)?);
+                } else if let Some(lines) = arg.strip_prefix("skip=") {
+                    let Ok(lines) = lines.parse() else {
+                        return Err(anyhow::anyhow!("skip: `{}` is not a line count", lines));
+                    };
+                    self.skip = Some(lines);
+                } else if let Some(lines) = arg.strip_prefix("norepeat=") {
+                    let Ok(lines) = lines.parse() else {
+                        return Err(anyhow::anyhow!("norepeat: `{}` is not a line count", lines));
+                    };
+                    self.no_repeat = lines;
+                } else if let Some(lists) = arg.strip_prefix("lists=") {
+                    if std::path::Path::new(lists).is_dir() {
+                        self.lists.extend(lists_in(lists)?);
+                    } else {
+                        let list = self.local_list(lists)?;
+                        self.lists.push(list);
+                    }
+                } else if let Some(minutes) = arg.strip_prefix("refresh=") {
+                    let Ok(minutes) = minutes.parse::<u64>() else {
+                        return Err(anyhow::anyhow!("refresh: bad minutes `{}`", minutes));
+                    };
+                    let seconds = minutes.max(1) * 60;
+                    self.refresh = std::time::Duration::from_secs(seconds);
+                } else if let Some(path) = arg.strip_prefix("skipline=") {
+                    self.skipline = Some(path.to_owned());
+                } else if let Some(keys) = arg.strip_prefix("hotkey=") {
+                    self.dispense_keys = hotkey::Hotkey::parse(keys)?;
+                    self.hotkey_given = true;
+                } else if let Some(names) = arg.strip_prefix("hotkeys=") {
+                    for name in names.split(',').map(str::trim) {
+                        let extras =
+                            HOTKEY_EXTRAS.iter().filter(|&&(n, ..)| name == "all" || n == name);
+                        let ids: Vec<i32> = extras.map(|&(_, _, id, _)| id).collect();
+                        if ids.is_empty() {
+                            let names: Vec<&str> = HOTKEY_EXTRAS.iter().map(|&(n, ..)| n).collect();
+                            return Err(anyhow::anyhow!(
+                                "hotkeys: unknown hotkey `{}`, expected all or {}",
+                                name,
+                                names.join(", ")
+                            ));
+                        }
+                        self.extra_hotkeys.extend(ids);
+                    }
+                } else if let Some(name) = arg.strip_prefix("instance=") {
+                    // it goes in file names
+                    if name.is_empty()
+                        || name.contains(['\\', '/', ':', '*', '?', '"', '<', '>', '|'])
+                    {
+                        return Err(anyhow::anyhow!(
+                            "instance: `{}` can't be part of a file name",
+                            name
+                        ));
+                    }
+                    self.instance_name = Some(name.to_owned());
+                } else if let Some(delay) = arg.strip_prefix("delay=") {
+                    let Some(delay) = delay.parse().ok().filter(|&ms| ms <= MAX_DELAY_MS) else {
+                        return Err(anyhow::anyhow!(
+                            "delay: expected 0 to {} milliseconds, got `{}`",
+                            MAX_DELAY_MS,
+                            delay
+                        ));
+                    };
+                    self.delay = delay;
+                } else if let Some(millis) = arg.strip_prefix("chardelay=") {
+                    let Ok(delay) = millis.parse::<f64>().map(|ms| ms / 1000.0) else {
+                        return Err(anyhow::anyhow!("chardelay: `{}` is not milliseconds", millis));
+                    };
+                    self.char_delay = std::time::Duration::try_from_secs_f64(delay)?;
+                } else if let Some(minutes) = arg.strip_prefix("idle=") {
+                    let Ok(minutes) = minutes.parse::<u64>() else {
+                        return Err(anyhow::anyhow!("idle: `{}` is not minutes", minutes));
+                    };
+                    self.idle_after = match minutes {
+                        0 => None,
+                        _ => Some(std::time::Duration::from_secs(minutes * 60)),
+                    };
+                } else if let Some(binding) = arg.strip_prefix("bind=") {
+                    let Some((keys, path)) = binding.split_once(',') else {
+                        return Err(anyhow::anyhow!(
+                            "bind: expected `<keys>,<file>[,<mode>]`, got `{}`",
+                            binding
+                        ));
+                    };
+                    // a file name may contain commas, so only a known mode is split off
+                    let (path, mode) = match path.rsplit_once(',') {
+                        Some((p, m)) if rules::Mode::parse(m).is_some() => {
+                            (p, rules::Mode::parse(m))
+                        }
+                        _ => (path, None),
+                    };
+                    let keys = hotkey::Hotkey::parse(keys)?;
+                    let path = self.local_list(path)?;
+                    self.bindings.push((keys, path, mode));
+                } else if let Some(name) = arg.strip_prefix("then=") {
+                    let Some(vkey) = tokens::key(name) else {
+                        return Err(anyhow::anyhow!("then: unknown key `{}`", name));
+                    };
+                    self.then_key = Some(vkey);
+                } else if let Some(mode) = arg.strip_prefix("mode=") {
+                    (self.use_clipboard, self.paste) = match rules::Mode::parse(mode) {
+                        Some(rules::Mode::Type) => (false, false),
+                        Some(rules::Mode::Clip) => (true, false),
+                        Some(rules::Mode::Paste) => (false, true),
+                        _ => {
+                            return Err(anyhow::anyhow!(
+                                "mode: expected type, clip or paste, got `{}`",
+                                mode
+                            ))
+                        }
+                    };
+                } else if let Some(port) = arg.strip_prefix("serve=") {
+                    let Ok(port) = port.parse() else {
+                        return Err(anyhow::anyhow!("serve: `{}` is not a port number", port));
+                    };
+                    self.serve = Some(port);
+                } else if let Some(path) = arg.strip_prefix("record=") {
+                    self.record = Some(path.to_owned());
+                } else if let Some(path) = arg.strip_prefix("replay=") {
+                    self.replay = Some(path.to_owned());
+                } else if let Some(command) = arg.strip_prefix("before=") {
+                    self.before_hook = Some(hooks::Hook::new(command));
+                } else if let Some(command) = arg.strip_prefix("after=") {
+                    self.after_hook = Some(hooks::Hook::new(command));
+                } else if let Some(rule) = arg.strip_prefix("rule=") {
+                    self.rules.push(rules::Rule::parse(rule)?);
+                } else if let Some(out) = arg.strip_prefix("out=") {
+                    self.outputs.push(output::Output::parse(out));
+                } else if let Some(url) = arg.strip_prefix("webhook=") {
+                    if !remote::is_https(url) {
+                        return Err(anyhow::anyhow!(
+                            "webhook: expected an https:// URL, got `{}`",
+                            url
+                        ));
+                    }
+                    self.webhook = Some(url.to_owned());
+                } else if let Some(words) = arg.strip_prefix("goal=") {
+                    let Ok(words) = words.parse() else {
+                        return Err(anyhow::anyhow!("goal: `{}` is not a word count", words));
+                    };
+                    self.goal = Some(words);
+                } else {
+                    return Err(anyhow::anyhow!("unknown option `{}`, see randword --help", arg));
+                }
+            }
+        }
+        return Ok(());
+    }
 
-    let mut args = std::env::args();
-    let _ = args.next();
-    if let Some(arg) = args.next() {
-        if arg == "clip" {
-            use_clipboard = true;
+    /// `list` itself, or the local copy if it is a URL.
+    fn local_list(&mut self, list: &str) -> anyhow::Result<String> {
+        if !remote::is_url(list) {
+            return Ok(list.to_owned());
         }
+        let remote = remote::Remote::new(list)?;
+        let copy = remote.copy.clone();
+        self.remotes.push(remote);
+        return Ok(copy);
     }
 
-    while is_runnig() {
-        poll_event(window.h_window, &mut ifile, &mut buffer, &mut lines_to_skip, use_clipboard);
-        unsafe { win32::Sleep(38) };
+    fn words_path(&self) -> &str {
+        return self.list.as_deref().unwrap_or(WORDS_PATH);
     }
 
-    unsafe { win32::UnregisterHotKey(window.h_window, 1) };
+    /// Where this instance keeps the state file `path`: as is, or `<stem>.<name>.<ext>` with
+    /// `instance=<name>`, so named instances don't write over each other's.
+    fn state_path(&self, path: &str) -> String {
+        let Some(name) = &self.instance_name else {
+            return path.to_owned();
+        };
+        return match path.rsplit_once('.') {
+            Some((stem, ext)) if !stem.is_empty() => format!("{}.{}.{}", stem, name, ext),
+            _ => format!("{}.{}", path, name),
+        };
+    }
 
-    let skipline_array = u64_to_array::<8>(lines_to_skip);
-    let _ = file_line.seek(std::io::SeekFrom::Start(0));
-    let _ = file_line.write_all(&skipline_array);
-}
+    fn skipline_path(&self) -> String {
+        return self.skipline.clone().unwrap_or_else(|| skipline_path(self.words_path()));
+    }
 
-fn u64_to_array<const N: usize>(mut num: u64) -> [u8; N] {
-    debug_assert!(N <= 8);
-    let mut buf = [0_u8; N];
+    fn is_quiet_now(&self) -> bool {
+        return self.quiet_hours.as_ref().is_some_and(|q| q.is_quiet_now());
+    }
 
-    for ele in buf.iter_mut().rev() {
-        *ele = b'0' + (num % 10) as u8;
-        num /= 10;
+    /// Warnings that weren't asked for wait while presenting or with do not disturb on.
+    fn holds_warnings(&self) -> bool {
+        return (self.quiet_when_presenting && power::is_presenting())
+            || (self.quiet_when_dnd && power::is_do_not_disturb());
+    }
+}
 
-        if num == 0 {
-            break;
-        }
+/// Returns false when the dispense hotkey could not be registered, after telling the user.
+fn register_hotkeys(h_window: win32::HWND, options: &Options) -> bool {
+    if !options.dispense_keys.register(h_window, HOTKEY_DISPENSE) {
+        let error_code = unsafe { win32::GetLastError() };
+        message_box(
+            &options.dispense_keys.diagnose(h_window, error_code, "dispense the next word"),
+            "RegisterHotKey Error",
+            win32::MB_OK | win32::MB_ICONEXCLAMATION,
+        );
+        return false;
     }
 
-    let mut res = [0; N];
-    let mut i = 0;
-    for ele in buf {
-        if ele != 0 {
-            res[i] = ele;
-            i += 1;
+    // the rest is optional; the app is still usable without them, and in a later instance the
+    // first one holds most of them anyway, so they are reported together and only by the first
+    let report = options.instance == 0;
+    let mut failed = Vec::new();
+    for (i, (keys, path, _)) in options.bindings.iter().enumerate() {
+        if !keys.register(h_window, HOTKEY_BINDINGS + i as i32) {
+            failed.push(format!("{} (dispense from {})", keys.name(), path));
         }
     }
-
-    return res;
-}
-
-fn poll_event(
-    h_window: isize,
-    ifile: &mut std::io::BufReader<std::fs::File>,
-    buffer: &mut String,
-    linse_to_skip: &mut u64,
-    use_clipboard: bool,
-) {
-    let mut msg = unsafe { std::mem::zeroed() };
-    while unsafe { win32::PeekMessageW(&mut msg, h_window, 0, 0, win32::PM_REMOVE) != 0 } {
-        if msg.message == win32::WM_QUIT {
-            set_is_running(false);
-            return;
+    for (_, keys, id, purpose) in HOTKEY_EXTRAS {
+        if !options.extra_hotkeys.contains(&id) {
+            continue;
+        }
+        // a bind= on the same keys wins over the built-in action
+        if options.bindings.iter().any(|(bound, _, _)| *bound == keys) {
+            continue;
+        }
+        if options.kiosk && KIOSK_HIDDEN.contains(&id) {
+            continue;
+        }
+        if !keys.register(h_window, id) && report {
+            failed.push(format!("{} ({})", keys.name(), purpose));
         }
+    }
+    if options.lists.len() > 1
+        && !options.kiosk
+        && !HOTKEY_NEXT_LIST_KEYS.register(h_window, HOTKEY_NEXT_LIST)
+        && report
+    {
+        failed.push(format!("{} (switch to the next list)", HOTKEY_NEXT_LIST_KEYS.name()));
+    }
 
-        if msg.message == win32::WM_HOTKEY {
-            if msg.wParam == 1 {
-                unsafe { win32::Sleep(400) };
-                buffer.clear();
-                let size = ifile.read_line(buffer).expect("to read successfully");
-                if size == 0 {
-                    *linse_to_skip = 0;
-                    ifile.rewind().expect("to rewind to the beginig of word.txt");
-                } else {
-                    let line_slice = &buffer[..size - 1];
-                    // dbg!(*linse_to_skip);
-                    *linse_to_skip += 1;
-                    // dbg!(line_slice);
-                    if use_clipboard {
-                        set_clipboard_string(line_slice);
-                    } else {
-                        type_out_characters(line_slice);
-                    }
+    if options.jump {
+        let mut jumps = Vec::new();
+        for n in 1..=JUMP_DIGITS {
+            let forward = hotkey::Hotkey::ctrl_alt(b'0' + n as u8);
+            let back =
+                hotkey::Hotkey { modifiers: forward.modifiers | win32::MOD_SHIFT, ..forward };
+            let ids = [HOTKEY_JUMP_FORWARD + n, HOTKEY_JUMP_BACK + n];
+            for (keys, id) in [forward, back].into_iter().zip(ids) {
+                if !keys.register(h_window, id) {
+                    jumps.push(keys.name());
                 }
-                break;
             }
         }
-        unsafe { win32::DispatchMessageW(&msg) };
+        if !jumps.is_empty() && report {
+            failed.push(format!("{} (jump through the list)", jumps.join(", ")));
+        }
     }
+    if !failed.is_empty() {
+        message_box(
+            &format!(
+                "Unable to register these hotkeys, likely held by another running program:\n\n{}\n\n\
+                 The others work as before; `randword doctor` tells which are free.",
+                failed.join("\n")
+            ),
+            "RegisterHotKey Error",
+            win32::MB_OK | win32::MB_ICONEXCLAMATION,
+        );
+    }
+    return true;
 }
 
-fn lobyte(w: u64) -> u8 {
-    (w & 0xff) as u8
+fn unregister_hotkeys(h_window: win32::HWND, options: &Options) {
+    unsafe {
+        win32::UnregisterHotKey(h_window, HOTKEY_DISPENSE);
+        win32::UnregisterHotKey(h_window, HOTKEY_EXPORT_SESSION);
+        win32::UnregisterHotKey(h_window, HOTKEY_RATE_UP);
+        win32::UnregisterHotKey(h_window, HOTKEY_RATE_DOWN);
+        win32::UnregisterHotKey(h_window, HOTKEY_EXPORT_BUNDLE);
+        win32::UnregisterHotKey(h_window, HOTKEY_UNDO);
+        win32::UnregisterHotKey(h_window, HOTKEY_PREVIOUS);
+        win32::UnregisterHotKey(h_window, HOTKEY_PEEK);
+        win32::UnregisterHotKey(h_window, HOTKEY_PICK);
+        win32::UnregisterHotKey(h_window, HOTKEY_RESET);
+        win32::UnregisterHotKey(h_window, HOTKEY_GOTO);
+        win32::UnregisterHotKey(h_window, HOTKEY_NEXT_LIST);
+        win32::UnregisterHotKey(h_window, HOTKEY_DUPLICATES);
+        for n in 1..=JUMP_DIGITS {
+            win32::UnregisterHotKey(h_window, HOTKEY_JUMP_FORWARD + n);
+            win32::UnregisterHotKey(h_window, HOTKEY_JUMP_BACK + n);
+        }
+        for i in 0..options.bindings.len() {
+            win32::UnregisterHotKey(h_window, HOTKEY_BINDINGS + i as i32);
+        }
+    };
 }
 
-fn type_out_characters(line_slice: &str) {
-    for &chr in line_slice.as_bytes() {
-        let vkey = unsafe { win32::VkKeyScanW(chr as u16) };
-        if vkey <= -1 {
+fn message_box(text: &str, caption: &str, style: u32) {
+    let text: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+    let caption: Vec<u16> = caption.encode_utf16().chain(std::iter::once(0)).collect();
+    unsafe { win32::MessageBoxW(0, text.as_ptr(), caption.as_ptr(), style) };
+}
+
+/// A Yes/No question; true for Yes.
+fn confirm(text: &str, caption: &str) -> bool {
+    let text: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+    let caption: Vec<u16> = caption.encode_utf16().chain(std::iter::once(0)).collect();
+    let style = win32::MB_YESNO | win32::MB_ICONEXCLAMATION | win32::MB_TOPMOST;
+    let answer = unsafe { win32::MessageBoxW(0, text.as_ptr(), caption.as_ptr(), style) };
+    return answer == win32::IDYES;
+}
+
+fn main() {
+    if update::apply_staged() {
+        return;
+    }
+    if cli::wants_help() {
+        message_box(cli::USAGE, "randword", win32::MB_OK | win32::MB_ICONINFORMATION);
+        return;
+    }
+    if std::env::args().nth(1).as_deref() == Some("bench") {
+        bench::run();
+        return;
+    }
+    if std::env::args().nth(1).as_deref() == Some("stats") {
+        stats::run(STATS_PATH, LIST_TIMES_PATH);
+        return;
+    }
+    if let Some(command @ ("install" | "uninstall")) = std::env::args().nth(1).as_deref() {
+        shell::run(command == "install");
+        return;
+    }
+    if std::env::args().nth(1).as_deref() == Some("settings") {
+        #[cfg(feature = "gui")]
+        gui::run();
+        #[cfg(not(feature = "gui"))]
+        message_box(
+            "This build has no settings window, it is built with `--features gui`",
+            "Settings",
+            win32::MB_OK | win32::MB_ICONINFORMATION,
+        );
+        return;
+    }
+    if std::env::args().nth(1).as_deref() == Some("quit") {
+        shell::quit_running();
+        return;
+    }
+    if std::env::args().nth(1).as_deref() == Some("task") {
+        jumplist::send(std::env::args().nth(2).as_deref());
+        return;
+    }
+    if std::env::args().nth(1).as_deref() == Some("update") {
+        update::run(std::env::args().nth(2).as_deref());
+        return;
+    }
+
+    let mut options = match Options::from_args(None) {
+        Ok(o) => o,
+        Err(e) => {
+            message_box(&e.to_string(), "Argument Error", win32::MB_OK | win32::MB_ICONEXCLAMATION);
+            return;
+        }
+    };
+    share_options(&options);
+    if std::env::args().nth(1).as_deref() == Some("doctor") {
+        doctor::run(&options);
+        return;
+    }
+    if std::env::args().nth(1).as_deref() == Some("check") {
+        check::run(options.words_path());
+        return;
+    }
+    if std::env::args().nth(1).as_deref() == Some("shape") {
+        shape::run(options.words_path());
+        return;
+    }
+    for remote in &options.remotes {
+        // without the download, the copy from the last run is used until a refresh works
+        if let Err(e) = remote.update() {
+            message_box(
+                &format!("{}: {}", remote.url, e),
+                "Download Error",
+                win32::MB_OK | win32::MB_ICONEXCLAMATION,
+            );
+        }
+    }
+    if let (Some(list), None) = (&options.list, &options.instance_name) {
+        if shell::send_to_running(list) {
+            return;
+        }
+    }
+    options.instance = instance::claim();
+    if options.instance > 0 && options.instance_name.is_none() {
+        message_box(
+            "randword is running already. To run another one next to it, give it a name with \
+             instance=<name>; it then keeps its own stats and positions.",
+            "randword",
+            win32::MB_OK | win32::MB_ICONINFORMATION,
+        );
+        return;
+    }
+    // while another instance writes to the journal, this one does without
+    let journaled = journal::claim(&options.state_path(JOURNAL_PATH));
+
+    migrate::upgrade(&options);
+    if journaled && !recover_journal(&options) {
+        return;
+    }
+
+    if std::env::args().nth(1).as_deref() == Some("tui") {
+        let Some((mut list, local)) = open_list(&options) else {
+            return;
+        };
+        let Some(mut stats) = load_stats(&options, journaled) else {
+            return;
+        };
+        let mut session = session::Session::new();
+        tui::run(&mut list, &options, &mut session, &mut stats);
+        save_progress(&options, &list, local, &session, &mut stats);
+        return;
+    }
+
+    let instance = unsafe { win32::GetModuleHandleW(std::ptr::null()) };
+    debug_assert!(instance != 0);
+
+    // a client area of height 0 leaves just the caption bar
+    let height = if options.compact { 0 } else { 100 };
+    let visible = !options.hidden && !options.headless;
+    // declared before the window, which has to be dropped first
+    let app = app::App::new();
+    let window = match Window::new(instance, 330, height, visible, options.headless) {
+        Ok(k) => k,
+        Err(e) => unsafe {
+            let err = e.to_string();
+            win32::MessageBoxA(
+                0,
+                err.as_str().as_ptr(),
+                win32::s!("Window Error"),
+                win32::MB_OK | win32::MB_ICONEXCLAMATION,
+            );
+            return;
+        },
+    };
+
+    app.attach(window.h_window);
+
+    if options.instance > 0 && !options.hotkey_given {
+        options.dispense_keys = instance::negotiate(window.h_window, options.dispense_keys);
+    }
+    show_keys(window.h_window, &options);
+    let _foreground = thumbbar::ForegroundHook::set(window.h_window);
+
+    if !options.headless {
+        snooze::add_menu_item(window.h_window);
+        jumplist::add_tasks();
+    }
+    app.paused.set(options.is_quiet_now());
+    if !app.paused.get() && !register_hotkeys(window.h_window, &options) {
+        return;
+    }
+
+    if let Some(placement) = options.osd {
+        // the words still go out without it
+        if let Err(e) = osd::create(instance, window.h_window, placement) {
+            message_box(&e.to_string(), "Overlay Error", win32::MB_OK | win32::MB_ICONEXCLAMATION);
+        }
+    }
+
+    if let Some(trigger) = &options.raw_trigger {
+        if let Err(e) = trigger.register(window.h_window) {
+            message_box(
+                &e.to_string(),
+                "Raw Input Error",
+                win32::MB_OK | win32::MB_ICONEXCLAMATION,
+            );
+        }
+    } else if options.practice.is_some() {
+        if let Err(e) = rawinput::register_keyboards(window.h_window) {
+            message_box(
+                &e.to_string(),
+                "Raw Input Error",
+                win32::MB_OK | win32::MB_ICONEXCLAMATION,
+            );
+        }
+    }
+
+    let Some((mut list, mut local)) = open_list(&options) else {
+        return;
+    };
+    if options.compact {
+        set_window_title(
+            window.h_window,
+            &format!("{} • {}/{}", options.dispense_keys.name(), list.position, list.total),
+        );
+    }
+    paint::show_progress(window.h_window, &list);
+
+    let mut channels = open_channels(&options);
+
+    let Some(mut stats) = load_stats(&options, journaled) else {
+        return;
+    };
+
+    let mut layout_watch = check::LayoutWatch::new();
+    let mut battery_saver = power::BatterySaver::default();
+    if !options.use_clipboard && !options.paste {
+        warn_untypeable_lines(&options, layout_watch.current());
+    }
+
+    let mut session = session::Session::new();
+
+    let mut tape = replay::Tape::default();
+    if let Some(path) = &options.record {
+        match replay::Recorder::create(path) {
+            Ok(r) => tape.recorder = Some(r),
+            Err(e) => {
+                message_box(
+                    &format!("{}: {}", path, e),
+                    "File Open Error",
+                    win32::MB_OK | win32::MB_ICONEXCLAMATION,
+                );
+                return;
+            }
+        }
+    }
+    if let Some(path) = &options.replay {
+        match replay::Replay::load(path) {
+            Ok(r) => tape.replay = Some(r),
+            Err(e) => {
+                message_box(
+                    &e.to_string(),
+                    "Replay Error",
+                    win32::MB_OK | win32::MB_ICONEXCLAMATION,
+                );
+                return;
+            }
+        }
+    }
+
+    let listener = match options.serve.map(http::Listener::bind).transpose() {
+        Ok(l) => l,
+        Err(e) => {
+            message_box(
+                &format!("Unable to listen on port {}: {}", options.serve.unwrap_or(0), e),
+                "Listener Error",
+                win32::MB_OK | win32::MB_ICONEXCLAMATION,
+            );
+            None
+        }
+    };
+
+    let mut refresher = remote::Refresher::new(options.remotes.clone(), options.refresh);
+    let mut watched = std::time::Instant::now();
+    let mut list_watch = watch::ListWatch::default();
+
+    while app.running.get() {
+        poll_event(
+            window.h_window,
+            &mut list,
+            &mut channels,
+            &options,
+            &mut session,
+            &mut stats,
+            &mut tape,
+        );
+        if let Some(local) = &mut local {
+            local.keep(&mut list);
+        }
+        channels.keep();
+        if let Some(replay) = &mut tape.replay {
+            while let Some((word, mode)) = replay.due() {
+                emit(word, mode);
+            }
+        }
+        if let Some(profile) = app.pending_profile.take() {
+            let switched = switch_profile(
+                window.h_window,
+                profile,
+                &mut options,
+                &mut list,
+                &mut local,
+                &mut channels,
+            );
+            if switched && !options.use_clipboard && !options.paste {
+                warn_untypeable_lines(&options, layout_watch.current());
+            }
+        }
+        match app.pending_task.take() {
+            Some(jumplist::MODE) => toggle_mode(window.h_window, &mut options),
+            Some(jumplist::OPEN) => open_word_file(window.h_window, &list.path, &options),
+            _ => {}
+        }
+        if let Some(command) = app.pending_command.take() {
+            let (list, _) = channels.current(&mut list);
+            tray_command(window.h_window, command, list, &options, &session, &mut stats);
+        }
+        if let Some(path) = app.pending_list.take() {
+            if switch_list(path, &mut list, &mut local, &mut options) {
+                paint::show_progress(window.h_window, &list);
+                if options.compact {
+                    set_window_title(
+                        window.h_window,
+                        &format!(
+                            "{} • {}/{}",
+                            options.dispense_keys.name(),
+                            list.position,
+                            list.total
+                        ),
+                    );
+                }
+                if !options.use_clipboard && !options.paste {
+                    warn_untypeable_lines(&options, layout_watch.current());
+                }
+            }
+        }
+        let refreshed = refresher.poll();
+        let bound = channels.bound.iter().map(|b| b.list.path.as_str());
+        list_watch.watch(std::iter::once(list.path.as_str()).chain(bound));
+        let watch = list_watch.take()
+            || (!list_watch.is_complete() && watched.elapsed() >= LIST_WATCH_INTERVAL);
+        if watch || !refreshed.is_empty() {
+            watched = std::time::Instant::now();
+            // edited while running or downloaded again: reopened at the same place
+            let bound = channels.bound.iter_mut().map(|b| &mut b.list);
+            let mut reloaded = false;
+            for changed in std::iter::once(&mut list).chain(bound) {
+                let edited = watch && changed.changed_on_disk();
+                if !edited && !refreshed.contains(&changed.path) {
+                    continue;
+                }
+                if let Err(e) = changed.reload() {
+                    message_box(
+                        &format!("{}: {}", changed.path, e),
+                        "File Read Error",
+                        win32::MB_OK | win32::MB_ICONEXCLAMATION,
+                    );
+                }
+                stats.note_position(changed);
+                reloaded = true;
+            }
+            if reloaded {
+                paint::show_progress(window.h_window, &list);
+            }
+        }
+        if let Some(listener) = &listener {
+            while let Some(request) = listener.accept() {
+                serve_request(request, &list, &options, &session, app.paused.get());
+            }
+        }
+        if let Some(layout) = layout_watch.poll() {
+            // a warning nobody asked for would show up on the projector, or break the focus
+            if !options.use_clipboard && !options.paste && !options.holds_warnings() {
+                warn_untypeable_lines(&options, layout);
+            }
+        }
+        if options.auto_hide {
+            dock::auto_hide(window.h_window);
+        }
+        osd::poll(window.h_window);
+        clipboard::poll();
+        if let Some(after) = options.idle_after {
+            session.poll_idle(after);
+        }
+        let quiet = options.is_quiet_now() || snooze::is_on(window.h_window);
+        if quiet != app.paused.get() {
+            app.paused.set(quiet);
+            if quiet {
+                unregister_hotkeys(window.h_window, &options);
+            } else if !register_hotkeys(window.h_window, &options) {
+                // taken while paused; without it the app is of no use, as at startup
+                stop_running(window.h_window);
+            }
+        }
+        let busy = options.auto_hide
+            || listener.is_some()
+            || osd::is_showing(window.h_window)
+            || tape.replay.as_ref().is_some_and(|r| r.is_running());
+        let timeout = if !busy {
+            idle_timeout(&app, &refresher, &list_watch)
+        } else if options.slow_on_battery_saver && battery_saver.is_on() {
+            BATTERY_SAVER_POLL_MS
+        } else {
+            POLL_MS
+        };
+        wait_for_message(&list_watch.handles(), timeout);
+    }
+
+    unregister_hotkeys(window.h_window, &options);
+    tray::remove(window.h_window);
+    clipboard::restore_now();
+    channels.save();
+    save_progress(&options, &list, local, &session, &mut stats);
+}
+
+/// A `bind=` channel: a list with the id of its hotkey and the mode it emits in, if it has
+/// one. `local` is None in `statedir=` mode, where its shared cursor keeps the position.
+struct Binding {
+    id: i32,
+    list: list::WordList,
+    local: Option<LocalPosition>,
+    mode: Option<rules::Mode>,
+}
+
+/// The channels besides the main list, and which one the hotkeys that move through a list
+/// act on: undo, the previous word, peek, pick, go to, reset and the jumps.
+struct Channels {
+    bound: Vec<Binding>,
+    /// the `bind=` channel dispensed from last, or None for the main list
+    current: Option<usize>,
+}
+
+impl Channels {
+    /// The channel of the hotkey `id`, which becomes the current one.
+    fn select(&mut self, id: usize) -> Option<&mut Binding> {
+        let index = self.bound.iter().position(|b| b.id as usize == id)?;
+        self.current = Some(index);
+        return Some(&mut self.bound[index]);
+    }
+
+    /// The list of the current channel, `main` or a `bind=` one, with the mode of its `bind=`.
+    fn current<'a>(
+        &'a mut self,
+        main: &'a mut list::WordList,
+    ) -> (&'a mut list::WordList, Option<rules::Mode>) {
+        return match self.current.and_then(|i| self.bound.get_mut(i)) {
+            Some(binding) => (&mut binding.list, binding.mode),
+            None => (main, None),
+        };
+    }
+
+    /// The list at `path`: a `bind=` one, or else `main`.
+    fn find<'a>(&'a mut self, main: &'a mut list::WordList, path: &str) -> &'a mut list::WordList {
+        return match self.bound.iter_mut().find(|b| b.list.path == path) {
+            Some(binding) => &mut binding.list,
+            None => main,
+        };
+    }
+
+    /// Saves the positions that moved, like `LocalPosition::keep`.
+    fn keep(&mut self) {
+        for binding in &mut self.bound {
+            if let Some(local) = &mut binding.local {
+                local.keep(&mut binding.list);
+            }
+        }
+    }
+
+    fn save(&mut self) {
+        for binding in &mut self.bound {
+            if let Some(local) = &mut binding.local {
+                local.save(binding.list.position);
+            }
+        }
+    }
+}
+
+/// Opens the `bind=` lists at their saved positions, the main list being the current channel.
+/// A list that can't be opened is left out after telling the user, its hotkey then does
+/// nothing.
+fn open_channels(options: &Options) -> Channels {
+    let mut bound = Vec::new();
+    for (i, (_, path, mode)) in options.bindings.iter().enumerate() {
+        if let Some((list, local)) = open_bound_list(options, path) {
+            bound.push(Binding { id: HOTKEY_BINDINGS + i as i32, list, local, mode: *mode });
+        }
+    }
+    return Channels { bound, current: None };
+}
+
+/// Opens the `bind=` list `path` at its saved position: that of its own shared cursor in
+/// `statedir=` mode, or else its line in positions.dat.
+fn open_bound_list(
+    options: &Options,
+    path: &str,
+) -> Option<(list::WordList, Option<LocalPosition>)> {
+    let shared = options.state_dir.as_deref().map(|dir| shared::SharedCursor::for_list(dir, path));
+    let (local, lines_to_skip) = match &shared {
+        Some(shared) => match shared.with_lock(|cursor| *cursor) {
+            Ok(n) => (None, n),
+            Err(e) => {
+                shared_state_error(&e);
+                return None;
+            }
+        },
+        None => {
+            let (local, n) = LocalPosition::open(path, None, &options.state_path(positions::PATH))?;
+            (Some(local), n)
+        }
+    };
+    let mut list = match list::WordList::open(path, lines_to_skip) {
+        Ok(list) => list,
+        Err(e) => {
+            message_box(
+                &format!("{}: {}", path, e),
+                "File Open Error",
+                win32::MB_OK | win32::MB_ICONEXCLAMATION,
+            );
+            return None;
+        }
+    };
+    let position_path = match &shared {
+        Some(shared) => shared.path().display().to_string(),
+        None => skipline_path(path),
+    };
+    apply_order(&mut list, options, &position_path);
+    if let Some(shared) = shared {
+        list.share(shared);
+    }
+    return Some((list, local));
+}
+
+/// Opens the active list at its saved position, after telling the user on failure. The
+/// `LocalPosition` is `None` in `statedir=` mode, where the shared cursor keeps the position.
+fn open_list(options: &Options) -> Option<(list::WordList, Option<LocalPosition>)> {
+    let shared = options.state_dir.as_deref().map(shared::SharedCursor::new);
+    let mut local = None;
+    let lines_to_skip = match &shared {
+        Some(shared) => match shared.with_lock(|cursor| {
+            // the other instances follow to where `skip=` starts
+            *cursor = options.skip.unwrap_or(*cursor);
+            *cursor
+        }) {
+            Ok(n) => n,
+            Err(e) => {
+                shared_state_error(&e);
+                return None;
+            }
+        },
+        None => {
+            let (position, n) = LocalPosition::open(
+                options.words_path(),
+                options.skipline.as_deref(),
+                &options.state_path(positions::PATH),
+            )?;
+            local = Some(position);
+            options.skip.unwrap_or(n)
+        }
+    };
+    let mut list = match list::WordList::open(options.words_path(), lines_to_skip) {
+        Ok(l) => l,
+        Err(e) => {
+            let err = e.to_string();
+            unsafe {
+                win32::MessageBoxA(
+                    0,
+                    err.as_str().as_ptr() as _,
+                    win32::s!("File Open Error"),
+                    win32::MB_OK | win32::MB_ICONEXCLAMATION,
+                )
+            };
+            return None;
+        }
+    };
+    let position_path = match &options.state_dir {
+        Some(dir) => std::path::Path::new(dir).join("skipline.dat").display().to_string(),
+        None => options.skipline_path(),
+    };
+    apply_order(&mut list, options, &position_path);
+    if let Some(shared) = shared {
+        list.share(shared);
+    }
+    return Some((list, local));
+}
+
+/// Sets up `random`, `coverage` or `shuffle` on a list just opened, whose position is kept in
+/// `position_path`; the deck of `shuffle` goes next to it.
+fn apply_order(list: &mut list::WordList, options: &Options, position_path: &str) {
+    if options.coverage {
+        list.cover(options.no_repeat);
+    } else if options.random {
+        list.randomize(options.no_repeat);
+    } else if options.shuffle {
+        let deck_path = std::path::Path::new(position_path).with_extension("deck");
+        if let Err(e) = list.deal(deck_path.clone()) {
+            message_box(
+                &format!("{}: {}", deck_path.display(), e),
+                "Shuffle Error",
+                win32::MB_OK | win32::MB_ICONEXCLAMATION,
+            );
+        }
+    }
+}
+
+/// The stats table, with the journal attached if `journaled`, i.e. this instance holds it.
+fn load_stats(options: &Options, journaled: bool) -> Option<stats::Stats> {
+    let path = options.state_path(STATS_PATH);
+    let mut stats = match stats::Stats::load(&path) {
+        Ok(s) => s,
+        Err(e) => {
+            message_box(
+                &format!("{}: {}", path, e),
+                "File Read Error",
+                win32::MB_OK | win32::MB_ICONEXCLAMATION,
+            );
+            return None;
+        }
+    };
+    for (path, banned) in [(BANNED_PATH, true), (FAVORITES_PATH, false)] {
+        if let Err(e) = stats.sync_flags(path, banned) {
+            message_box(
+                &format!("{}: {}", path, e),
+                "File Read Error",
+                win32::MB_OK | win32::MB_ICONEXCLAMATION,
+            );
+        }
+    }
+    // without the journal the changes are still saved on a clean exit
+    if !journaled {
+        return Some(stats);
+    }
+    let path = options.state_path(JOURNAL_PATH);
+    match journal::Journal::open(&path) {
+        Ok(journal) => stats.attach(journal),
+        Err(e) => message_box(
+            &format!("{}: {}", path, e),
+            "File Open Error",
+            win32::MB_OK | win32::MB_ICONEXCLAMATION,
+        ),
+    }
+    return Some(stats);
+}
+
+/// Replays what the journal holds after a crash into the stats and position files, before
+/// they are opened. Returns false, after telling the user, if that failed; the journal is
+/// then kept for the next start.
+fn recover_journal(options: &Options) -> bool {
+    let journal_path = options.state_path(JOURNAL_PATH);
+    let stats_path = options.state_path(STATS_PATH);
+    let changes = match journal::Journal::leftover(&journal_path) {
+        Ok(c) if c.is_empty() => return true,
+        Ok(c) => c,
+        Err(e) => {
+            message_box(
+                &format!("{}: {}", journal_path, e),
+                "File Read Error",
+                win32::MB_OK | win32::MB_ICONEXCLAMATION,
+            );
+            return false;
+        }
+    };
+    let mut stats = match stats::Stats::load(&stats_path) {
+        Ok(s) => s,
+        Err(e) => {
+            message_box(
+                &format!("{}: {}", stats_path, e),
+                "File Read Error",
+                win32::MB_OK | win32::MB_ICONEXCLAMATION,
+            );
+            return false;
+        }
+    };
+    let mut positions = std::collections::HashMap::new();
+    for change in changes {
+        match change {
+            journal::Change::Record { timestamp, word } => stats.record(&word, &timestamp),
+            journal::Change::Unrecord { word } => stats.unrecord(&word),
+            journal::Change::Rate { delta, word } => {
+                stats.rate(&word, delta);
+            }
+            journal::Change::Position { lines, list } => {
+                positions.insert(list, lines);
+            }
+        }
+    }
+    if let Err(e) = stats.save() {
+        message_box(
+            &format!("{}: {}", stats_path, e),
+            "File Write Error",
+            win32::MB_OK | win32::MB_ICONEXCLAMATION,
+        );
+        return false;
+    }
+    for (list, lines) in positions {
+        let skipline = if options.state_dir.is_some() {
+            // the shared positions were written on every move
             continue;
+        } else if list == options.words_path() {
+            options.skipline.as_deref()
+        } else {
+            None
+        };
+        let Some((mut local, _)) =
+            LocalPosition::open(&list, skipline, &options.state_path(positions::PATH))
+        else {
+            return false;
+        };
+        local.save(lines);
+    }
+    let emptied = journal::Journal::open(&journal_path).and_then(|mut j| j.checkpoint());
+    if let Err(e) = emptied {
+        message_box(
+            &format!("{}: {}", journal_path, e),
+            "File Write Error",
+            win32::MB_OK | win32::MB_ICONEXCLAMATION,
+        );
+        return false;
+    }
+    return true;
+}
+
+fn save_progress(
+    options: &Options,
+    list: &list::WordList,
+    local: Option<LocalPosition>,
+    session: &session::Session,
+    stats: &mut stats::Stats,
+) {
+    let saved = stats.save();
+    if let Err(e) = &saved {
+        message_box(
+            &format!("{}: {}", options.state_path(STATS_PATH), e),
+            "File Write Error",
+            win32::MB_OK | win32::MB_ICONEXCLAMATION,
+        );
+    }
+    let list_times_path = options.state_path(LIST_TIMES_PATH);
+    if let Err(e) = stats::add_list_times(&list_times_path, session.list_times()) {
+        message_box(
+            &format!("{}: {}", list_times_path, e),
+            "File Write Error",
+            win32::MB_OK | win32::MB_ICONEXCLAMATION,
+        );
+    }
+
+    // in shared mode every move was already written to the state directory
+    if let Some(mut local) = local {
+        local.save(list.position);
+    }
+    // a table that couldn't be saved is still in the journal for the next start
+    if saved.is_ok() {
+        let _ = stats.checkpoint();
+    }
+}
+
+/// Where the position of a list is kept when not in `statedir=` mode: its line in
+/// positions.dat, or the instance's copy of it, or the `skipline=` file.
+enum LocalPosition {
+    Listed { positions: String, key: String, loaded: u64 },
+    File(SkiplineFile),
+}
+
+impl LocalPosition {
+    /// The position of `list` in the `positions` file, or kept in `skipline` if given.
+    fn open(list: &str, skipline: Option<&str>, positions: &str) -> Option<(Self, u64)> {
+        if let Some(path) = skipline {
+            let (file, lines_to_skip) = SkiplineFile::open(path.to_owned())?;
+            return Some((Self::File(file), lines_to_skip));
+        }
+        let key = positions::key(list);
+        let lines_to_skip = match positions::load(positions, &key) {
+            Ok(Some(lines)) => lines,
+            // kept in its own file before positions.dat, which is left for older versions
+            Ok(None) => std::fs::read(skipline_path(list)).map_or(0, |b| parse_lines_to_skip(&b)),
+            Err(e) => {
+                message_box(
+                    &format!("{}: {}", positions, e),
+                    "File Read Error",
+                    win32::MB_OK | win32::MB_ICONEXCLAMATION,
+                );
+                return None;
+            }
+        };
+        let positions = positions.to_owned();
+        return Some((Self::Listed { positions, key, loaded: lines_to_skip }, lines_to_skip));
+    }
+
+    /// Saves `position`, returning the position saved, further if another instance or a sync
+    /// client got further meanwhile; None if it couldn't be written.
+    fn save(&mut self, position: u64) -> Option<u64> {
+        match self {
+            Self::Listed { positions, key, loaded } => {
+                let saved = positions::save(positions, key, position, *loaded).ok()?;
+                *loaded = saved;
+                return Some(saved);
+            }
+            Self::File(file) => return file.save(position),
+        }
+    }
+
+    /// Saves the position of `list` if it moved since the last save, so a crash, power loss or
+    /// `taskkill` repeats at most the word that was being emitted. A further position kept by
+    /// the save moves the list there, or the next save would write the lower one back.
+    fn keep(&mut self, list: &mut list::WordList) {
+        let saved = match self {
+            Self::Listed { loaded, .. } => *loaded,
+            Self::File(file) => file.saved,
+        };
+        if list.position == saved {
+            return;
+        }
+        if let Some(saved) = self.save(list.position).filter(|&saved| saved > list.position) {
+            let _ = list.jump((saved - list.position) as i64);
+        }
+    }
+}
+
+/// The `skipline=` file, holding the position of the starting list only.
+struct SkiplineFile {
+    file: std::fs::File,
+    path: String,
+    /// write time after opening or the last save, to notice a sync client replacing the file
+    /// meanwhile
+    opened: Option<std::time::SystemTime>,
+    saved: u64,
+}
+
+impl SkiplineFile {
+    fn open(path: String) -> Option<(Self, u64)> {
+        let (file, lines_to_skip) = open_skipline(&path)?;
+        let opened = conflict::modified(&path);
+        return Some((Self { file, path, opened, saved: lines_to_skip }, lines_to_skip));
+    }
+
+    fn save(&mut self, mut position: u64) -> Option<u64> {
+        // a sync client brought in another machine's progress meanwhile: keep the furthest
+        if conflict::modified(&self.path) != self.opened {
+            let mut buffer = Vec::with_capacity(SKILINE_NUMBER_SIZE);
+            let _ = self.file.seek(std::io::SeekFrom::Start(0));
+            if self.file.read_to_end(&mut buffer).is_ok() {
+                position = position.max(parse_lines_to_skip(&buffer));
+            }
+        }
+        let position = merge_skipline_copies(&mut self.file, &self.path, position);
+        let skipline_array = u64_to_array::<8>(position);
+        let _ = self.file.seek(std::io::SeekFrom::Start(0));
+        // 8 bytes at the start of the file are written in one go, a crash can't leave half
+        let written = self.file.write_all(&skipline_array).and_then(|()| self.file.sync_data());
+        self.opened = conflict::modified(&self.path);
+        written.ok()?;
+        self.saved = position;
+        return Some(position);
+    }
+}
+
+/// The .txt files in `dir`, by name.
+fn lists_in(dir: &str) -> anyhow::Result<Vec<String>> {
+    let entries = std::fs::read_dir(dir).map_err(|e| anyhow::anyhow!("lists: {}: {}", dir, e))?;
+    let mut lists: Vec<String> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("txt")))
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect();
+    lists.sort();
+    return Ok(lists);
+}
+
+/// Where a list's position was kept before positions.dat; its shuffled deck still goes next
+/// to it.
+fn skipline_path(words_path: &str) -> String {
+    if words_path == WORDS_PATH {
+        return SKIPLINE_PATH.to_owned();
+    }
+    return format!("{}.skipline.dat", words_path);
+}
+
+/// The Toggle mode task: type, then clip, then paste, then type again.
+fn toggle_mode(h_window: win32::HWND, options: &mut Options) {
+    (options.use_clipboard, options.paste) = match (options.use_clipboard, options.paste) {
+        (false, false) => (true, false),
+        (true, false) => (false, true),
+        _ => (false, false),
+    };
+    let mode = match (options.use_clipboard, options.paste) {
+        (_, true) => "paste",
+        (true, _) => "clip",
+        _ => "type",
+    };
+    paint::show_status(h_window, &format!("Mode: {}", mode));
+}
+
+/// The Open word file task: the list in the editor Windows has for it. Not in kiosk mode,
+/// where it would show the words to come.
+fn open_word_file(h_window: win32::HWND, path: &str, options: &Options) {
+    if options.kiosk {
+        message_box(
+            "The word file can't be opened in kiosk mode.",
+            "Open Word File",
+            win32::MB_OK | win32::MB_ICONEXCLAMATION,
+        );
+        return;
+    }
+    let file: Vec<u16> = path.encode_utf16().chain(std::iter::once(0)).collect();
+    let result = unsafe {
+        win32::ShellExecuteW(
+            h_window,
+            win32::w!("open"),
+            file.as_ptr(),
+            std::ptr::null(),
+            std::ptr::null(),
+            win32::SW_SHOWNORMAL,
+        )
+    };
+    // anything up to 32 is an error code
+    if result <= 32 {
+        message_box(
+            &format!("Unable to open {}", path),
+            "Open Word File",
+            win32::MB_OK | win32::MB_ICONEXCLAMATION,
+        );
+    }
+}
+
+/// Makes `path` the active list after saving the position in the current one. Returns false,
+/// after telling the user, if the list was kept.
+fn switch_list(
+    path: String,
+    list: &mut list::WordList,
+    local: &mut Option<LocalPosition>,
+    options: &mut Options,
+) -> bool {
+    if options.kiosk {
+        message_box(
+            "Lists can't be switched in kiosk mode.",
+            "Switch List",
+            win32::MB_OK | win32::MB_ICONEXCLAMATION,
+        );
+        return false;
+    }
+    let Some(current) = local.as_mut() else {
+        message_box(
+            "Lists can't be switched while sharing the position through statedir=.",
+            "Switch List",
+            win32::MB_OK | win32::MB_ICONEXCLAMATION,
+        );
+        return false;
+    };
+    let Some((next, lines_to_skip)) =
+        LocalPosition::open(&path, None, &options.state_path(positions::PATH))
+    else {
+        return false;
+    };
+    let mut next_list = match list::WordList::open(&path, lines_to_skip) {
+        Ok(l) => l,
+        Err(e) => {
+            message_box(
+                &format!("{}: {}", path, e),
+                "File Open Error",
+                win32::MB_OK | win32::MB_ICONEXCLAMATION,
+            );
+            return false;
+        }
+    };
+    apply_order(&mut next_list, options, &skipline_path(&path));
+    current.save(list.position);
+    *list = next_list;
+    *local = Some(next);
+    options.list = Some(path);
+    options.skipline = None;
+    return true;
+}
+
+/// Switches to `profile` picked in the tray, or back to none: the options are read again with
+/// it on top, and the list, the `bind=` lists and the hotkeys follow, after saving the
+/// positions. Returns false, after telling the user, if everything was kept.
+fn switch_profile(
+    h_window: win32::HWND,
+    profile: Option<String>,
+    options: &mut Options,
+    list: &mut list::WordList,
+    local: &mut Option<LocalPosition>,
+    channels: &mut Channels,
+) -> bool {
+    let Some(app) = app::get(h_window) else {
+        return false;
+    };
+    if options.kiosk {
+        message_box(
+            "Profiles can't be switched in kiosk mode.",
+            "Switch Profile",
+            win32::MB_OK | win32::MB_ICONEXCLAMATION,
+        );
+        return false;
+    }
+    let current = app.profile.borrow().clone();
+    // reading the options sets the typing ones already; on failure they are read again
+    let mut next = match Options::from_args(profile.as_deref()) {
+        Ok(next) => next,
+        Err(e) => {
+            let _ = Options::from_args(current.as_deref());
+            message_box(&e.to_string(), "Profile Error", win32::MB_OK | win32::MB_ICONEXCLAMATION);
+            return false;
+        }
+    };
+    let Some((next_list, next_local)) = open_list(&next) else {
+        let _ = Options::from_args(current.as_deref());
+        return false;
+    };
+    next.instance = options.instance;
+    if next.instance > 0 && !next.hotkey_given {
+        next.dispense_keys = options.dispense_keys;
+    }
+
+    unregister_hotkeys(h_window, options);
+    if let Some(local) = local {
+        local.save(list.position);
+    }
+    channels.save();
+    *options = next;
+    *list = next_list;
+    *local = next_local;
+    *channels = open_channels(options);
+    share_options(options);
+    if !app.paused.get() {
+        // without the dispense hotkey the tray can still switch back
+        register_hotkeys(h_window, options);
+    }
+    show_keys(h_window, options);
+    if options.compact {
+        set_window_title(
+            h_window,
+            &format!("{} • {}/{}", options.dispense_keys.name(), list.position, list.total),
+        );
+    }
+    paint::show_progress(h_window, list);
+    let status = match &profile {
+        Some(name) => format!("Profile {}", name),
+        None => "Default profile".to_owned(),
+    };
+    paint::show_status(h_window, &status);
+    *app.profile.borrow_mut() = profile;
+    return true;
+}
+
+/// Hands the options that the typing and reporting code reads without `Options` over to it.
+fn share_options(options: &Options) {
+    CHAR_DELAY_MICROS
+        .store(options.char_delay.as_micros() as u64, std::sync::atomic::Ordering::Relaxed);
+    webhook::configure(options.webhook.as_deref());
+    output::configure(&options.outputs);
+}
+
+/// Puts the dispense hotkey, and the instance name if given, in the window title and the tip
+/// of the tray icon.
+fn show_keys(h_window: win32::HWND, options: &Options) {
+    let keys = options.dispense_keys.name();
+    let (title, tip) = match &options.instance_name {
+        Some(name) => {
+            (format!("{}: {}. Close When Done", name, keys), format!("randword {}: {}", name, keys))
+        }
+        None => (format!("{}. Close When Done", keys), format!("randword: {}", keys)),
+    };
+    set_window_title(h_window, &title);
+    tray::show(h_window, &tip);
+}
+
+/// Answers one request of the `serve=` listener.
+fn serve_request(
+    request: http::Request,
+    list: &list::WordList,
+    options: &Options,
+    session: &session::Session,
+    quiet: bool,
+) {
+    if request.method != "GET" {
+        request.respond("405 Method Not Allowed", "text/plain", "only GET is supported\n");
+        return;
+    }
+    match request.path.as_str() {
+        "/status" => {
+            let mode = if options.use_clipboard {
+                "clipboard"
+            } else if options.paste {
+                "paste"
+            } else if options.auto_clip {
+                "autoclip"
+            } else {
+                "type"
+            };
+            let body = format!(
+                "{{\"index\":{},\"total\":{},\"mode\":{},\"list\":{},\"paused\":{}}}\n",
+                list.position,
+                list.total,
+                http::json_string(mode),
+                http::json_string(options.words_path()),
+                quiet
+            );
+            request.respond("200 OK", "application/json", &body);
+        }
+        "/metrics" => {
+            let body = format!(
+                "# HELP randword_dispensed_total Words dispensed since start.\n\
+                 # TYPE randword_dispensed_total counter\n\
+                 randword_dispensed_total {}\n\
+                 # HELP randword_dispense_failures_total Dispense hotkey presses that produced no word.\n\
+                 # TYPE randword_dispense_failures_total counter\n\
+                 randword_dispense_failures_total {}\n\
+                 # HELP randword_queue_depth Lines left before the list starts over.\n\
+                 # TYPE randword_queue_depth gauge\n\
+                 randword_queue_depth {}\n\
+                 # HELP randword_uptime_seconds Seconds since start.\n\
+                 # TYPE randword_uptime_seconds gauge\n\
+                 randword_uptime_seconds {}\n",
+                session.len(),
+                session.failures(),
+                list.total.saturating_sub(list.position),
+                session.uptime().as_secs()
+            );
+            request.respond("200 OK", "text/plain; version=0.0.4", &body);
+        }
+        _ => request.not_found(),
+    }
+}
+
+/// Opens a skipline.dat and reads the saved position from it. Only the one of ./words.txt
+/// has to exist already.
+fn open_skipline(path: &str) -> Option<(std::fs::File, u64)> {
+    let create = path != SKIPLINE_PATH;
+    let mut file_line =
+        match std::fs::OpenOptions::new().read(true).write(true).create(create).open(path) {
+            Ok(f) => f,
+            Err(e) => {
+                let err = e.to_string();
+                unsafe {
+                    win32::MessageBoxA(
+                        0,
+                        err.as_str().as_ptr() as _,
+                        win32::s!("File Open Error"),
+                        win32::MB_OK | win32::MB_ICONEXCLAMATION,
+                    )
+                };
+                return None;
+            }
+        };
+
+    let lines_to_skip = {
+        let mut file_line_buff = [b'0'; SKILINE_NUMBER_SIZE];
+        // a list's position file that was just created is still empty
+        let created = file_line.metadata().is_ok_and(|m| m.len() == 0);
+        if let (false, Err(e)) = (created, file_line.read_exact(&mut file_line_buff)) {
+            let err = e.to_string();
+            unsafe {
+                win32::MessageBoxA(
+                    0,
+                    err.as_str().as_ptr() as _,
+                    win32::s!("File Read Error"),
+                    win32::MB_OK | win32::MB_ICONEXCLAMATION,
+                )
+            };
+        }
+        // eprintln!("{:?}", std::str::from_utf8(&file_line_buff));
+        parse_lines_to_skip(&file_line_buff)
+    };
+    // dbg!(&lines_to_skip);
+    let lines_to_skip = merge_skipline_copies(&mut file_line, path, lines_to_skip);
+    return Some((file_line, lines_to_skip));
+}
+
+/// Continues from the furthest of a skipline.dat and the conflicted copies a sync client made
+/// of it, writes that back and deletes the copies.
+fn merge_skipline_copies(file_line: &mut std::fs::File, path: &str, lines_to_skip: u64) -> u64 {
+    let copies = conflict::conflicted_copies(path);
+    if copies.is_empty() {
+        return lines_to_skip;
+    }
+    let mut furthest = lines_to_skip;
+    for copy in &copies {
+        if let Ok(buffer) = std::fs::read(copy) {
+            furthest = furthest.max(parse_lines_to_skip(&buffer));
+        }
+    }
+    let _ = file_line.seek(std::io::SeekFrom::Start(0));
+    if file_line.write_all(&u64_to_array::<SKILINE_NUMBER_SIZE>(furthest)).is_ok() {
+        conflict::remove(&copies);
+    }
+    return furthest;
+}
+
+fn u64_to_array<const N: usize>(mut num: u64) -> [u8; N] {
+    debug_assert!(N <= 8);
+    let mut buf = [0_u8; N];
+
+    for ele in buf.iter_mut().rev() {
+        *ele = b'0' + (num % 10) as u8;
+        num /= 10;
+
+        if num == 0 {
+            break;
+        }
+    }
+
+    let mut res = [0; N];
+    let mut i = 0;
+    for ele in buf {
+        if ele != 0 {
+            res[i] = ele;
+            i += 1;
+        }
+    }
+
+    return res;
+}
+
+fn poll_event(
+    h_window: isize,
+    list: &mut list::WordList,
+    channels: &mut Channels,
+    options: &Options,
+    session: &mut session::Session,
+    stats: &mut stats::Stats,
+    tape: &mut replay::Tape,
+) {
+    let mut msg = unsafe { std::mem::zeroed() };
+    while unsafe { win32::PeekMessageW(&mut msg, h_window, 0, 0, win32::PM_REMOVE) != 0 } {
+        if msg.message == win32::WM_QUIT {
+            stop_running(h_window);
+            return;
+        }
+
+        if msg.message == win32::WM_HOTKEY {
+            if msg.wParam == HOTKEY_EXPORT_SESSION as usize {
+                export_session(session);
+                break;
+            }
+            if msg.wParam == HOTKEY_EXPORT_BUNDLE as usize && !options.kiosk {
+                export_bundle(list, options);
+                break;
+            }
+            if msg.wParam == HOTKEY_DISPENSE as usize {
+                if let Some(replay) = &mut tape.replay {
+                    replay.toggle();
+                    break;
+                }
+                channels.current = None;
+                if wait_to_emit(h_window, options) {
+                    dispense(h_window, list, None, options, session, stats, tape);
+                }
+                break;
+            }
+            if let Some(binding) = channels.select(msg.wParam) {
+                if wait_to_emit(h_window, options) {
+                    let list = &mut binding.list;
+                    dispense(h_window, list, binding.mode, options, session, stats, tape);
+                }
+                break;
+            }
+            // taking a word back is a step back in the list it came from
+            if msg.wParam == HOTKEY_UNDO as usize && !options.kiosk {
+                if wait_to_emit(h_window, options) {
+                    undo_last(h_window, list, channels, options, session, stats);
+                }
+                break;
+            }
+            if msg.wParam == HOTKEY_NEXT_LIST as usize {
+                // switched in the main loop, like a list opened from the Explorer menu
+                let current = options.lists.iter().position(|l| *l == list.path);
+                let next = current.map_or(0, |i| (i + 1) % options.lists.len());
+                shell::queue_list(h_window, options.lists[next].clone());
+                break;
+            }
+            // the rest move through the list of the channel dispensed from last
+            let (list, mode) = channels.current(list);
+            // a step back in the list; random picks have no order to step back in
+            if msg.wParam == HOTKEY_PREVIOUS as usize && !options.kiosk && !list.is_random() {
+                if list.position < 2 || !wait_to_emit(h_window, options) {
+                    break;
+                }
+                if let Err(e) = list.jump(-2) {
+                    shared_state_error(&e);
+                    break;
+                }
+                dispense(h_window, list, mode, options, session, stats, tape);
+                break;
+            }
+            // only shown, nothing typed and the position stays; an exam gives nothing away
+            if msg.wParam == HOTKEY_PEEK as usize && !options.kiosk {
+                let next = match list.peek() {
+                    Ok(Some(line)) => line,
+                    Ok(None) if list.is_random() => "(random)".to_owned(),
+                    Ok(None) => "(end of the list)".to_owned(),
+                    Err(e) => {
+                        shared_state_error(&e);
+                        break;
+                    }
+                };
+                paint::show_status(h_window, &format!("Next: {}", next));
+                if options.compact {
+                    set_window_title(h_window, &format!("next: {}", next));
+                }
+                break;
+            }
+            // the lines before the picked one are skipped, as with a jump
+            if msg.wParam == HOTKEY_PICK as usize && !options.kiosk && !list.is_random() {
+                let upcoming = match list.upcoming(picker::WORDS) {
+                    Ok(lines) => lines,
+                    Err(e) => {
+                        shared_state_error(&e);
+                        break;
+                    }
+                };
+                // banned lines would be passed over anyway
+                let offered: Vec<(usize, &str)> = upcoming
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, line)| !stats.is_banned(line))
+                    .map(|(i, line)| (i, line.as_str()))
+                    .collect();
+                let words: Vec<&str> = offered.iter().map(|&(_, line)| line).collect();
+                let Some(choice) = picker::pick(h_window, &words) else {
+                    break;
+                };
+                if !wait_to_emit(h_window, options) {
+                    break;
+                }
+                if let Err(e) = list.jump(offered[choice].0 as i64) {
+                    shared_state_error(&e);
+                    break;
+                }
+                dispense(h_window, list, mode, options, session, stats, tape);
+                break;
+            }
+            // random picks leave the position alone, there is none to reset or go to
+            if msg.wParam == HOTKEY_RESET as usize && !options.kiosk && !list.is_random() {
+                reset_list(h_window, list, options, stats);
+                break;
+            }
+            if msg.wParam == HOTKEY_GOTO as usize && !options.kiosk && !list.is_random() {
+                let Some(line) = goto::ask(h_window, list.position + 1, list.total) else {
+                    break;
+                };
+                // the line asked for is the next one dispensed
+                if let Err(e) = list.jump(line as i64 - 1 - list.position as i64) {
+                    shared_state_error(&e);
+                }
+                show_move(h_window, list, options, stats);
+                break;
+            }
+            // each later copy of a repeated line, to go there and remove it or keep it
+            if msg.wParam == HOTKEY_DUPLICATES as usize && !options.kiosk && !list.is_random() {
+                let lines = list.lines();
+                let numbered = lines.iter().enumerate().map(|(i, line)| (i + 1, line.as_str()));
+                let repeats: Vec<(usize, String)> = check::duplicates(numbered)
+                    .into_iter()
+                    .flat_map(|(line, numbers)| {
+                        let first = numbers[0];
+                        numbers
+                            .into_iter()
+                            .skip(1)
+                            .map(move |n| (n, format!("{}: \"{}\" (line {})", n, line, first)))
+                    })
+                    .collect();
+                if repeats.is_empty() {
+                    paint::show_status(h_window, "No repeated lines");
+                    break;
+                }
+                let items: Vec<&str> = repeats.iter().map(|(_, item)| item.as_str()).collect();
+                let Some(choice) = picker::pick(h_window, &items) else {
+                    break;
+                };
+                if let Err(e) = list.go_to_line(repeats[choice].0 as u64 - 1) {
+                    shared_state_error(&e);
+                }
+                show_move(h_window, list, options, stats);
+                break;
+            }
+            let rate =
+                msg.wParam == HOTKEY_RATE_UP as usize || msg.wParam == HOTKEY_RATE_DOWN as usize;
+            if rate && !options.kiosk {
+                let delta = if msg.wParam == HOTKEY_RATE_UP as usize { 1 } else { -1 };
+                rate_last_word(h_window, session, stats, delta);
+                break;
+            }
+            let forward = msg.wParam as i32 - HOTKEY_JUMP_FORWARD;
+            let back = msg.wParam as i32 - HOTKEY_JUMP_BACK;
+            let jump = if (1..=JUMP_DIGITS).contains(&forward) {
+                Some(10_i64.pow(forward as u32))
+            } else if (1..=JUMP_DIGITS).contains(&back) {
+                Some(-10_i64.pow(back as u32))
+            } else {
+                None
+            };
+            if let Some(delta) = jump {
+                if let Err(e) = list.jump(delta) {
+                    shared_state_error(&e);
+                }
+                show_move(h_window, list, options, stats);
+                break;
+            }
         }
 
-        let wvk = lobyte(vkey as u64) as u16;
-        let mut keyboard_input = win32::KEYBDINPUT {
-            wVk: wvk,
-            wScan: unsafe { win32::MapVirtualKeyW(wvk as u32, win32::MAPVK_VK_TO_VSC) as u16 },
-            dwFlags: 0,
-            time: 0,
-            dwExtraInfo: 0,
+        if msg.message == win32::WM_INPUT {
+            if let Some(practice) = &options.practice {
+                if let Err(e) = practice.handle(msg.lParam) {
+                    practice_error(practice, &e);
+                }
+            }
+            if let Some(trigger) = &options.raw_trigger {
+                // the trigger has no hotkey to unregister, so quiet hours and snooze hold it here
+                let paused = app::get(h_window).is_some_and(|app| app.paused.get());
+                if trigger.handle(msg.lParam) && !paused {
+                    // the same way as the dispense hotkey, with the delay and Esc before the word
+                    match &mut tape.replay {
+                        Some(replay) => replay.toggle(),
+                        None if wait_to_emit(h_window, options) => {
+                            dispense(h_window, list, None, options, session, stats, tape);
+                        }
+                        None => {}
+                    }
+                }
+            }
+        }
+        unsafe { win32::DispatchMessageW(&msg) };
+    }
+}
+
+/// Waits out `delay=` before a word goes out, counting down in the overlay if there is one
+/// and in the window title with `countdown`.
+/// Returns false if Esc was pressed meanwhile, e.g. with the wrong window in front; nothing
+/// is emitted then.
+fn wait_to_emit(h_window: win32::HWND, options: &Options) -> bool {
+    let delay = std::time::Duration::from_millis(options.delay as u64);
+    let started = std::time::Instant::now();
+    let title = options.countdown.then(|| window_title(h_window));
+    loop {
+        let left = delay.saturating_sub(started.elapsed());
+        let cancelled = esc_is_down();
+        if cancelled || left.is_zero() {
+            osd::hide(h_window);
+            if let Some(title) = &title {
+                set_window_title(h_window, title);
+            }
+            return !cancelled;
+        }
+        let countdown = format!("{:.1} s, Esc cancels", left.as_secs_f32());
+        osd::hold(h_window, &countdown);
+        if title.is_some() {
+            set_window_title(h_window, &format!("Typing in {}", countdown));
+        }
+        osd::poll(h_window);
+        unsafe { win32::Sleep(COUNTDOWN_TICK_MS.min(left.as_millis() as u32)) };
+    }
+}
+
+fn esc_is_down() -> bool {
+    return unsafe { win32::GetAsyncKeyState(win32::VK_ESCAPE as i32) } < 0;
+}
+
+/// Sleeps until a message comes, one of `handles` is signaled or `timeout_ms` is up. A message
+/// already in the queue but looked at before, which `MsgWaitForMultipleObjects` alone would
+/// sleep over, wakes it right away.
+fn wait_for_message(handles: &[win32::HANDLE], timeout_ms: u32) {
+    unsafe {
+        win32::MsgWaitForMultipleObjectsEx(
+            handles.len() as u32,
+            handles.as_ptr(),
+            timeout_ms,
+            win32::QS_ALLINPUT,
+            win32::MWMO_INPUTAVAILABLE,
+        )
+    };
+}
+
+/// How long the main loop can sleep with nothing on the screen moving: until the next minute,
+/// when quiet hours start or end and idleness and the keyboard layout are looked at, or sooner
+/// when the saved clipboard text, the end of a snooze or a download of the remote lists is due.
+fn idle_timeout(
+    app: &app::App,
+    refresher: &remote::Refresher,
+    list_watch: &watch::ListWatch,
+) -> u32 {
+    let now = std::time::Instant::now();
+    let mut wait = schedule::until_next_minute();
+    for due in [clipboard::due(), app.snoozed_until.get(), refresher.due()].into_iter().flatten() {
+        wait = wait.min(due.saturating_duration_since(now));
+    }
+    if refresher.is_fetching() || !list_watch.is_complete() {
+        wait = wait.min(LIST_WATCH_INTERVAL);
+    }
+    // a wake a little early would only find nothing due yet
+    return wait.as_millis() as u32 + 1;
+}
+
+fn window_title(h_window: win32::HWND) -> String {
+    let mut title = [0u16; 256];
+    let len = unsafe { win32::GetWindowTextW(h_window, title.as_mut_ptr(), title.len() as i32) };
+    return String::from_utf16_lossy(&title[..len.max(0) as usize]);
+}
+
+fn set_window_title(h_window: win32::HWND, title: &str) {
+    let title: Vec<u16> = title.encode_utf16().chain(std::iter::once(0)).collect();
+    unsafe { win32::SetWindowTextW(h_window, title.as_ptr()) };
+}
+
+fn warn_untypeable_lines(options: &Options, layout: win32::HKL) {
+    let Ok(data) = std::fs::read(options.words_path()) else {
+        return;
+    };
+    let lines = check::untypeable_lines(&data, layout);
+    if lines.is_empty() {
+        return;
+    }
+
+    let fallback = if options.auto_clip {
+        "They will be put on the clipboard instead of typed."
+    } else {
+        "The missing characters will be sent as Unicode input, which some apps ignore. Start with `autoclip` to send them via the clipboard instead."
+    };
+    message_box(
+        &format!(
+            "{} lines contain characters the current keyboard layout can't type (first: line {}).\n{}\n\nRun `randword check` for details.",
+            lines.len(),
+            lines[0],
+            fallback
+        ),
+        "Untypeable Characters",
+        win32::MB_OK | win32::MB_ICONEXCLAMATION,
+    );
+}
+
+/// `mode` is that of the `bind=` the list belongs to, if it set one.
+fn dispense(
+    h_window: isize,
+    list: &mut list::WordList,
+    mode: Option<rules::Mode>,
+    options: &Options,
+    session: &mut session::Session,
+    stats: &mut stats::Stats,
+    tape: &mut replay::Tape,
+) {
+    match next_line(list, session, stats) {
+        Ok(true) => {}
+        Ok(false) => {
+            let text = format!("Finished {}", list.path);
+            output::log_event("list_completed", &text);
+            webhook::notify("list_completed", &text);
+            return;
+        }
+        Err(e) => {
+            shared_state_error(&e);
+            return;
+        }
+    }
+    let line_slice = list.line();
+    // dbg!(line_slice);
+    let use_clipboard = options.use_clipboard
+        || (options.auto_clip
+            && check::first_untypeable(line_slice, check::active_layout()).is_some());
+    let target = session::foreground_window_title();
+    let rule = rules::matching(&options.rules, &target);
+    // a directive in the line is the most specific, then the bind=, the rules and the options
+    let mode = match list.directive().or(mode).or(rule.and_then(|r| r.mode)) {
+        Some(mode) => mode,
+        None if options.paste => rules::Mode::Paste,
+        None if use_clipboard => rules::Mode::Clip,
+        None => rules::Mode::Type,
+    };
+    let ime_open = mode == rules::Mode::Type && options.paste_into_ime && check::ime_is_open();
+    let mode = if ime_open { rules::Mode::Paste } else { mode };
+    let text = rule.map_or_else(|| line_slice.to_owned(), |r| r.apply(line_slice));
+    // only typing presses the keys; the other modes leave the tokens out. A list from the web
+    // is asked about always, whoever can edit it shouldn't press keys here unasked
+    let remote = options.remotes.iter().any(|r| r.copy == list.path);
+    if (options.confirm_keys || remote) && mode == rules::Mode::Type && !confirm_key_tokens(&text) {
+        return;
+    }
+    session.track(&list.path);
+    let entry = session.record(line_slice, &list.path, options.outputs.mode_name(mode), target);
+    stats.record(&entry.word, &entry.timestamp);
+    stats.note_position(list);
+    paint::add_to_history(h_window, list, &entry.word);
+    if ime_open {
+        paint::show_status(h_window, "Pasted, the IME is on");
+    }
+    // has to land ahead of the word, so it runs even for a line Esc stops
+    if let Some(hook) = &options.before_hook {
+        if let Err(e) = hook.run(line_slice, list.position, entry.mode) {
+            hook_error(&e);
+        }
+    }
+    match options.outputs.run(&text, mode, entry, list) {
+        Ok(true) => {
+            if let Some(vkey) = options.then_key {
+                if matches!(mode, rules::Mode::Type | rules::Mode::Paste) {
+                    press_key(vkey);
+                }
+            }
+            // a line Esc stopped is put back, neither replayed nor counted for the goal
+            if let Some(recorder) = &mut tape.recorder {
+                if let Err(e) = recorder.append(entry) {
+                    let flags = win32::MB_OK | win32::MB_ICONEXCLAMATION;
+                    message_box(&e.to_string(), "Record Error", flags);
+                    tape.recorder = None;
+                }
+            }
+            // `YYYY-MM-DD` of the timestamp
+            check_goal(options, stats, &entry.timestamp[..10]);
+        }
+        Ok(false) => {
+            // what was typed stays, but the line counts as not dispensed
+            put_back(h_window, list, session, stats);
+            paint::show_status(h_window, "Stopped, the line comes again next");
+            return;
+        }
+        Err(e) => {
+            let text = format!("Output error: {}", e);
+            output::log_event("error", &text);
+            webhook::notify("error", &text);
+            message_box(&e.to_string(), "Output Error", win32::MB_OK | win32::MB_ICONEXCLAMATION);
+        }
+    }
+    if let Some(hook) = &options.after_hook {
+        if let Err(e) = hook.start(line_slice, list.position, entry.mode) {
+            hook_error(&e);
+        }
+    }
+    osd::flash(h_window, &tokens::plain(&text));
+    if let Some(practice) = &options.practice {
+        if let Err(e) = practice.start(line_slice, &entry.timestamp) {
+            practice_error(practice, &e);
+        }
+    }
+    if options.compact {
+        let title = format!("{} • {}/{}", bidi::isolate(line_slice), list.position, list.total);
+        set_window_title(h_window, &title);
+    }
+}
+
+/// Moves `list` to the next line that isn't banned. Returns false at the end of the list, or
+/// when every line is banned.
+fn next_line(
+    list: &mut list::WordList,
+    session: &mut session::Session,
+    stats: &stats::Stats,
+) -> std::io::Result<bool> {
+    // banned words are passed over, but at most one full round so an all-banned list ends
+    let mut tries = list.total + 1;
+    if let Err(e) = list.weigh(|word| stats.times(word)) {
+        session.record_failure();
+        return Err(e);
+    }
+    loop {
+        match list.advance() {
+            Ok(true) => {}
+            Ok(false) => return Ok(false),
+            Err(e) => {
+                session.record_failure();
+                return Err(e);
+            }
+        }
+        if !stats.is_banned(list.line()) {
+            return Ok(true);
+        }
+        tries -= 1;
+        if tries == 0 {
+            session.record_failure();
+            return Ok(false);
+        }
+    }
+}
+
+/// Asks before a line with key tokens is typed, since a malformed list could otherwise
+/// submit a form or close a document. Returns true to go ahead.
+fn confirm_key_tokens(text: &str) -> bool {
+    let keys = tokens::keys(text);
+    if keys.is_empty() {
+        return true;
+    }
+    let target = unsafe { win32::GetForegroundWindow() };
+    let go_ahead = confirm(
+        &format!("This line presses {}:\n\n{}\n\nType it?", keys.join(", "), text),
+        "Confirm Keys",
+    );
+    // the dialog took the focus; hand it back to the window the line is meant for
+    unsafe { win32::SetForegroundWindow(target) };
+    return go_ahead;
+}
+
+/// Tells the webhook the first time the count of `today` reaches `goal=`. The day is kept in
+/// goal.dat, so an undo taking the count under the goal and the next word reaching it again,
+/// or a restart, don't tell it again.
+fn check_goal(options: &Options, stats: &stats::Stats, today: &str) {
+    let Some(goal) = options.goal else {
+        return;
+    };
+    if stats.drilled_on(today) < goal {
+        return;
+    }
+    let path = options.state_path(GOAL_PATH);
+    if std::fs::read_to_string(&path).is_ok_and(|met| met.trim() == today) {
+        return;
+    }
+    // a day that can't be kept would be told with every word after
+    if std::fs::write(&path, today).is_ok() {
+        let text = format!("Daily goal of {} words met on {}", goal, today);
+        output::log_event("goal_met", &text);
+        webhook::notify("goal_met", &text);
+    }
+}
+
+/// Asks, then moves `list` back to its first line, as if skipline.dat held 0.
+fn reset_list(
+    h_window: win32::HWND,
+    list: &mut list::WordList,
+    options: &Options,
+    stats: &mut stats::Stats,
+) {
+    if list.position == 0 {
+        return;
+    }
+    let target = unsafe { win32::GetForegroundWindow() };
+    let go_ahead = confirm(
+        &format!(
+            "Start {} over from the first line?\n\nIt is at line {} of {}.",
+            list.path, list.position, list.total
+        ),
+        "Reset List",
+    );
+    unsafe { win32::SetForegroundWindow(target) };
+    if !go_ahead {
+        return;
+    }
+    if let Err(e) = list.jump(-(list.position as i64)) {
+        shared_state_error(&e);
+        return;
+    }
+    show_move(h_window, list, options, stats);
+}
+
+/// After the list moved without dispensing: keeps the new position and shows it.
+fn show_move(
+    h_window: win32::HWND,
+    list: &list::WordList,
+    options: &Options,
+    stats: &mut stats::Stats,
+) {
+    stats.note_position(list);
+    paint::show_progress(h_window, list);
+    if options.compact {
+        set_window_title(h_window, &format!("→ {}/{}", list.position, list.total));
+    }
+}
+
+/// Copy again, Skip or Star picked in the tray menu, for quick corrections without the main
+/// window. Skip passes over the next line of the list dispensed from last; `kiosk` leaves
+/// only Copy again, like the hotkeys for moving around and rating.
+fn tray_command(
+    h_window: win32::HWND,
+    command: usize,
+    list: &mut list::WordList,
+    options: &Options,
+    session: &session::Session,
+    stats: &mut stats::Stats,
+) {
+    if options.kiosk && command != tray::COPY_AGAIN {
+        return;
+    }
+    match command {
+        tray::COPY_AGAIN => {
+            let Some(entry) = session.last() else {
+                return;
+            };
+            clipboard::put(&tokens::plain(&entry.word));
+            paint::show_status(h_window, "Copied the last word again");
+        }
+        // random picks have no next line to pass over
+        tray::SKIP if !list.is_random() => {
+            if let Err(e) = list.jump(1) {
+                shared_state_error(&e);
+            }
+            show_move(h_window, list, options, stats);
+        }
+        tray::STAR => star_last_word(h_window, session, stats),
+        _ => {}
+    }
+}
+
+/// Adds the last word to favorites.txt, which the stats take the favorite flags from.
+fn star_last_word(h_window: win32::HWND, session: &session::Session, stats: &mut stats::Stats) {
+    let Some(entry) = session.last() else {
+        return;
+    };
+    let mut text = match std::fs::read_to_string(FAVORITES_PATH) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => {
+            message_box(
+                &format!("{}: {}", FAVORITES_PATH, e),
+                "File Read Error",
+                win32::MB_OK | win32::MB_ICONEXCLAMATION,
+            );
+            return;
+        }
+    };
+    if !text.lines().any(|line| line.trim_end() == entry.word) {
+        if !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+        text.push_str(&entry.word);
+        text.push('\n');
+        if let Err(e) = std::fs::write(FAVORITES_PATH, text) {
+            message_box(
+                &format!("{}: {}", FAVORITES_PATH, e),
+                "File Write Error",
+                win32::MB_OK | win32::MB_ICONEXCLAMATION,
+            );
+            return;
+        }
+    }
+    if let Err(e) = stats.sync_flags(FAVORITES_PATH, false) {
+        message_box(
+            &format!("{}: {}", FAVORITES_PATH, e),
+            "File Read Error",
+            win32::MB_OK | win32::MB_ICONEXCLAMATION,
+        );
+        return;
+    }
+    paint::show_status(h_window, &format!("★ {}", entry.word));
+}
+
+fn practice_error(practice: &cadence::Practice, e: &std::io::Error) {
+    message_box(
+        &format!("{}: {}", practice.path, e),
+        "Practice Error",
+        win32::MB_OK | win32::MB_ICONEXCLAMATION,
+    );
+}
+
+fn hook_error(e: &std::io::Error) {
+    message_box(&e.to_string(), "Hook Error", win32::MB_OK | win32::MB_ICONEXCLAMATION);
+}
+
+/// Only `statedir=` mode does I/O besides the words file when moving through the list.
+fn shared_state_error(e: &std::io::Error) {
+    let text = format!("Shared state error: {}", e);
+    output::log_event("error", &text);
+    webhook::notify("error", &text);
+    message_box(&e.to_string(), "Shared State Error", win32::MB_OK | win32::MB_ICONEXCLAMATION);
+}
+
+fn rate_last_word(
+    h_window: isize,
+    session: &session::Session,
+    stats: &mut stats::Stats,
+    delta: i32,
+) {
+    let Some(entry) = session.last() else {
+        return;
+    };
+    let rating = stats.rate(&entry.word, delta);
+    let text = format!("{} rated {:+}", entry.word, rating);
+    output::log_event("rate", &text);
+    set_window_title(h_window, &text);
+}
+
+/// Takes the last word out of the session, the stats and the history, and makes it the next
+/// one again.
+fn put_back(
+    h_window: isize,
+    list: &mut list::WordList,
+    session: &mut session::Session,
+    stats: &mut stats::Stats,
+) -> Option<session::Entry> {
+    let entry = session.undo()?;
+    stats.unrecord(&entry.word);
+    // a random pick didn't move the position
+    if !list.is_random() {
+        if let Err(e) = list.jump(-1) {
+            shared_state_error(&e);
+        }
+        stats.note_position(list);
+    }
+    paint::remove_last_from_history(h_window, list);
+    return Some(entry);
+}
+
+/// Puts the last word back as the next one of the list it came from, `list` or a `bind=` one,
+/// and, if it was typed, erases it from the focused window with backspaces. Lines with key
+/// tokens are not erased, their effect can't be undone.
+fn undo_last(
+    h_window: isize,
+    list: &mut list::WordList,
+    channels: &mut Channels,
+    options: &Options,
+    session: &mut session::Session,
+    stats: &mut stats::Stats,
+) {
+    let Some(path) = session.last().map(|entry| entry.list.clone()) else {
+        return;
+    };
+    let list = channels.find(list, &path);
+    let Some(entry) = put_back(h_window, list, session, stats) else {
+        return;
+    };
+    output::log_event("undo", &entry.word);
+    if entry.mode == rules::Mode::Type.name() {
+        let rule = rules::matching(&options.rules, &entry.target);
+        let text = rule.map_or_else(|| entry.word.clone(), |r| r.apply(&entry.word));
+        // the key of `then=` went to the app after the word, which took it elsewhere
+        if tokens::keys(&text).is_empty() && options.then_key.is_none() {
+            erase(tokens::plain(&text).chars().count());
+        }
+    }
+    if options.compact {
+        let title = format!("↶ {} • {}/{}", bidi::isolate(&entry.word), list.position, list.total);
+        set_window_title(h_window, &title);
+    }
+}
+
+fn export_bundle(list: &list::WordList, options: &Options) {
+    match bundle::export(BUNDLE_EXPORT_PATH, options.words_path(), &options.args, list.position) {
+        Ok(()) => message_box(
+            &format!("Exported the list at line {} to {}", list.position, BUNDLE_EXPORT_PATH),
+            "Bundle Export",
+            win32::MB_OK | win32::MB_ICONINFORMATION,
+        ),
+        Err(e) => message_box(
+            &e.to_string(),
+            "Bundle Export Error",
+            win32::MB_OK | win32::MB_ICONEXCLAMATION,
+        ),
+    }
+}
+
+fn export_session(session: &session::Session) {
+    match session.export_csv(SESSION_CSV_PATH) {
+        Ok(()) => message_box(
+            &format!("Exported {} words to {}", session.len(), SESSION_CSV_PATH),
+            "Session Export",
+            win32::MB_OK | win32::MB_ICONINFORMATION,
+        ),
+        Err(e) => message_box(
+            &e.to_string(),
+            "Session Export Error",
+            win32::MB_OK | win32::MB_ICONEXCLAMATION,
+        ),
+    }
+}
+
+fn lobyte(w: u64) -> u8 {
+    (w & 0xff) as u8
+}
+
+/// Tokens like `{pause:500}` or `{TAB}` only take effect when typing; the other modes leave
+/// them out. Returns false if Esc stopped the typing partway.
+fn emit(line_slice: &str, mode: rules::Mode) -> bool {
+    match mode {
+        rules::Mode::Type => {
+            let timer = paces_chars().then(timing::Timer::new);
+            for (i, token) in tokens::tokenize(line_slice).into_iter().enumerate() {
+                // `chardelay=` between the text and the keys around it too
+                if let Some(timer) = timer.as_ref().filter(|_| i > 0) {
+                    timer.wait(char_delay(""));
+                }
+                if i > 0 && esc_is_down() {
+                    return false;
+                }
+                match token {
+                    tokens::Token::Text(text) => {
+                        if !type_out_characters(text) {
+                            return false;
+                        }
+                    }
+                    tokens::Token::Pause(millis) => unsafe { win32::Sleep(millis) },
+                    tokens::Token::Key { vkey, .. } => press_key(vkey),
+                }
+            }
+        }
+        rules::Mode::Clip => {
+            clipboard::put(&tokens::plain(line_slice));
+            signal::send();
+        }
+        rules::Mode::Paste => {
+            clipboard::put(&tokens::plain(line_slice));
+            send_paste();
+        }
+        rules::Mode::Speak | rules::Mode::Spell | rules::Mode::Nato => {
+            if let Err(e) = speech::say(&tokens::plain(line_slice), mode) {
+                message_box(
+                    &format!("powershell.exe: {}", e),
+                    "Speech Error",
+                    win32::MB_OK | win32::MB_ICONEXCLAMATION,
+                );
+            }
+        }
+    }
+    return true;
+}
+
+fn key_input(vkey: u16, flags: u32) -> win32::INPUT {
+    return win32::INPUT {
+        r#type: win32::INPUT_KEYBOARD,
+        Anonymous: win32::INPUT_0 {
+            ki: win32::KEYBDINPUT { wVk: vkey, wScan: 0, dwFlags: flags, time: 0, dwExtraInfo: 0 },
+        },
+    };
+}
+
+/// Returns how many of the inputs got through; fewer when something blocks them.
+fn send_inputs(inputs: &[win32::INPUT]) -> u32 {
+    return unsafe {
+        win32::SendInput(
+            inputs.len() as u32,
+            inputs.as_ptr(),
+            std::mem::size_of::<win32::INPUT>() as i32,
+        )
+    };
+}
+
+/// Presses and releases one virtual key in the focused window.
+fn press_key(vkey: u16) {
+    send_inputs(&[key_input(vkey, 0), key_input(vkey, win32::KEYEVENTF_KEYUP)]);
+}
+
+/// Presses Backspace `count` times in the focused window.
+fn erase(count: usize) {
+    // apps that drop fast chars drop fast backspaces too
+    if paces_chars() {
+        let timer = timing::Timer::new();
+        for i in 0..count {
+            if i > 0 {
+                timer.wait(char_delay(""));
+            }
+            press_key(win32::VK_BACK);
+        }
+        return;
+    }
+    let mut inputs = Vec::with_capacity(count * 2);
+    for _ in 0..count {
+        inputs.push(key_input(win32::VK_BACK, 0));
+        inputs.push(key_input(win32::VK_BACK, win32::KEYEVENTF_KEYUP));
+    }
+    send_inputs(&inputs);
+}
+
+/// Presses and releases Ctrl+V in the focused window.
+fn send_paste() {
+    send_inputs(&[
+        key_input(win32::VK_CONTROL, 0),
+        key_input(b'V' as u16, 0),
+        key_input(b'V' as u16, win32::KEYEVENTF_KEYUP),
+        key_input(win32::VK_CONTROL, win32::KEYEVENTF_KEYUP),
+    ]);
+}
+
+/// Whether chars are typed one by one with waits between them, for `chardelay=` or
+/// `humanize=`; otherwise everything is sent at once.
+fn paces_chars() -> bool {
+    return CHAR_DELAY_MICROS.load(std::sync::atomic::Ordering::Relaxed) > 0 || humanize::is_on();
+}
+
+/// The wait after typing `typed`, a char or "" for a key: random with `humanize=`, else
+/// `chardelay=`.
+fn char_delay(typed: &str) -> std::time::Duration {
+    if let Some(delay) = humanize::delay(typed) {
+        return delay;
+    }
+    let micros = CHAR_DELAY_MICROS.load(std::sync::atomic::Ordering::Relaxed);
+    return std::time::Duration::from_micros(micros);
+}
+
+/// Types the line with `SendInput`: a press and release per char, wrapped in the Shift, Ctrl
+/// and Alt presses the char needs on the layout. The inputs go out `TYPING_CHUNK` at a time;
+/// with `chardelay=` or `humanize=` every char is a call of its own, with the wait after it.
+/// Returns false if Esc was down between two calls, with the rest of the line left out.
+fn type_out_characters(line_slice: &str) -> bool {
+    let timer = paces_chars().then(timing::Timer::new);
+    let layout = check::active_layout();
+    let mut inputs = Vec::with_capacity(line_slice.len() * 2);
+    // modifiers still held from the hotkey would change every char, so let go of them first
+    for modifier in
+        [win32::VK_SHIFT, win32::VK_CONTROL, win32::VK_MENU, win32::VK_LWIN, win32::VK_RWIN]
+    {
+        if unsafe { win32::GetAsyncKeyState(modifier as i32) } < 0 {
+            inputs.push(key_input(modifier, win32::KEYEVENTF_KEYUP));
+        }
+    }
+    let mut previous = "";
+    for (i, cluster) in grapheme::clusters(line_slice).into_iter().enumerate() {
+        if i > 0 && (timer.is_some() || inputs.len() >= TYPING_CHUNK) {
+            send_inputs(&inputs);
+            inputs.clear();
+            if let Some(timer) = &timer {
+                timer.wait(char_delay(previous));
+            }
+            if esc_is_down() {
+                return false;
+            }
+        }
+        previous = cluster;
+        let mut chars = cluster.chars();
+        let (Some(chr), None) = (chars.next(), chars.next()) else {
+            // a letter with its marks or a joined emoji goes in one piece, or a key between
+            // its parts could combine with the first one
+            cluster.chars().for_each(|c| push_unicode(&mut inputs, c));
+            continue;
         };
-        let mut input = win32::INPUT {
-            r#type: win32::INPUT_KEYBOARD,
-            Anonymous: win32::INPUT_0 { ki: keyboard_input },
+        // a Hebrew or Arabic layout with Caps Lock on types Latin letters from those keys, so
+        // right-to-left letters always go as chars, in the order they are stored
+        let Some(vkey) = check::key_for(chr, layout).filter(|_| !bidi::is_rtl_char(chr)) else {
+            push_unicode(&mut inputs, chr);
+            continue;
         };
+        let wvk = lobyte(vkey as u64) as u16;
+        let scan = unsafe { win32::MapVirtualKeyW(wvk as u32, win32::MAPVK_VK_TO_VSC) as u16 };
+        // the high byte says which modifiers the char needs
+        let modifiers: Vec<u16> =
+            [(1, win32::VK_SHIFT), (2, win32::VK_CONTROL), (4, win32::VK_MENU)]
+                .into_iter()
+                .filter(|&(bit, _)| (vkey >> 8) & bit != 0)
+                .map(|(_, modifier)| modifier)
+                .collect();
+        for &modifier in &modifiers {
+            inputs.push(key_input(modifier, 0));
+        }
+        for flags in [0, win32::KEYEVENTF_KEYUP] {
+            let mut input = key_input(wvk, flags);
+            input.Anonymous.ki.wScan = scan;
+            inputs.push(input);
+        }
+        for &modifier in modifiers.iter().rev() {
+            inputs.push(key_input(modifier, win32::KEYEVENTF_KEYUP));
+        }
+    }
+    send_inputs(&inputs);
+    return true;
+}
 
-        unsafe {
-            win32::SendInput(1, &input, std::mem::size_of::<win32::INPUT>() as i32);
-            keyboard_input.dwFlags = win32::KEYEVENTF_KEYUP;
-            input.Anonymous = win32::INPUT_0 { ki: keyboard_input };
+/// Adds `chr` as `KEYEVENTF_UNICODE` input, for chars the keyboard layout has no key for.
+fn push_unicode(inputs: &mut Vec<win32::INPUT>, chr: char) {
+    let mut units = [0; 2];
+    for &unit in chr.encode_utf16(&mut units).iter() {
+        for flags in [win32::KEYEVENTF_UNICODE, win32::KEYEVENTF_UNICODE | win32::KEYEVENTF_KEYUP] {
+            let mut input = key_input(0, flags);
+            input.Anonymous.ki.wScan = unit;
+            inputs.push(input);
         }
     }
 }
 
 fn set_clipboard_string(line_slice: &str) {
-    let bytes = line_slice.as_bytes();
+    let wide: Vec<u16> = line_slice.encode_utf16().chain(std::iter::once(0)).collect();
     unsafe {
         if win32::OpenClipboard(0) != 0 {
             win32::EmptyClipboard();
 
-            let size = line_slice.len() + 1;
+            let size = wide.len() * std::mem::size_of::<u16>();
             let h_mem = windows_sys::Win32::System::Memory::GlobalAlloc(
                 windows_sys::Win32::System::Memory::GMEM_MOVEABLE,
                 size,
             );
             if h_mem != std::ptr::null_mut() {
-                let mem_data = win32::GlobalLock(h_mem) as *mut u8;
-                for i in 0..line_slice.len() {
-                    let &byte = bytes.get_unchecked(i);
-                    *mem_data.offset(i as isize) = byte;
-                }
+                let mem_data = win32::GlobalLock(h_mem) as *mut u16;
+                std::ptr::copy_nonoverlapping(wide.as_ptr(), mem_data, wide.len());
                 win32::GlobalUnlock(mem_data as _);
 
-                win32::SetClipboardData(win32::CF_TEXT as u32, h_mem as isize);
+                win32::SetClipboardData(win32::CF_UNICODETEXT as u32, h_mem as isize);
             }
 
             win32::CloseClipboard();
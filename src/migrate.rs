@@ -0,0 +1,107 @@
+/// The version of randword that last ran with these files, `version.dat`.
+pub const VERSION_PATH: &str = "./version.dat";
+
+/// Brings the files of an older version over to the one it is listed with.
+type Step = fn(&crate::Options);
+
+/// The versions that changed the config or state files, in order, with their step.
+const STEPS: [(&str, Step); 1] = [("0.1.0", positions_dat)];
+
+/// Brings the files over once a newer randword runs, after an update or the first time: the
+/// steps of every version after the one in `version.dat` run, all of them without the file,
+/// then this version is kept there. Each step passes over files it finds migrated already.
+/// An older randword, run after a newer one, runs none and leaves the file as it is.
+pub fn upgrade(options: &crate::Options) {
+    let path = options.state_path(VERSION_PATH);
+    let current = env!("CARGO_PKG_VERSION");
+    let last = std::fs::read_to_string(&path).ok();
+    if last.as_deref().map(str::trim) == Some(current) {
+        return;
+    }
+    let last = last.and_then(|l| crate::update::Version::parse(l.trim()).ok());
+    let Ok(now) = crate::update::Version::parse(current) else {
+        return;
+    };
+    if last.as_ref().is_some_and(|last| *last > now) {
+        return;
+    }
+    for (version, step) in STEPS {
+        let Ok(version) = crate::update::Version::parse(version) else {
+            continue;
+        };
+        if last.as_ref().is_none_or(|last| version > *last) {
+            step(options);
+        }
+    }
+    if let Err(e) = std::fs::write(&path, current) {
+        crate::message_box(
+            &format!("{}: {}", path, e),
+            "File Write Error",
+            crate::win32::MB_OK | crate::win32::MB_ICONEXCLAMATION,
+        );
+    }
+}
+
+/// 0.1.0 keeps the place in every list in positions.dat, not in a `skipline.dat` per list.
+fn positions_dat(options: &crate::Options) {
+    if options.state_dir.is_some() || options.skipline.is_some() {
+        return;
+    }
+    let mut lists = vec![options.words_path()];
+    let bound = options.bindings.iter().map(|(_, path, _)| path);
+    for list in options.lists.iter().chain(bound) {
+        if !lists.contains(&list.as_str()) {
+            lists.push(list);
+        }
+    }
+    offer(&lists, &options.state_path(crate::positions::PATH));
+}
+
+/// Offers to copy the positions older versions kept in a `skipline.dat` per list into
+/// `positions.dat`, if it isn't there yet. Every list the options name is looked at,
+/// not only the one opened first, and the positions are taken over to the line. The old files
+/// are left for older versions; without the copy they are still read for lists missing in
+/// `positions.dat`.
+fn offer(lists: &[&str], positions: &str) {
+    if std::path::Path::new(positions).exists() {
+        return;
+    }
+    let legacy: Vec<(&str, String, u64)> = lists
+        .iter()
+        .filter_map(|&list| {
+            let path = crate::skipline_path(list);
+            let bytes = std::fs::read(&path).ok()?;
+            Some((list, path, crate::parse_lines_to_skip(&bytes)))
+        })
+        .collect();
+    if legacy.is_empty() {
+        return;
+    }
+    let found: Vec<String> = legacy
+        .iter()
+        .map(|(list, path, lines)| format!("{} at line {}, from {}", list, lines, path))
+        .collect();
+    let text = format!(
+        "The place in every list is kept in {} now. Older files were found:\n\n{}\n\nCopy them \
+         over? The old files are left for older versions.",
+        positions,
+        found.join("\n")
+    );
+    if !crate::confirm(&text, "Migrate Positions") {
+        // an empty file, so this is asked once; the old files are still read without it
+        let _ = std::fs::write(positions, "");
+        return;
+    }
+    for (list, _, lines) in legacy {
+        if let Err(e) =
+            crate::positions::save(positions, &crate::positions::key(list), lines, lines)
+        {
+            crate::message_box(
+                &format!("{}: {}", positions, e),
+                "File Write Error",
+                crate::win32::MB_OK | crate::win32::MB_ICONEXCLAMATION,
+            );
+            return;
+        }
+    }
+}
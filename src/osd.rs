@@ -0,0 +1,269 @@
+use crate::win32;
+
+const CLASS_NAME: *const u16 = win32::w!("randword_osd");
+/// How long the word stays fully visible, then how long it takes to fade out.
+const HOLD: std::time::Duration = std::time::Duration::from_millis(900);
+const FADE: std::time::Duration = std::time::Duration::from_millis(400);
+const OPACITY: u8 = 230;
+const PADDING: i32 = 10;
+/// Distance from the cursor, or from the screen corner.
+const OFFSET: i32 = 24;
+
+/// `osd` shows the word near the cursor, `osd=caret` under the text caret where there is
+/// one, and `osd=corner` in the bottom right screen corner.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Placement {
+    Cursor,
+    Caret,
+    Corner,
+}
+
+/// The overlay window and the word in it; kept in the app, which its window is attached to as
+/// well for `WM_PAINT`.
+pub struct Overlay {
+    h_window: win32::HWND,
+    placement: Placement,
+    text: Vec<u16>,
+    rtl: bool,
+    /// when the word was flashed, to fade it out; None while held or hidden
+    shown: Option<std::time::Instant>,
+}
+
+impl Drop for Overlay {
+    fn drop(&mut self) {
+        crate::app::detach(self.h_window);
+        unsafe { win32::DestroyWindow(self.h_window) };
+    }
+}
+
+extern "system" fn wndproc(
+    window: win32::HWND,
+    message: u32,
+    wparam: win32::WPARAM,
+    lparam: win32::LPARAM,
+) -> win32::LRESULT {
+    unsafe {
+        if message != win32::WM_PAINT {
+            return win32::DefWindowProcW(window, message, wparam, lparam);
+        }
+        let mut ps: win32::PAINTSTRUCT = std::mem::zeroed();
+        let hdc = win32::BeginPaint(window, &mut ps);
+        let mut rect: win32::RECT = std::mem::zeroed();
+        win32::GetClientRect(window, &mut rect);
+        win32::FillRect(hdc, &rect, win32::GetSysColorBrush(win32::COLOR_INFOBK));
+        win32::SelectObject(hdc, win32::GetStockObject(win32::DEFAULT_GUI_FONT));
+        win32::SetBkMode(hdc, win32::TRANSPARENT as i32);
+        win32::SetTextColor(hdc, win32::GetSysColor(win32::COLOR_INFOTEXT));
+        let overlay = crate::app::get(window).map(|app| app.overlay.borrow());
+        if let Some(overlay) = overlay.as_ref().and_then(|overlay| overlay.as_ref()) {
+            let mut format =
+                win32::DT_SINGLELINE | win32::DT_VCENTER | win32::DT_CENTER | win32::DT_NOPREFIX;
+            if overlay.rtl {
+                format |= win32::DT_RTLREADING;
+            }
+            win32::DrawTextW(
+                hdc,
+                overlay.text.as_ptr(),
+                overlay.text.len() as i32,
+                &mut rect,
+                format,
+            );
+        }
+        win32::EndPaint(window, &ps);
+        return 0;
+    }
+}
+
+/// Creates the hidden overlay window for the app attached to `owner`: layered to fade,
+/// click-through and never activated, so it can't take the focus from the app the word went to.
+pub fn create(
+    instance: win32::HINSTANCE,
+    owner: win32::HWND,
+    placement: Placement,
+) -> anyhow::Result<()> {
+    let Some(app) = crate::app::get(owner) else {
+        return Ok(());
+    };
+    let wc = win32::WNDCLASSW {
+        style: 0,
+        lpfnWndProc: Some(wndproc),
+        cbClsExtra: 0,
+        cbWndExtra: 0,
+        hInstance: instance,
+        hIcon: 0,
+        hCursor: 0,
+        hbrBackground: 0,
+        lpszMenuName: std::ptr::null(),
+        lpszClassName: CLASS_NAME,
+    };
+    let h_window = unsafe {
+        if win32::RegisterClassW(&wc) == 0 {
+            return Err(anyhow::anyhow!(
+                "Overlay class: {}",
+                crate::format_win32_error(win32::GetLastError())
+            ));
+        }
+        win32::CreateWindowExW(
+            win32::WS_EX_LAYERED
+                | win32::WS_EX_TRANSPARENT
+                | win32::WS_EX_TOPMOST
+                | win32::WS_EX_TOOLWINDOW
+                | win32::WS_EX_NOACTIVATE,
+            CLASS_NAME,
+            std::ptr::null(),
+            win32::WS_POPUP,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            instance,
+            std::ptr::null(),
+        )
+    };
+    if h_window == 0 {
+        let error_code = unsafe { win32::GetLastError() };
+        return Err(anyhow::anyhow!("Overlay window: {}", crate::format_win32_error(error_code)));
+    }
+    app.attach(h_window);
+    let overlay = Overlay { h_window, placement, text: Vec::new(), rtl: false, shown: None };
+    *app.overlay.borrow_mut() = Some(overlay);
+    return Ok(());
+}
+
+/// Flashes `word` in the overlay of the app attached to `owner`, if there is one.
+pub fn flash(owner: win32::HWND, word: &str) {
+    show(owner, word, Some(std::time::Instant::now()));
+}
+
+/// Shows `text` in the overlay until the next `flash`, `hold` or `hide`.
+pub fn hold(owner: win32::HWND, text: &str) {
+    show(owner, text, None);
+}
+
+pub fn hide(owner: win32::HWND) {
+    let Some(app) = crate::app::get(owner) else {
+        return;
+    };
+    let h_window = {
+        let mut guard = app.overlay.borrow_mut();
+        let Some(overlay) = guard.as_mut() else {
+            return;
+        };
+        overlay.shown = None;
+        overlay.h_window
+    };
+    unsafe { win32::ShowWindow(h_window, win32::SW_HIDE) };
+}
+
+/// Puts `word` in the overlay, to fade out after `shown` if given.
+fn show(owner: win32::HWND, word: &str, shown: Option<std::time::Instant>) {
+    let Some(app) = crate::app::get(owner) else {
+        return;
+    };
+    let text: Vec<u16> = word.encode_utf16().collect();
+    // let go before any call that could paint the window, which borrows it too
+    let (h_window, placement) = {
+        let mut guard = app.overlay.borrow_mut();
+        let Some(overlay) = guard.as_mut() else {
+            return;
+        };
+        overlay.text = text.clone();
+        overlay.rtl = crate::bidi::is_rtl(word);
+        overlay.shown = shown;
+        (overlay.h_window, overlay.placement)
+    };
+    let mut size: win32::SIZE = unsafe { std::mem::zeroed() };
+    unsafe {
+        let hdc = win32::GetDC(h_window);
+        win32::SelectObject(hdc, win32::GetStockObject(win32::DEFAULT_GUI_FONT));
+        win32::GetTextExtentPoint32W(hdc, text.as_ptr(), text.len() as i32, &mut size);
+        win32::ReleaseDC(h_window, hdc);
+    }
+    let width = size.cx + 2 * PADDING;
+    let height = size.cy + 2 * PADDING;
+
+    // just below the caret line, so the word just typed stays readable
+    let caret = match placement {
+        Placement::Caret => crate::dock::caret(),
+        _ => None,
+    };
+    let anchor = match caret {
+        Some(caret) => win32::POINT { x: caret.left, y: caret.bottom + PADDING },
+        None => {
+            let mut cursor: win32::POINT = unsafe { std::mem::zeroed() };
+            unsafe { win32::GetCursorPos(&mut cursor) };
+            win32::POINT { x: cursor.x + OFFSET, y: cursor.y + OFFSET }
+        }
+    };
+    let at_anchor =
+        win32::RECT { left: anchor.x, top: anchor.y, right: anchor.x, bottom: anchor.y };
+    let work = crate::dock::work_area(&at_anchor);
+    let (x, y) = match placement {
+        // kept on the screen the cursor or caret is on
+        Placement::Cursor | Placement::Caret => (
+            anchor.x.min(work.right - width).max(work.left),
+            anchor.y.min(work.bottom - height).max(work.top),
+        ),
+        Placement::Corner => (work.right - width - OFFSET, work.bottom - height - OFFSET),
+    };
+    unsafe {
+        win32::SetLayeredWindowAttributes(h_window, 0, OPACITY, win32::LWA_ALPHA);
+        win32::SetWindowPos(
+            h_window,
+            win32::HWND_TOPMOST,
+            x,
+            y,
+            width,
+            height,
+            win32::SWP_NOACTIVATE | win32::SWP_SHOWWINDOW,
+        );
+        win32::InvalidateRect(h_window, std::ptr::null(), 0);
+    }
+}
+
+/// Whether a word is in the overlay, to be faded out by `poll`.
+pub fn is_showing(owner: win32::HWND) -> bool {
+    let Some(app) = crate::app::get(owner) else {
+        return false;
+    };
+    return app.overlay.borrow().as_ref().is_some_and(|o| o.shown.is_some());
+}
+
+/// Paints the overlay and fades it out a step; called every poll. The main loop only takes
+/// the messages of the main window.
+pub fn poll(owner: win32::HWND) {
+    let Some(app) = crate::app::get(owner) else {
+        return;
+    };
+    let Some(h_window) = app.overlay.borrow().as_ref().map(|o| o.h_window) else {
+        return;
+    };
+    // not borrowed meanwhile, `WM_PAINT` borrows it
+    let mut msg = unsafe { std::mem::zeroed() };
+    while unsafe { win32::PeekMessageW(&mut msg, h_window, 0, 0, win32::PM_REMOVE) } != 0 {
+        unsafe { win32::DispatchMessageW(&msg) };
+    }
+
+    let fading = {
+        let mut guard = app.overlay.borrow_mut();
+        let Some(shown) = guard.as_ref().and_then(|o| o.shown) else {
+            return;
+        };
+        let fading = shown.elapsed().saturating_sub(HOLD);
+        if fading >= FADE {
+            if let Some(overlay) = guard.as_mut() {
+                overlay.shown = None;
+            }
+        }
+        fading
+    };
+    if fading >= FADE {
+        unsafe { win32::ShowWindow(h_window, win32::SW_HIDE) };
+    } else if !fading.is_zero() {
+        let left = 1.0 - fading.as_secs_f32() / FADE.as_secs_f32();
+        let alpha = (OPACITY as f32 * left) as u8;
+        unsafe { win32::SetLayeredWindowAttributes(h_window, 0, alpha, win32::LWA_ALPHA) };
+    }
+}
@@ -0,0 +1,168 @@
+use std::io::Write;
+
+enum Format {
+    Plain,
+    Time,
+    Json,
+}
+
+/// `out=<file>`, `out=<file>,time` or `out=<file>,json`: words are appended to `file`, one per
+/// line; with `time` the local time and a tab go in front, with `json` every line is an object
+/// like `{"time":"…","event":"word","word":"…","mode":"type","target":"…","list":"…","line":12}`
+/// for jq and log shippers, and the other events go there too, see `log_event`. The file is
+/// opened for every word, so other tools can read or rotate it meanwhile.
+pub struct FileOutput {
+    path: String,
+    format: Format,
+}
+
+impl FileOutput {
+    pub fn parse(text: &str) -> Self {
+        for (suffix, format) in [(",time", Format::Time), (",json", Format::Json)] {
+            if let Some(path) = text.strip_suffix(suffix) {
+                return Self { path: path.to_owned(), format };
+            }
+        }
+        return Self { path: text.to_owned(), format: Format::Plain };
+    }
+
+    pub fn append(
+        &self,
+        word: &str,
+        entry: &crate::session::Entry,
+        list: &crate::list::WordList,
+    ) -> std::io::Result<()> {
+        let mut out = std::fs::OpenOptions::new().create(true).append(true).open(&self.path)?;
+        return match self.format {
+            Format::Plain => writeln!(out, "{}", word),
+            Format::Time => writeln!(out, "{}\t{}", entry.timestamp, word),
+            Format::Json => writeln!(
+                out,
+                "{{\"time\":{},\"event\":\"word\",\"word\":{},\"mode\":{},\"target\":{},\"list\":{},\"line\":{}}}",
+                crate::http::json_string(&entry.timestamp),
+                crate::http::json_string(word),
+                crate::http::json_string(entry.mode),
+                crate::http::json_string(&entry.target),
+                crate::http::json_string(&list.path),
+                list.position
+            ),
+        };
+    }
+}
+
+/// The `out=<file>,json` files, set from the options for `log_event`, which is called where
+/// no options are at hand.
+static EVENT_LOGS: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(Vec::new());
+
+/// Sets the files `log_event` writes to, the JSON ones of `pipeline`.
+pub fn configure(pipeline: &Pipeline) {
+    let logs = pipeline.outputs.iter().filter_map(|output| match output {
+        Output::File(FileOutput { path, format: Format::Json }) => Some(path.clone()),
+        _ => None,
+    });
+    *EVENT_LOGS.lock().unwrap_or_else(|e| e.into_inner()) = logs.collect();
+}
+
+/// Appends `{"time":"…","event":…,"text":…}` to every `out=<file>,json`: `undo` and `rate`,
+/// and `error`, `list_completed` and `goal_met` as the webhook gets them. A log that can't be
+/// written is passed over, like a failed webhook.
+pub fn log_event(event: &str, text: &str) {
+    let logs = EVENT_LOGS.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    if logs.is_empty() {
+        return;
+    }
+    let line = format!(
+        "{{\"time\":{},\"event\":{},\"text\":{}}}",
+        crate::http::json_string(&crate::session::local_timestamp()),
+        crate::http::json_string(event),
+        crate::http::json_string(text)
+    );
+    for path in logs {
+        if let Ok(mut out) = std::fs::OpenOptions::new().create(true).append(true).open(&path) {
+            let _ = writeln!(out, "{}", line);
+        }
+    }
+}
+
+pub enum Output {
+    /// `out=emit`: typed or put on the clipboard as `clip`, `autoclip` and the rules decide
+    Emit,
+    /// `out=type`, `out=clip` or `out=paste`, whatever the rules say
+    Fixed(crate::rules::Mode),
+    File(FileOutput),
+}
+
+impl Output {
+    pub fn parse(text: &str) -> Self {
+        if text == "emit" {
+            return Self::Emit;
+        }
+        return match crate::rules::Mode::parse(text) {
+            Some(mode) => Self::Fixed(mode),
+            None => Self::File(FileOutput::parse(text)),
+        };
+    }
+}
+
+/// Every `out=` option, run in the order given for each word, e.g.
+/// `out=type out=obs.txt out=journal.txt,time` types the word, then shows it in an OBS text
+/// source, then logs it. Without any, words are only emitted.
+#[derive(Default)]
+pub struct Pipeline {
+    outputs: Vec<Output>,
+}
+
+impl Pipeline {
+    pub fn push(&mut self, output: Output) {
+        self.outputs.push(output);
+    }
+
+    fn outputs(&self) -> &[Output] {
+        if self.outputs.is_empty() {
+            return &[Output::Emit];
+        }
+        return &self.outputs;
+    }
+
+    /// What the session records as the mode: the first output that reaches the focused app.
+    pub fn mode_name(&self, emit_mode: crate::rules::Mode) -> &'static str {
+        for output in self.outputs() {
+            match output {
+                Output::Emit => return emit_mode.name(),
+                Output::Fixed(mode) => return mode.name(),
+                Output::File(_) => {}
+            }
+        }
+        return "file";
+    }
+
+    /// Runs every output even if one fails, and returns the first failure. Returns false if Esc
+    /// stopped the typing; the outputs after it don't run then.
+    pub fn run(
+        &self,
+        text: &str,
+        emit_mode: crate::rules::Mode,
+        entry: &crate::session::Entry,
+        list: &crate::list::WordList,
+    ) -> std::io::Result<bool> {
+        let mut result = Ok(true);
+        for output in self.outputs() {
+            let emitted = match output {
+                Output::Emit => crate::emit(text, emit_mode),
+                Output::Fixed(mode) => crate::emit(text, *mode),
+                Output::File(file) => {
+                    if let Err(e) = file.append(&crate::tokens::plain(text), entry, list) {
+                        if result.is_ok() {
+                            result = Err(e);
+                        }
+                    }
+                    true
+                }
+            };
+            if !emitted {
+                return result.map(|_| false);
+            }
+        }
+        return result;
+    }
+}
@@ -0,0 +1,176 @@
+use crate::win32;
+
+/// How many of the last words the window lists, above the progress line.
+const HISTORY_LINES: usize = 3;
+const LINE_HEIGHT: i32 = 20;
+const MARGIN: i32 = 8;
+/// Text colors of the lists, as 0x00BBGGRR, in the order they were first dispensed from.
+const CHANNEL_COLORS: [u32; 6] =
+    [0x00B05000, 0x002828C0, 0x003C8000, 0x00A03280, 0x000064C8, 0x00827800];
+
+/// The last words dispensed, with the index of their list in `lists`, the position in the
+/// list last used as `(line, total)`, and a status note like the peeked next word.
+#[derive(Default)]
+pub struct History {
+    entries: std::collections::VecDeque<(usize, String)>,
+    lists: Vec<String>,
+    progress: Option<(u64, u64)>,
+    status: Option<String>,
+}
+
+/// Changes the history of the app attached to `h_window` and has the window redrawn.
+fn update(h_window: win32::HWND, change: impl FnOnce(&mut History)) {
+    let Some(app) = crate::app::get(h_window) else {
+        return;
+    };
+    change(&mut app.history.borrow_mut());
+    unsafe { win32::InvalidateRect(h_window, std::ptr::null(), 0) };
+}
+
+/// Lists `word` in the window. Once words came from more than one list, each is labeled and
+/// colored by the list it came from.
+pub fn add_to_history(h_window: win32::HWND, list: &crate::list::WordList, word: &str) {
+    update(h_window, |history| {
+        let channel = match history.lists.iter().position(|l| *l == list.path) {
+            Some(i) => i,
+            None => {
+                history.lists.push(list.path.clone());
+                history.lists.len() - 1
+            }
+        };
+        history.entries.push_back((channel, word.to_owned()));
+        if history.entries.len() > HISTORY_LINES {
+            history.entries.pop_front();
+        }
+    });
+    show_progress(h_window, list);
+}
+
+/// Takes an undone word out of the window again.
+pub fn remove_last_from_history(h_window: win32::HWND, list: &crate::list::WordList) {
+    update(h_window, |history| {
+        history.entries.pop_back();
+    });
+    show_progress(h_window, list);
+}
+
+/// Shows the line `list` is at, after it moved without a word being dispensed too. The status
+/// note is gone with the move.
+pub fn show_progress(h_window: win32::HWND, list: &crate::list::WordList) {
+    update(h_window, |history| {
+        history.progress = Some((list.position, list.total));
+        history.status = None;
+    });
+}
+
+/// Shows `text` next to the progress, until the list moves.
+pub fn show_status(h_window: win32::HWND, text: &str) {
+    update(h_window, |history| history.status = Some(text.to_owned()));
+}
+
+/// Handles `WM_PAINT` by drawing into an off-screen bitmap and blitting it in one go,
+/// so the client area never flickers or shows what was under it.
+pub fn paint(h_window: win32::HWND) {
+    unsafe {
+        let mut ps: win32::PAINTSTRUCT = std::mem::zeroed();
+        let hdc = win32::BeginPaint(h_window, &mut ps);
+        if hdc == 0 {
+            return;
+        }
+
+        let mut rect: win32::RECT = std::mem::zeroed();
+        win32::GetClientRect(h_window, &mut rect);
+        let width = rect.right - rect.left;
+        let height = rect.bottom - rect.top;
+
+        let mem_dc = win32::CreateCompatibleDC(hdc);
+        let bitmap = win32::CreateCompatibleBitmap(hdc, width, height);
+        if mem_dc == 0 || bitmap == 0 {
+            // fall back to drawing straight onto the window
+            draw(h_window, hdc, &rect);
+        } else {
+            let old_bitmap = win32::SelectObject(mem_dc, bitmap);
+            draw(h_window, mem_dc, &rect);
+            win32::BitBlt(hdc, 0, 0, width, height, mem_dc, 0, 0, win32::SRCCOPY);
+            win32::SelectObject(mem_dc, old_bitmap);
+        }
+
+        if bitmap != 0 {
+            win32::DeleteObject(bitmap);
+        }
+        if mem_dc != 0 {
+            win32::DeleteDC(mem_dc);
+        }
+        win32::EndPaint(h_window, &ps);
+    }
+}
+
+fn draw(h_window: win32::HWND, hdc: win32::HDC, rect: &win32::RECT) {
+    unsafe {
+        win32::FillRect(hdc, rect, win32::GetSysColorBrush(win32::COLOR_WINDOW));
+        win32::SelectObject(hdc, win32::GetStockObject(win32::DEFAULT_GUI_FONT));
+        win32::SetBkMode(hdc, win32::TRANSPARENT as i32);
+    }
+    let Some(app) = crate::app::get(h_window) else {
+        return;
+    };
+    let history = app.history.borrow();
+    let labeled = history.lists.len() > 1;
+    let text_color = unsafe { win32::GetSysColor(win32::COLOR_WINDOWTEXT) };
+    let gray = unsafe { win32::GetSysColor(win32::COLOR_GRAYTEXT) };
+    for (i, (channel, word)) in history.entries.iter().enumerate() {
+        let (color, text) = if labeled {
+            let list = &history.lists[*channel];
+            let stem = std::path::Path::new(list).file_stem();
+            let label = stem.map_or(list.as_str().into(), |s| s.to_string_lossy());
+            let word = crate::bidi::isolate(word);
+            (CHANNEL_COLORS[channel % CHANNEL_COLORS.len()], format!("[{}]  {}", label, word))
+        } else if i + 1 == history.entries.len() {
+            (text_color, word.clone())
+        } else {
+            // the words before the last one are just context
+            (gray, word.clone())
+        };
+        let top = rect.top + MARGIN + i as i32 * LINE_HEIGHT;
+        // a Hebrew or Arabic word reads from the right edge; with a list label it stays left
+        let align = match !labeled && crate::bidi::is_rtl(word) {
+            true => win32::DT_RIGHT | win32::DT_RTLREADING,
+            false => win32::DT_LEFT,
+        };
+        draw_line(hdc, rect, top, &text, color, align);
+    }
+    if let Some((line, total)) = history.progress {
+        let text = format!("Line {} of {}", line, total);
+        let top = rect.bottom - MARGIN - LINE_HEIGHT;
+        draw_line(hdc, rect, top, &text, gray, win32::DT_RIGHT);
+    }
+    if let Some(status) = &history.status {
+        let top = rect.bottom - MARGIN - LINE_HEIGHT;
+        draw_line(hdc, rect, top, status, gray, win32::DT_LEFT);
+    }
+}
+
+/// One line of text across the window at `top`, cut off with an ellipsis if too long.
+fn draw_line(hdc: win32::HDC, rect: &win32::RECT, top: i32, text: &str, color: u32, align: u32) {
+    let mut line = win32::RECT {
+        left: rect.left + MARGIN,
+        top,
+        right: rect.right - MARGIN,
+        bottom: top + LINE_HEIGHT,
+    };
+    let wide: Vec<u16> = text.encode_utf16().collect();
+    unsafe {
+        win32::SetTextColor(hdc, color);
+        win32::DrawTextW(
+            hdc,
+            wide.as_ptr(),
+            wide.len() as i32,
+            &mut line,
+            align
+                | win32::DT_SINGLELINE
+                | win32::DT_VCENTER
+                | win32::DT_NOPREFIX
+                | win32::DT_END_ELLIPSIS,
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use crate::win32;
+
+/// How many of the coming words the picker offers.
+pub const WORDS: usize = 10;
+const WIDTH: i32 = 320;
+/// More items than this scroll.
+const MAX_ROWS: usize = 20;
+/// The list box border, above and below the items.
+const BORDER: i32 = 2;
+
+/// Under the text caret, or at the mouse cursor when the app doesn't show one.
+fn anchor() -> win32::POINT {
+    if let Some(caret) = crate::dock::caret() {
+        return win32::POINT { x: caret.left, y: caret.bottom };
+    }
+    let mut cursor = win32::POINT { x: 0, y: 0 };
+    unsafe { win32::GetCursorPos(&mut cursor) };
+    return cursor;
+}
+
+/// Shows `words` in a popup list at the caret, to pick one with the arrow keys and Enter or
+/// with a double click. Returns its index, or None on Esc or when the popup loses the focus.
+/// The app that had the focus gets it back either way, for the word to be typed into. Closing
+/// `owner`, the randword window, ends the pick too.
+pub fn pick(owner: win32::HWND, words: &[&str]) -> Option<usize> {
+    if words.is_empty() {
+        return None;
+    }
+    let previous = unsafe { win32::GetForegroundWindow() };
+    let at = anchor();
+    let h_list = unsafe {
+        win32::CreateWindowExW(
+            win32::WS_EX_TOPMOST | win32::WS_EX_TOOLWINDOW,
+            win32::w!("LISTBOX"),
+            std::ptr::null(),
+            win32::WS_POPUP | win32::WS_BORDER | win32::WS_VSCROLL,
+            at.x,
+            at.y,
+            WIDTH,
+            0,
+            0,
+            0,
+            0,
+            std::ptr::null(),
+        )
+    };
+    if h_list == 0 {
+        return None;
+    }
+    for word in words {
+        let wide: Vec<u16> = word.encode_utf16().chain(std::iter::once(0)).collect();
+        unsafe { win32::SendMessageW(h_list, win32::LB_ADDSTRING, 0, wide.as_ptr() as isize) };
+    }
+    let item_height = unsafe {
+        win32::SendMessageW(h_list, win32::LB_SETCURSEL, 0, 0);
+        win32::SendMessageW(h_list, win32::LB_GETITEMHEIGHT, 0, 0) as i32
+    };
+    let height = item_height * words.len().min(MAX_ROWS) as i32 + 2 * BORDER;
+    // kept on the screen the caret is on
+    let work =
+        crate::dock::work_area(&win32::RECT { left: at.x, top: at.y, right: at.x, bottom: at.y });
+    let x = at.x.min(work.right - WIDTH).max(work.left);
+    let y = at.y.min(work.bottom - height).max(work.top);
+
+    let choice = unsafe {
+        win32::SetWindowPos(
+            h_list,
+            win32::HWND_TOPMOST,
+            x,
+            y,
+            WIDTH,
+            height,
+            win32::SWP_SHOWWINDOW,
+        );
+        if win32::SetForegroundWindow(h_list) != 0 {
+            run(owner, h_list)
+        } else {
+            None
+        }
+    };
+    unsafe {
+        win32::DestroyWindow(h_list);
+        win32::SetForegroundWindow(previous);
+    }
+    return choice;
+}
+
+/// Handles the popup's input until a word is picked or the pick is given up. Only its own
+/// messages are taken from the queue, hotkeys wait for afterwards.
+fn run(owner: win32::HWND, h_list: win32::HWND) -> Option<usize> {
+    loop {
+        let mut msg: win32::MSG = unsafe { std::mem::zeroed() };
+        while unsafe { win32::PeekMessageW(&mut msg, h_list, 0, 0, win32::PM_REMOVE) } != 0 {
+            let picked = match msg.message {
+                win32::WM_KEYDOWN if msg.wParam == win32::VK_ESCAPE as usize => return None,
+                win32::WM_KEYDOWN => msg.wParam == win32::VK_RETURN as usize,
+                win32::WM_LBUTTONDBLCLK => true,
+                _ => false,
+            };
+            if picked {
+                let selected = unsafe { win32::SendMessageW(h_list, win32::LB_GETCURSEL, 0, 0) };
+                return usize::try_from(selected).ok();
+            }
+            unsafe {
+                win32::TranslateMessage(&msg);
+                win32::DispatchMessageW(&msg);
+            }
+        }
+        if !crate::is_running(owner) || unsafe { win32::GetForegroundWindow() } != h_list {
+            return None;
+        }
+        unsafe { win32::Sleep(10) };
+    }
+}
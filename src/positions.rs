@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+
+/// The position of every list, one `<lines> <list>` line each. Lists are keyed by their
+/// canonical path, so `words.txt` started from another folder, or the same file reached
+/// through another relative path, keeps its own place. A named instance has a copy of its own.
+pub const PATH: &str = "./positions.dat";
+
+/// The canonical path of `list`, or `list` as given if it doesn't exist (yet).
+pub fn key(list: &str) -> String {
+    return match std::fs::canonicalize(list) {
+        Ok(path) => path.display().to_string(),
+        Err(_) => list.to_owned(),
+    };
+}
+
+fn parse(text: &str) -> BTreeMap<String, u64> {
+    return text
+        .lines()
+        .filter_map(|line| {
+            let (lines, list) = line.split_once(' ')?;
+            Some((list.to_owned(), lines.parse().ok()?))
+        })
+        .collect();
+}
+
+/// Every saved position in `path`; none before the first save.
+fn read(path: &str) -> std::io::Result<BTreeMap<String, u64>> {
+    return match std::fs::read_to_string(path) {
+        Ok(text) => Ok(parse(&text)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(e) => Err(e),
+    };
+}
+
+/// The positions in the conflicted copies a sync client made of the file.
+fn conflicted(copies: &[std::path::PathBuf]) -> Vec<(String, u64)> {
+    return copies
+        .iter()
+        .filter_map(|copy| std::fs::read_to_string(copy).ok())
+        .flat_map(|text| parse(&text))
+        .collect();
+}
+
+/// The saved position of the list under `key` in `path`, or the furthest one of the conflicted
+/// copies.
+pub fn load(path: &str, key: &str) -> std::io::Result<Option<u64>> {
+    let mut lines = read(path)?.get(key).copied();
+    for (list, saved) in conflicted(&crate::conflict::conflicted_copies(path)) {
+        if list == key {
+            lines = Some(lines.unwrap_or(0).max(saved));
+        }
+    }
+    return Ok(lines);
+}
+
+/// Saves `lines` for the list under `key` in `path`, leaving the other lists as they are, and returns
+/// what was saved. `loaded` is the list's position when it was last read or saved: if the file
+/// holds another one now, another instance or a sync client moved it meanwhile and the
+/// furthest of the two is kept. Other instances save under the same `<path>.lock`, so none
+/// of them writes over what another one saved meanwhile.
+pub fn save(path: &str, key: &str, lines: u64, loaded: u64) -> std::io::Result<u64> {
+    let lock = std::path::PathBuf::from(format!("{}.lock", path));
+    return crate::shared::with_lock_file(&lock, || merge(path, key, lines, loaded))?;
+}
+
+/// `save`, holding the lock.
+fn merge(path: &str, key: &str, lines: u64, loaded: u64) -> std::io::Result<u64> {
+    let mut positions = read(path)?;
+    let copies = crate::conflict::conflicted_copies(path);
+    for (list, saved) in conflicted(&copies) {
+        let position = positions.entry(list).or_insert(saved);
+        *position = (*position).max(saved);
+    }
+    let lines = match positions.get(key) {
+        Some(&saved) if saved != loaded => lines.max(saved),
+        _ => lines,
+    };
+    positions.insert(key.to_owned(), lines);
+    let text: String = positions.iter().map(|(list, n)| format!("{} {}\n", n, list)).collect();
+    // written next to it and moved over it, so a crash leaves either the old or the new file
+    let temp = format!("{}.{}.tmp", path, std::process::id());
+    let mut file = std::fs::File::create(&temp)?;
+    file.write_all(text.as_bytes())?;
+    file.sync_all()?;
+    std::fs::rename(&temp, path)?;
+    crate::conflict::remove(&copies);
+    return Ok(lines);
+}
@@ -0,0 +1,78 @@
+use crate::win32;
+
+/// How often the battery saver state is looked up again.
+const RECHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Presentation mode, a full-screen game or video, or Focus Assist's busy state: pop-ups
+/// now would show up on a projector or steal focus.
+pub fn is_presenting() -> bool {
+    let mut state = 0;
+    if unsafe { win32::SHQueryUserNotificationState(&mut state) } != 0 {
+        return false;
+    }
+    return matches!(
+        state,
+        win32::QUNS_PRESENTATION_MODE | win32::QUNS_RUNNING_D3D_FULL_SCREEN | win32::QUNS_BUSY
+    );
+}
+
+/// `WNF_SHEL_QUIETHOURS_ACTIVE_PROFILE_CHANGED`: the Focus Assist profile, 0 while it is off.
+const FOCUS_ASSIST_STATE: u64 = 0x0D83_063E_A3BF_1C75;
+
+#[link(name = "ntdll")]
+extern "system" {
+    /// Undocumented and not in windows-sys, but the only way to read Focus Assist; it has been
+    /// there since Focus Assist came in Windows 10 1803.
+    fn NtQueryWnfStateData(
+        state_name: *const u64,
+        type_id: *const u8,
+        explicit_scope: *const u8,
+        change_stamp: *mut u32,
+        buffer: *mut u8,
+        buffer_size: *mut u32,
+    ) -> i32;
+}
+
+/// Focus Assist (do not disturb) is on, by hand or by its automatic rules, or the older quiet
+/// hours where Focus Assist isn't there.
+pub fn is_do_not_disturb() -> bool {
+    let mut profile = 0u32;
+    let mut size = std::mem::size_of::<u32>() as u32;
+    let mut change_stamp = 0;
+    let status = unsafe {
+        NtQueryWnfStateData(
+            &FOCUS_ASSIST_STATE,
+            std::ptr::null(),
+            std::ptr::null(),
+            &mut change_stamp,
+            &mut profile as *mut u32 as *mut u8,
+            &mut size,
+        )
+    };
+    if status >= 0 && size as usize == std::mem::size_of::<u32>() {
+        return profile != 0;
+    }
+    let mut state = 0;
+    return unsafe { win32::SHQueryUserNotificationState(&mut state) } == 0
+        && state == win32::QUNS_QUIET_TIME;
+}
+
+/// Follows the battery saver, looked up at most every `RECHECK_INTERVAL`.
+#[derive(Default)]
+pub struct BatterySaver {
+    checked: Option<std::time::Instant>,
+    on: bool,
+}
+
+impl BatterySaver {
+    pub fn is_on(&mut self) -> bool {
+        if self.checked.is_some_and(|c| c.elapsed() < RECHECK_INTERVAL) {
+            return self.on;
+        }
+        let mut status = unsafe { std::mem::zeroed() };
+        self.on = unsafe { win32::GetSystemPowerStatus(&mut status) } != 0
+            && status.SystemStatusFlag == 1;
+        self.checked = Some(std::time::Instant::now());
+        return self.on;
+    }
+}
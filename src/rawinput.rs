@@ -0,0 +1,153 @@
+use crate::win32;
+
+/// `raw=<device>,<key>`: dispense when `key` goes down on the keyboard whose device path
+/// contains `device`, e.g. `raw=VID_1234&PID_5678,F13`. `raw=learn` shows the device path
+/// and key of the next key press instead, to find out what to put there.
+///
+/// Raw input only observes keys, it cannot swallow them, so the focused app still gets the
+/// key press. Map the keypad to a key that does nothing there, like F13-F24.
+pub enum RawTrigger {
+    /// only the first key press is reported, the dialog's own Enter would start a loop
+    Learn {
+        done: std::cell::Cell<bool>,
+    },
+    Key {
+        device: String,
+        vkey: u16,
+    },
+}
+
+fn parse_key(text: &str) -> Option<u16> {
+    if let Some(hex) = text.strip_prefix("0x") {
+        return u16::from_str_radix(hex, 16).ok();
+    }
+    if let Some(n) = text.strip_prefix(['F', 'f']).and_then(|n| n.parse::<u16>().ok()) {
+        if (1..=24).contains(&n) {
+            return Some(win32::VK_F1 + n - 1);
+        }
+    }
+    let mut chars = text.chars();
+    return match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_alphanumeric() => Some(c.to_ascii_uppercase() as u16),
+        _ => text.parse().ok(),
+    };
+}
+
+impl RawTrigger {
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        if text == "learn" {
+            return Ok(Self::Learn { done: std::cell::Cell::new(false) });
+        }
+        let Some((device, key)) = text.rsplit_once(',') else {
+            return Err(anyhow::anyhow!(
+                "raw: expected `<device>,<key>` or `learn`, got `{}`",
+                text
+            ));
+        };
+        let Some(vkey) = parse_key(key) else {
+            return Err(anyhow::anyhow!("raw: unknown key `{}`", key));
+        };
+        return Ok(Self::Key { device: device.to_uppercase(), vkey });
+    }
+
+    pub fn register(&self, h_window: win32::HWND) -> anyhow::Result<()> {
+        return register_keyboards(h_window);
+    }
+
+    /// Handles a `WM_INPUT` message, returning true when it is the trigger key going down.
+    pub fn handle(&self, lparam: win32::LPARAM) -> bool {
+        let Some((h_device, vkey)) = read_key_down(lparam) else {
+            return false;
+        };
+        match self {
+            Self::Learn { done } => {
+                if done.replace(true) {
+                    return false;
+                }
+                crate::message_box(
+                    &format!(
+                        "Device: {}\nKey: 0x{:02X}\n\nStart with raw=<part of the device>,0x{:02X}",
+                        device_name(h_device),
+                        vkey,
+                        vkey
+                    ),
+                    "Raw Input",
+                    win32::MB_OK | win32::MB_ICONINFORMATION,
+                );
+                return false;
+            }
+            Self::Key { device, vkey: trigger } => {
+                return vkey == *trigger && device_name(h_device).to_uppercase().contains(device);
+            }
+        }
+    }
+}
+
+/// Asks for keyboard input even while another window has the focus.
+pub fn register_keyboards(h_window: win32::HWND) -> anyhow::Result<()> {
+    let device = win32::RAWINPUTDEVICE {
+        usUsagePage: 0x01, // generic desktop
+        usUsage: 0x06,     // keyboard
+        dwFlags: win32::RIDEV_INPUTSINK,
+        hwndTarget: h_window,
+    };
+    let size = std::mem::size_of::<win32::RAWINPUTDEVICE>() as u32;
+    if unsafe { win32::RegisterRawInputDevices(&device, 1, size) } == 0 {
+        let error_code = unsafe { win32::GetLastError() };
+        return Err(anyhow::anyhow!(
+            "RegisterRawInputDevices failed: {}",
+            crate::format_win32_error(error_code)
+        ));
+    }
+    return Ok(());
+}
+
+/// The device and virtual key of a key going down, from a `WM_INPUT` message. The device is 0
+/// for keys sent with `SendInput`.
+pub fn read_key_down(lparam: win32::LPARAM) -> Option<(win32::HANDLE, u16)> {
+    let mut raw: win32::RAWINPUT = unsafe { std::mem::zeroed() };
+    let mut size = std::mem::size_of::<win32::RAWINPUT>() as u32;
+    let read = unsafe {
+        win32::GetRawInputData(
+            lparam,
+            win32::RID_INPUT,
+            &mut raw as *mut _ as _,
+            &mut size,
+            std::mem::size_of::<win32::RAWINPUTHEADER>() as u32,
+        )
+    };
+    if read == u32::MAX || raw.header.dwType != win32::RIM_TYPEKEYBOARD {
+        return None;
+    }
+    let keyboard = unsafe { raw.data.keyboard };
+    if keyboard.Flags as u32 & win32::RI_KEY_BREAK != 0 {
+        return None;
+    }
+    return Some((raw.header.hDevice, keyboard.VKey));
+}
+
+fn device_name(h_device: win32::HANDLE) -> String {
+    let mut len = 0_u32;
+    unsafe {
+        win32::GetRawInputDeviceInfoW(
+            h_device,
+            win32::RIDI_DEVICENAME,
+            std::ptr::null_mut(),
+            &mut len,
+        );
+    }
+    let mut name = vec![0_u16; len as usize];
+    let read = unsafe {
+        win32::GetRawInputDeviceInfoW(
+            h_device,
+            win32::RIDI_DEVICENAME,
+            name.as_mut_ptr() as _,
+            &mut len,
+        )
+    };
+    if read == u32::MAX {
+        return String::new();
+    }
+    let end = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+    return String::from_utf16_lossy(&name[..end]);
+}
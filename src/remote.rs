@@ -0,0 +1,206 @@
+/// `list=<url>` or `bind=<keys>,<url>`: the list is the first column of a CSV file on the web,
+/// e.g. a Google Sheet published as CSV. It is dispensed from a local copy, which is
+/// downloaded at startup and refreshed every `refresh=` minutes while the app runs.
+///
+/// Only https:// is downloaded, and whoever can edit the sheet still decides what is typed,
+/// so lines of it that press keys like {ENTER} are confirmed first, as with `confirmkeys`.
+#[derive(Clone)]
+pub struct Remote {
+    pub url: String,
+    /// the local copy, one line per row
+    pub copy: String,
+}
+
+pub fn is_url(text: &str) -> bool {
+    return text.starts_with("https://") || text.starts_with("http://");
+}
+
+/// Whether `text` is an https:// URL with a host, the only kind randword sends to or
+/// downloads from.
+pub fn is_https(text: &str) -> bool {
+    return text.strip_prefix("https://").is_some_and(|rest| {
+        !rest.is_empty() && !rest.starts_with('/') && !rest.contains(char::is_whitespace)
+    });
+}
+
+/// FNV-1a, to name the local copy after the URL the same way on every run.
+fn hash(text: &str) -> u64 {
+    let mut hash = 0xcbf29ce484222325_u64;
+    for byte in text.bytes() {
+        hash = (hash ^ byte as u64).wrapping_mul(0x100000001b3);
+    }
+    return hash;
+}
+
+/// The first field of every record of `csv`. Quoted fields may contain commas, doubled
+/// quotes and line breaks; the line breaks become spaces to keep one word per line.
+fn first_column(csv: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut column = 0;
+    let mut field_start = true;
+    let mut quoted = false;
+    let mut chars = csv.chars().peekable();
+    while let Some(c) = chars.next() {
+        let at_start = std::mem::replace(&mut field_start, false);
+        let c = match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                '"'
+            }
+            '"' if quoted || at_start => {
+                quoted = !quoted;
+                continue;
+            }
+            '\r' | '\n' if quoted => ' ',
+            ',' if !quoted => {
+                column += 1;
+                field_start = true;
+                continue;
+            }
+            '\n' => {
+                words.push(std::mem::take(&mut word));
+                column = 0;
+                field_start = true;
+                continue;
+            }
+            '\r' => continue,
+            c => c,
+        };
+        if column == 0 {
+            word.push(c);
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    return words;
+}
+
+impl Remote {
+    pub fn new(url: &str) -> anyhow::Result<Self> {
+        if !is_https(url) {
+            return Err(anyhow::anyhow!("`{}`: lists are only downloaded over https://", url));
+        }
+        return Ok(Self { url: url.to_owned(), copy: format!("./remote-{:016x}.txt", hash(url)) });
+    }
+
+    /// Where `fetch` leaves the new words until `install`.
+    fn staged(&self) -> String {
+        return format!("{}.tmp", self.copy);
+    }
+
+    /// Downloads the CSV and, if the words in it changed, leaves them next to the local copy
+    /// for `install`. Returns whether they did.
+    fn fetch(&self) -> anyhow::Result<bool> {
+        let csv_path = std::path::PathBuf::from(format!("{}.csv", self.copy));
+        crate::update::download(&self.url, &csv_path)?;
+        let csv = std::fs::read_to_string(&csv_path);
+        let _ = std::fs::remove_file(&csv_path);
+        let text: String = first_column(&csv?).into_iter().map(|w| w + "\n").collect();
+        if std::fs::read_to_string(&self.copy).is_ok_and(|old| old == text) {
+            return Ok(false);
+        }
+        std::fs::write(self.staged(), text)?;
+        return Ok(true);
+    }
+
+    /// Moves the words of the last `fetch` over the local copy. Done on the thread that reads
+    /// the list and reloads it right after, which then never reads a copy half written.
+    fn install(&self) -> std::io::Result<()> {
+        return std::fs::rename(self.staged(), &self.copy);
+    }
+
+    /// Downloads the CSV and updates the local copy right away, before the list is opened.
+    pub fn update(&self) -> anyhow::Result<()> {
+        if self.fetch()? {
+            self.install()?;
+        }
+        return Ok(());
+    }
+}
+
+/// Fetches the remote lists in the background every `every`, so a slow network never holds
+/// up the hotkeys.
+pub struct Refresher {
+    remotes: Vec<Remote>,
+    every: std::time::Duration,
+    next: std::time::Instant,
+    /// the lists that changed, once a fetch in progress is done
+    pending: Option<std::sync::mpsc::Receiver<Vec<Remote>>>,
+}
+
+impl Refresher {
+    pub fn new(remotes: Vec<Remote>, every: std::time::Duration) -> Self {
+        let next = std::time::Instant::now() + every;
+        return Self { remotes, every, next, pending: None };
+    }
+
+    /// When the next download starts, if there are remote lists.
+    pub fn due(&self) -> Option<std::time::Instant> {
+        return (!self.remotes.is_empty()).then_some(self.next);
+    }
+
+    /// Whether a download is in progress, which `poll` picks up once done.
+    pub fn is_fetching(&self) -> bool {
+        return self.pending.is_some();
+    }
+
+    /// The local copies that were rewritten since the last call, to be reloaded right away.
+    /// Failed fetches are left for the next round, a network hiccup is no reason to interrupt
+    /// the user.
+    pub fn poll(&mut self) -> Vec<String> {
+        if let Some(pending) = &self.pending {
+            return match pending.try_recv() {
+                Ok(changed) => {
+                    self.pending = None;
+                    changed.into_iter().filter(|r| r.install().is_ok()).map(|r| r.copy).collect()
+                }
+                Err(std::sync::mpsc::TryRecvError::Empty) => Vec::new(),
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    self.pending = None;
+                    Vec::new()
+                }
+            };
+        }
+        if self.remotes.is_empty() || std::time::Instant::now() < self.next {
+            return Vec::new();
+        }
+        self.next = std::time::Instant::now() + self.every;
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let remotes = self.remotes.clone();
+        std::thread::spawn(move || {
+            let changed = remotes.into_iter().filter(|r| r.fetch().unwrap_or(false)).collect();
+            let _ = sender.send(changed);
+        });
+        self.pending = Some(receiver);
+        return Vec::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn takes_the_first_column() {
+        assert_eq!(first_column("cat,animal\r\ndog,animal\r\n"), ["cat", "dog"]);
+        assert_eq!(first_column("one\ntwo"), ["one", "two"]);
+    }
+
+    #[test]
+    fn downloads_only_over_https() {
+        assert!(Remote::new("https://example.com/words.csv").is_ok());
+        assert!(Remote::new("http://example.com/words.csv").is_err());
+        assert!(!is_https("https://"));
+        assert!(!is_https("https://example.com/a b"));
+    }
+
+    #[test]
+    fn unquotes_fields() {
+        let csv = "\"a, b\",x\n\"say \"\"hi\"\"\"\n\"two\nlines\",y\n";
+        assert_eq!(first_column(csv), ["a, b", "say \"hi\"", "two lines"]);
+        // quotes inside an unquoted field are kept
+        assert_eq!(first_column("5\" disk,x\n"), ["5\" disk"]);
+    }
+}
@@ -0,0 +1,96 @@
+use std::io::{BufRead, Write};
+
+/// The `record=` and `replay=` ends of a session; either may be off.
+#[derive(Default)]
+pub struct Tape {
+    pub recorder: Option<Recorder>,
+    pub replay: Option<Replay>,
+}
+
+/// `record=<file>`: every dispense is appended to `file` as it happens, one
+/// `milliseconds\tmode\tword` row each, the milliseconds counted from the start of the
+/// session. The word goes last since it may contain tabs.
+pub struct Recorder {
+    out: std::fs::File,
+}
+
+impl Recorder {
+    pub fn create(path: &str) -> std::io::Result<Self> {
+        return Ok(Self { out: std::fs::File::create(path)? });
+    }
+
+    pub fn append(&mut self, entry: &crate::session::Entry) -> std::io::Result<()> {
+        return writeln!(self.out, "{}\t{}\t{}", entry.offset.as_millis(), entry.mode, entry.word);
+    }
+}
+
+struct Event {
+    /// since the first event of the recording
+    at: std::time::Duration,
+    mode: crate::rules::Mode,
+    word: String,
+}
+
+/// `replay=<file>`: the dispense hotkey plays a recording back, emitting the same words the
+/// same way with the same pauses in between, instead of reading the list. Pressing it again
+/// during the replay stops it.
+pub struct Replay {
+    events: Vec<Event>,
+    next: usize,
+    started: Option<std::time::Instant>,
+}
+
+impl Replay {
+    pub fn load(path: &str) -> anyhow::Result<Self> {
+        let file = std::fs::File::open(path).map_err(|e| anyhow::anyhow!("{}: {}", path, e))?;
+        let mut events = Vec::new();
+        for (number, line) in std::io::BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            let mut fields = line.splitn(3, '\t');
+            let (Some(millis), Some(mode), Some(word)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                return Err(anyhow::anyhow!("{}:{}: expected 3 columns", path, number + 1));
+            };
+            let Ok(millis) = millis.parse() else {
+                return Err(anyhow::anyhow!("{}:{}: bad time `{}`", path, number + 1, millis));
+            };
+            events.push(Event {
+                at: std::time::Duration::from_millis(millis),
+                mode: crate::rules::Mode::parse(mode).unwrap_or(crate::rules::Mode::Type),
+                word: word.to_owned(),
+            });
+        }
+        let first = events.first().map_or(std::time::Duration::ZERO, |e| e.at);
+        for event in &mut events {
+            event.at = event.at.saturating_sub(first);
+        }
+        return Ok(Self { events, next: 0, started: None });
+    }
+
+    /// Starts the replay from the top, or stops it if it is running.
+    pub fn toggle(&mut self) {
+        if self.started.take().is_none() {
+            self.next = 0;
+            self.started = Some(std::time::Instant::now());
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        return self.started.is_some();
+    }
+
+    /// The next word whose time has come, with how it was emitted.
+    pub fn due(&mut self) -> Option<(&str, crate::rules::Mode)> {
+        let started = self.started?;
+        let Some(event) = self.events.get(self.next) else {
+            self.started = None;
+            return None;
+        };
+        if started.elapsed() < event.at {
+            return None;
+        }
+        self.next += 1;
+        return Some((&event.word, event.mode));
+    }
+}
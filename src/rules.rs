@@ -0,0 +1,122 @@
+/// How a word reaches the focused app.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Mode {
+    Type,
+    Clip,
+    /// put on the clipboard and pasted with Ctrl+V, for apps where typing is slow or garbled
+    Paste,
+    /// read aloud instead, for spelling drills: the word, or spelled out letter by letter, or
+    /// with the NATO alphabet
+    Speak,
+    Spell,
+    Nato,
+}
+
+impl Mode {
+    pub fn parse(text: &str) -> Option<Self> {
+        return match text {
+            "type" => Some(Self::Type),
+            "clip" => Some(Self::Clip),
+            "paste" => Some(Self::Paste),
+            "speak" => Some(Self::Speak),
+            "spell" => Some(Self::Spell),
+            "nato" => Some(Self::Nato),
+            _ => None,
+        };
+    }
+
+    pub fn name(self) -> &'static str {
+        return match self {
+            Self::Type => "type",
+            Self::Clip => "clip",
+            Self::Paste => "paste",
+            Self::Speak => "speak",
+            Self::Spell => "spell",
+            Self::Nato => "nato",
+        };
+    }
+}
+
+/// `rule=<title>|<mode>|<template>`: while the focused window's title contains `title`
+/// (ignoring case), words are emitted with `mode` and as `template`, where `{word}` stands for
+/// the word and `\t`, `\n` for a tab and a new line. An empty mode or template keeps the
+/// default, e.g. `rule=Excel||{word}\t` or `rule=Chrome|paste|`. The first matching rule wins.
+pub struct Rule {
+    pattern: String,
+    pub mode: Option<Mode>,
+    template: Option<String>,
+}
+
+impl Rule {
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let mut fields = text.splitn(3, '|');
+        let (Some(pattern), Some(mode), Some(template)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            return Err(anyhow::anyhow!(
+                "rule: expected `<title>|<mode>|<template>`, got `{}`",
+                text
+            ));
+        };
+        let mode = match mode {
+            "" => None,
+            _ => match Mode::parse(mode) {
+                Some(m) => Some(m),
+                None => {
+                    return Err(anyhow::anyhow!(
+                        "rule: unknown mode `{}`, expected type, clip, paste, speak, spell or nato",
+                        mode
+                    ))
+                }
+            },
+        };
+        let template = match template {
+            "" => None,
+            _ => Some(template.replace("\\t", "\t").replace("\\n", "\n")),
+        };
+        return Ok(Self { pattern: pattern.to_lowercase(), mode, template });
+    }
+
+    pub fn apply(&self, word: &str) -> String {
+        return match &self.template {
+            Some(template) => template.replace("{word}", word),
+            None => word.to_owned(),
+        };
+    }
+}
+
+pub fn matching<'a>(rules: &'a [Rule], title: &str) -> Option<&'a Rule> {
+    let title = title.to_lowercase();
+    return rules.iter().find(|r| title.contains(&r.pattern));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rules() {
+        let rule = Rule::parse("Excel||{word}\\t").unwrap();
+        assert_eq!(rule.pattern, "excel");
+        assert!(rule.mode.is_none());
+        assert_eq!(rule.apply("cat"), "cat\t");
+
+        let rule = Rule::parse("Chrome|paste|").unwrap();
+        assert!(rule.mode == Some(Mode::Paste));
+        assert_eq!(rule.apply("cat"), "cat");
+    }
+
+    #[test]
+    fn rejects_bad_rules() {
+        assert!(Rule::parse("Excel|type").is_err());
+        assert!(Rule::parse("Excel|shout|").is_err());
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let rules = [Rule::parse("word|clip|").unwrap(), Rule::parse("doc|paste|").unwrap()];
+        let rule = matching(&rules, "Doc1 - Word").unwrap();
+        assert!(rule.mode == Some(Mode::Clip));
+        assert!(matching(&rules, "Notepad").is_none());
+    }
+}
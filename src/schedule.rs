@@ -0,0 +1,117 @@
+use crate::win32;
+
+/// `quiet=9:00-17:00` or `quiet=9:00-17:00/weekdays`: while inside the window the hotkeys
+/// are unregistered, so other software gets its shortcuts back.
+pub struct QuietHours {
+    /// minutes since midnight
+    from: u16,
+    to: u16,
+    weekdays_only: bool,
+}
+
+fn parse_time(text: &str) -> Option<u16> {
+    let (hours, minutes) = text.split_once(':')?;
+    let hours: u16 = hours.trim().parse().ok()?;
+    let minutes: u16 = minutes.trim().parse().ok()?;
+    if hours > 24 || minutes > 59 || (hours == 24 && minutes != 0) {
+        return None;
+    }
+    return Some(hours * 60 + minutes);
+}
+
+impl QuietHours {
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let (range, weekdays_only) = match text.split_once('/') {
+            Some((range, "weekdays")) => (range, true),
+            Some((_, days)) => {
+                return Err(anyhow::anyhow!(
+                    "quiet: unknown day set `{}`, expected `weekdays`",
+                    days
+                ));
+            }
+            None => (text, false),
+        };
+        let parsed =
+            range.split_once('-').and_then(|(from, to)| Some((parse_time(from)?, parse_time(to)?)));
+        let Some((from, to)) = parsed else {
+            return Err(anyhow::anyhow!("quiet: expected `HH:MM-HH:MM`, got `{}`", range));
+        };
+        return Ok(Self { from, to, weekdays_only });
+    }
+
+    pub fn is_quiet_now(&self) -> bool {
+        let mut time = unsafe { std::mem::zeroed() };
+        unsafe { win32::GetLocalTime(&mut time) };
+        return self.is_quiet(&time);
+    }
+
+    /// With `/weekdays` a range past midnight counts for the day it starts on: 22:00-6:00 is
+    /// quiet from Friday night into Saturday morning, but not from Sunday night.
+    fn is_quiet(&self, time: &win32::SYSTEMTIME) -> bool {
+        let now = time.wHour * 60 + time.wMinute;
+        let (inside, day) = if self.from <= self.to {
+            (now >= self.from && now < self.to, time.wDayOfWeek)
+        } else if now < self.to {
+            // the range wraps past midnight, e.g. 22:00-6:00, and started yesterday
+            (true, (time.wDayOfWeek + 6) % 7)
+        } else {
+            (now >= self.from, time.wDayOfWeek)
+        };
+        // wDayOfWeek: 0 is Sunday
+        return inside && !(self.weekdays_only && (day == 0 || day == 6));
+    }
+}
+
+/// The time left in the current minute, which quiet hours start and end with.
+pub fn until_next_minute() -> std::time::Duration {
+    let mut time: win32::SYSTEMTIME = unsafe { std::mem::zeroed() };
+    unsafe { win32::GetLocalTime(&mut time) };
+    let into = time.wSecond as u64 * 1000 + time.wMilliseconds as u64;
+    return std::time::Duration::from_millis(60_000u64.saturating_sub(into));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 0 is Sunday.
+    fn at(day: u16, hour: u16, minute: u16) -> win32::SYSTEMTIME {
+        let mut time: win32::SYSTEMTIME = unsafe { std::mem::zeroed() };
+        time.wDayOfWeek = day;
+        time.wHour = hour;
+        time.wMinute = minute;
+        return time;
+    }
+
+    #[test]
+    fn parses_ranges() {
+        assert!(QuietHours::parse("9:00-17:00").is_ok());
+        assert!(QuietHours::parse("22:00-24:00/weekdays").is_ok());
+        assert!(QuietHours::parse("9:00-25:00").is_err());
+        assert!(QuietHours::parse("9:00-17:60").is_err());
+        assert!(QuietHours::parse("9-17").is_err());
+        assert!(QuietHours::parse("9:00-17:00/weekends").is_err());
+    }
+
+    #[test]
+    fn is_quiet_inside_the_range() {
+        let quiet = QuietHours::parse("9:00-17:00").unwrap();
+        assert!(quiet.is_quiet(&at(1, 9, 0)));
+        assert!(quiet.is_quiet(&at(0, 16, 59)));
+        assert!(!quiet.is_quiet(&at(1, 17, 0)));
+        assert!(!quiet.is_quiet(&at(1, 8, 59)));
+    }
+
+    #[test]
+    fn weekday_ranges_past_midnight_count_from_their_start() {
+        let quiet = QuietHours::parse("22:00-6:00/weekdays").unwrap();
+        // Friday night into Saturday morning
+        assert!(quiet.is_quiet(&at(5, 23, 0)));
+        assert!(quiet.is_quiet(&at(6, 5, 0)));
+        // Sunday night into Monday morning
+        assert!(!quiet.is_quiet(&at(0, 23, 0)));
+        assert!(!quiet.is_quiet(&at(1, 5, 0)));
+        assert!(quiet.is_quiet(&at(1, 23, 0)));
+        assert!(!quiet.is_quiet(&at(1, 12, 0)));
+    }
+}
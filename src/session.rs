@@ -0,0 +1,189 @@
+use std::io::Write;
+
+use crate::win32;
+
+pub struct Entry {
+    pub word: String,
+    /// the path of the list it came from, the one undo steps back in
+    pub list: String,
+    pub timestamp: String,
+    pub mode: &'static str,
+    pub target: String,
+    /// since the session started
+    pub offset: std::time::Duration,
+}
+
+/// Time spent dispensing from one list: from its first to its last dispense, minus the time
+/// the user was away.
+struct ListTime {
+    active: std::time::Duration,
+    last: std::time::Instant,
+    /// `Session::paused()` at the last dispense
+    paused: std::time::Duration,
+}
+
+/// Every word dispensed since the app started, in order.
+pub struct Session {
+    entries: Vec<Entry>,
+    /// by list path
+    list_times: std::collections::HashMap<String, ListTime>,
+    /// dispense hotkey presses that produced no word
+    failures: u64,
+    started: std::time::Instant,
+    /// time of the last input before the user went idle, while they are
+    idle_since: Option<std::time::Instant>,
+    /// idle time of the finished idle periods
+    paused: std::time::Duration,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        return Self {
+            entries: Vec::new(),
+            list_times: std::collections::HashMap::new(),
+            failures: 0,
+            started: std::time::Instant::now(),
+            idle_since: None,
+            paused: std::time::Duration::ZERO,
+        };
+    }
+
+    pub fn len(&self) -> usize {
+        return self.entries.len();
+    }
+
+    /// `target` is the title of the window the word goes to.
+    pub fn record(&mut self, word: &str, list: &str, mode: &'static str, target: String) -> &Entry {
+        self.entries.push(Entry {
+            word: word.to_owned(),
+            list: list.to_owned(),
+            timestamp: local_timestamp(),
+            mode,
+            target,
+            offset: self.started.elapsed(),
+        });
+        return self.entries.last().expect("just pushed");
+    }
+
+    /// Counts the time since the last dispense from `list` as spent on it.
+    pub fn track(&mut self, list: &str) {
+        let now = std::time::Instant::now();
+        let paused = self.paused();
+        match self.list_times.get_mut(list) {
+            Some(time) => {
+                time.active += (now - time.last).saturating_sub(paused.saturating_sub(time.paused));
+                time.last = now;
+                time.paused = paused;
+            }
+            None => {
+                let time = ListTime { active: std::time::Duration::ZERO, last: now, paused };
+                self.list_times.insert(list.to_owned(), time);
+            }
+        }
+    }
+
+    /// All the idle time so far, including an idle period still going on.
+    fn paused(&self) -> std::time::Duration {
+        return self.paused + self.idle_since.map_or(std::time::Duration::ZERO, |s| s.elapsed());
+    }
+
+    /// Notices the user going idle for `after` and coming back, to leave that time out.
+    pub fn poll_idle(&mut self, after: std::time::Duration) {
+        let idle = user_idle_time();
+        let now = std::time::Instant::now();
+        // the idle period starts with the last input
+        let last_input = now.checked_sub(idle).unwrap_or(now);
+        match self.idle_since {
+            None if idle >= after => self.idle_since = Some(last_input),
+            Some(since) if idle < after => {
+                self.paused += last_input.saturating_duration_since(since);
+                self.idle_since = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// The time spent on each list this session.
+    pub fn list_times(&self) -> impl Iterator<Item = (&str, std::time::Duration)> {
+        return self.list_times.iter().map(|(list, time)| (list.as_str(), time.active));
+    }
+
+    /// Takes back the last entry, for undo.
+    pub fn undo(&mut self) -> Option<Entry> {
+        return self.entries.pop();
+    }
+
+    pub fn record_failure(&mut self) {
+        self.failures += 1;
+    }
+
+    pub fn failures(&self) -> u64 {
+        return self.failures;
+    }
+
+    pub fn uptime(&self) -> std::time::Duration {
+        return self.started.elapsed();
+    }
+
+    pub fn last(&self) -> Option<&Entry> {
+        return self.entries.last();
+    }
+
+    /// The last `count` entries, oldest first.
+    pub fn recent(&self, count: usize) -> &[Entry] {
+        return &self.entries[self.entries.len().saturating_sub(count)..];
+    }
+
+    pub fn export_csv(&self, path: &str) -> std::io::Result<()> {
+        let mut out = std::io::BufWriter::new(std::fs::File::create(path)?);
+        writeln!(out, "word,timestamp,mode,target")?;
+        for entry in &self.entries {
+            writeln!(
+                out,
+                "{},{},{},{}",
+                csv_field(&entry.word),
+                csv_field(&entry.timestamp),
+                csv_field(entry.mode),
+                csv_field(&entry.target)
+            )?;
+        }
+        return out.flush();
+    }
+}
+
+pub fn csv_field(field: &str) -> std::borrow::Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        return format!("\"{}\"", field.replace('"', "\"\"")).into();
+    }
+    return field.into();
+}
+
+pub fn local_timestamp() -> String {
+    let mut time = unsafe { std::mem::zeroed() };
+    unsafe { win32::GetLocalTime(&mut time) };
+    return format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        time.wYear, time.wMonth, time.wDay, time.wHour, time.wMinute, time.wSecond
+    );
+}
+
+/// Time since the last keyboard or mouse input anywhere on the desktop.
+fn user_idle_time() -> std::time::Duration {
+    let mut info = win32::LASTINPUTINFO {
+        cbSize: std::mem::size_of::<win32::LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    if unsafe { win32::GetLastInputInfo(&mut info) } == 0 {
+        return std::time::Duration::ZERO;
+    }
+    let ticks = unsafe { win32::GetTickCount() }.wrapping_sub(info.dwTime);
+    return std::time::Duration::from_millis(ticks as u64);
+}
+
+pub fn foreground_window_title() -> String {
+    let mut title: [u16; 256] = [0; 256];
+    let len = unsafe {
+        win32::GetWindowTextW(win32::GetForegroundWindow(), title.as_mut_ptr(), title.len() as i32)
+    };
+    return String::from_utf16_lossy(&title[..len.max(0) as usize]);
+}
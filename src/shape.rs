@@ -0,0 +1,112 @@
+use crate::win32;
+
+/// Upper ends (in chars as seen, see `grapheme::count`) of the length histogram buckets;
+/// longer lines go in a last one.
+const LENGTH_BUCKETS: [usize; 5] = [4, 8, 16, 32, 64];
+/// Width of the longest histogram bar.
+const BAR_WIDTH: usize = 20;
+/// How many of the non-ASCII chars are shown.
+const MAX_LISTED_CHARS: usize = 40;
+
+/// What kinds of chars a list is made of, counted in lines that contain them.
+#[derive(Default)]
+struct Classes {
+    lowercase: usize,
+    uppercase: usize,
+    digits: usize,
+    spaces: usize,
+    punctuation: usize,
+    non_ascii: usize,
+}
+
+/// Length histogram and char classes of the list, to see up front whether type mode will get
+/// along with a picky input field.
+fn describe(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().map(|l| crate::list::split_directive(l).1).collect();
+    if lines.is_empty() {
+        return "The list is empty".to_owned();
+    }
+
+    let lengths: Vec<usize> = lines.iter().map(|l| crate::grapheme::count(l)).collect();
+    let mut buckets = [0; LENGTH_BUCKETS.len() + 1];
+    for &len in &lengths {
+        let bucket = LENGTH_BUCKETS.iter().position(|&max| len <= max);
+        buckets[bucket.unwrap_or(LENGTH_BUCKETS.len())] += 1;
+    }
+    let mut out = format!(
+        "{} lines, {} to {} chars, {:.1} on average\n",
+        lines.len(),
+        lengths.iter().min().unwrap_or(&0),
+        lengths.iter().max().unwrap_or(&0),
+        lengths.iter().sum::<usize>() as f64 / lines.len() as f64
+    );
+    let largest = buckets.iter().copied().max().unwrap_or(1).max(1);
+    let mut min = 1;
+    for (i, &count) in buckets.iter().enumerate() {
+        let range = match LENGTH_BUCKETS.get(i) {
+            Some(max) => format!("{}-{}", min, max),
+            None => format!("{}+", min),
+        };
+        let bar = "█".repeat((count * BAR_WIDTH).div_ceil(largest));
+        out.push_str(&format!("\n{:>6}  {} {}", range, bar, count));
+        min = LENGTH_BUCKETS.get(i).map_or(min, |max| max + 1);
+    }
+
+    let mut classes = Classes::default();
+    let mut non_ascii_chars = std::collections::BTreeSet::new();
+    for line in &lines {
+        let has = |f: fn(char) -> bool| line.chars().any(f) as usize;
+        classes.lowercase += has(|c| c.is_lowercase());
+        classes.uppercase += has(|c| c.is_uppercase());
+        classes.digits += has(|c| c.is_ascii_digit());
+        classes.spaces += has(|c| c.is_whitespace());
+        classes.punctuation += has(|c| c.is_ascii_punctuation());
+        classes.non_ascii += has(|c| !c.is_ascii());
+        non_ascii_chars.extend(line.chars().filter(|c| !c.is_ascii()));
+    }
+    out.push_str("\n\nLines with");
+    for (name, count) in [
+        ("lowercase letters", classes.lowercase),
+        ("uppercase letters", classes.uppercase),
+        ("digits", classes.digits),
+        ("spaces or tabs", classes.spaces),
+        ("punctuation", classes.punctuation),
+        ("non-ASCII chars", classes.non_ascii),
+    ] {
+        out.push_str(&format!("\n    {}: {}", name, count));
+    }
+    if !non_ascii_chars.is_empty() {
+        let listed: String = non_ascii_chars.iter().take(MAX_LISTED_CHARS).collect();
+        out.push_str(&format!(
+            "\n\n{} different non-ASCII chars: {}",
+            non_ascii_chars.len(),
+            listed
+        ));
+        if non_ascii_chars.len() > MAX_LISTED_CHARS {
+            out.push_str(" ...");
+        }
+    }
+    let untypeable = crate::check::untypeable_lines(text.as_bytes(), crate::check::active_layout());
+    out.push_str(&format!("\n\n{} lines can't be typed on the current layout", untypeable.len()));
+    return out;
+}
+
+/// `randword shape`: a profile of the word list instead of starting a session.
+pub fn run(path: &str) {
+    let text = match std::fs::read_to_string(path) {
+        Ok(t) => t,
+        Err(e) => {
+            crate::message_box(
+                &format!("{}: {}", path, e),
+                "File Open Error",
+                win32::MB_OK | win32::MB_ICONEXCLAMATION,
+            );
+            return;
+        }
+    };
+    crate::message_box(
+        &describe(&text),
+        &format!("Shape of {}", path),
+        win32::MB_OK | win32::MB_ICONINFORMATION,
+    );
+}
@@ -0,0 +1,117 @@
+use std::io::{Read, Seek, Write};
+
+/// A lock older than this is assumed to be left behind by a crashed instance.
+const STALE_LOCK: std::time::Duration = std::time::Duration::from_secs(30);
+const LOCK_RETRY: std::time::Duration = std::time::Duration::from_millis(50);
+const LOCK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// `statedir=<dir>`: skipline.dat lives in `dir`, typically on a network share, and is read
+/// and written on every move under a lock file so several people working through one list
+/// never get the same line.
+///
+/// The lock is a `skipline.lock` file created with `create_new`, which is atomic on SMB shares
+/// too, unlike byte-range locks on some NAS boxes.
+#[derive(Clone)]
+pub struct SharedCursor {
+    cursor_path: std::path::PathBuf,
+    lock_path: std::path::PathBuf,
+}
+
+impl SharedCursor {
+    pub fn new(dir: &str) -> Self {
+        let dir = std::path::Path::new(dir);
+        return Self {
+            cursor_path: dir.join("skipline.dat"),
+            lock_path: dir.join("skipline.lock"),
+        };
+    }
+
+    /// The cursor of the `bind=` list `list` in `dir`, next to and apart from the main one.
+    pub fn for_list(dir: &str, list: &str) -> Self {
+        let path = std::path::Path::new(list);
+        let name = path.file_name().map_or(list.into(), |n| n.to_string_lossy());
+        let dir = std::path::Path::new(dir);
+        return Self {
+            cursor_path: dir.join(format!("{}.skipline.dat", name)),
+            lock_path: dir.join(format!("{}.skipline.lock", name)),
+        };
+    }
+
+    /// Where the cursor is stored.
+    pub fn path(&self) -> &std::path::Path {
+        return &self.cursor_path;
+    }
+
+    /// Runs `f` on the shared cursor while holding the lock and stores what it leaves behind.
+    pub fn with_lock<T>(&self, f: impl FnOnce(&mut u64) -> T) -> std::io::Result<T> {
+        return with_lock_file(&self.lock_path, || self.update(f))?;
+    }
+
+    fn update<T>(&self, f: impl FnOnce(&mut u64) -> T) -> std::io::Result<T> {
+        let mut file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.cursor_path)?;
+        let mut buffer = Vec::with_capacity(crate::SKILINE_NUMBER_SIZE);
+        file.read_to_end(&mut buffer)?;
+        let stored = crate::parse_lines_to_skip(&buffer);
+
+        // a conflicted copy means two people moved at once while the share was offline;
+        // the one further down the list wins so nobody gets a line twice
+        let copies = crate::conflict::conflicted_copies(&self.cursor_path.to_string_lossy());
+        let mut cursor = stored;
+        for copy in &copies {
+            if let Ok(buffer) = std::fs::read(copy) {
+                cursor = cursor.max(crate::parse_lines_to_skip(&buffer));
+            }
+        }
+
+        let result = f(&mut cursor);
+        if cursor != stored {
+            file.set_len(0)?;
+            file.rewind()?;
+            file.write_all(&crate::u64_to_array::<{ crate::SKILINE_NUMBER_SIZE }>(cursor))?;
+            file.sync_all()?;
+        }
+        crate::conflict::remove(&copies);
+        return Ok(result);
+    }
+}
+
+/// Runs `f` while holding the lock file `lock_path`, taken like the one of the cursor.
+pub fn with_lock_file<T>(lock_path: &std::path::Path, f: impl FnOnce() -> T) -> std::io::Result<T> {
+    lock(lock_path)?;
+    let result = f();
+    let _ = std::fs::remove_file(lock_path);
+    return Ok(result);
+}
+
+fn lock(lock_path: &std::path::Path) -> std::io::Result<()> {
+    let start = std::time::Instant::now();
+    loop {
+        match std::fs::OpenOptions::new().write(true).create_new(true).open(lock_path) {
+            Ok(_) => return Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e),
+        }
+
+        let stale = std::fs::metadata(lock_path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.elapsed().ok())
+            .is_some_and(|age| age > STALE_LOCK);
+        if stale {
+            let _ = std::fs::remove_file(lock_path);
+            continue;
+        }
+        if start.elapsed() > LOCK_TIMEOUT {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("{} is held by another instance", lock_path.display()),
+            ));
+        }
+        std::thread::sleep(LOCK_RETRY);
+    }
+}
@@ -0,0 +1,164 @@
+use crate::win32;
+
+const MENU_KEY: &str = "Software\\Classes\\SystemFileAssociations\\.txt\\shell\\randword";
+const MENU_TEXT: &str = "Use with randword";
+const BUNDLE_EXTENSION_KEY: &str = "Software\\Classes\\.rword";
+const BUNDLE_TYPE_KEY: &str = "Software\\Classes\\randword.bundle";
+/// `dwData` of the `WM_COPYDATA` message that hands a list to the running instance.
+const COPYDATA_LIST: usize = 0x5257;
+
+fn wide(text: &str) -> Vec<u16> {
+    return text.encode_utf16().chain(std::iter::once(0)).collect();
+}
+
+fn set_default_value(key: &str, value: &str) -> anyhow::Result<()> {
+    let mut h_key = 0;
+    let error_code = unsafe {
+        win32::RegCreateKeyExW(
+            win32::HKEY_CURRENT_USER,
+            wide(key).as_ptr(),
+            0,
+            std::ptr::null(),
+            win32::REG_OPTION_NON_VOLATILE,
+            win32::KEY_WRITE,
+            std::ptr::null(),
+            &mut h_key,
+            std::ptr::null_mut(),
+        )
+    };
+    if error_code != 0 {
+        return Err(anyhow::anyhow!("{}: {}", key, crate::format_win32_error(error_code)));
+    }
+    let value = wide(value);
+    let error_code = unsafe {
+        win32::RegSetValueExW(
+            h_key,
+            std::ptr::null(),
+            0,
+            win32::REG_SZ,
+            value.as_ptr() as _,
+            (value.len() * 2) as u32,
+        )
+    };
+    unsafe { win32::RegCloseKey(h_key) };
+    if error_code != 0 {
+        return Err(anyhow::anyhow!("{}: {}", key, crate::format_win32_error(error_code)));
+    }
+    return Ok(());
+}
+
+/// Adds "Use with randword" to the Explorer context menu of .txt files, which starts randword
+/// with `list=<file>`, and opens .rword bundles with randword. Only for the current user, so
+/// no elevation is needed.
+fn install() -> anyhow::Result<()> {
+    let exe = std::env::current_exe()?;
+    set_default_value(MENU_KEY, MENU_TEXT)?;
+    set_default_value(
+        &format!("{}\\command", MENU_KEY),
+        &format!("\"{}\" list=\"%1\"", exe.display()),
+    )?;
+
+    set_default_value(BUNDLE_EXTENSION_KEY, "randword.bundle")?;
+    set_default_value(BUNDLE_TYPE_KEY, "randword drill")?;
+    set_default_value(
+        &format!("{}\\shell\\open\\command", BUNDLE_TYPE_KEY),
+        &format!("\"{}\" \"%1\"", exe.display()),
+    )?;
+    return Ok(());
+}
+
+fn uninstall() -> anyhow::Result<()> {
+    for key in [MENU_KEY, BUNDLE_EXTENSION_KEY, BUNDLE_TYPE_KEY] {
+        let error_code =
+            unsafe { win32::RegDeleteTreeW(win32::HKEY_CURRENT_USER, wide(key).as_ptr()) };
+        if error_code != 0 && error_code != win32::ERROR_FILE_NOT_FOUND {
+            return Err(anyhow::anyhow!("{}: {}", key, crate::format_win32_error(error_code)));
+        }
+    }
+    return Ok(());
+}
+
+/// `randword install` / `randword uninstall`, instead of starting a session.
+pub fn run(install_menu: bool) {
+    let result = if install_menu { install() } else { uninstall() };
+    match result {
+        Ok(()) => crate::message_box(
+            if install_menu {
+                "Added \"Use with randword\" to the context menu of .txt files and associated .rword bundles."
+            } else {
+                "Removed the context menu entry and the .rword association."
+            },
+            "randword",
+            win32::MB_OK | win32::MB_ICONINFORMATION,
+        ),
+        Err(e) => crate::message_box(
+            &e.to_string(),
+            "Registry Error",
+            win32::MB_OK | win32::MB_ICONEXCLAMATION,
+        ),
+    }
+}
+
+/// The window of a randword already running, or 0. A `headless` one is message-only, which
+/// `FindWindowW` doesn't look at.
+pub fn find_running() -> win32::HWND {
+    let class = crate::WINDOW_CLASS_NAME.as_ptr();
+    let h_window = unsafe { win32::FindWindowW(class, std::ptr::null()) };
+    if h_window != 0 {
+        return h_window;
+    }
+    return unsafe { win32::FindWindowExW(win32::HWND_MESSAGE, 0, class, std::ptr::null()) };
+}
+
+/// Hands `list` to an already running randword, so the context menu doesn't start a second
+/// instance fighting over the hotkeys. Returns false if there is none.
+pub fn send_to_running(list: &str) -> bool {
+    let h_window = find_running();
+    if h_window == 0 {
+        return false;
+    }
+    let path: Vec<u16> = list.encode_utf16().collect();
+    let data = win32::COPYDATASTRUCT {
+        dwData: COPYDATA_LIST,
+        cbData: (path.len() * 2) as u32,
+        lpData: path.as_ptr() as _,
+    };
+    let handled = unsafe {
+        win32::SendMessageW(h_window, win32::WM_COPYDATA, 0, &data as *const _ as win32::LPARAM)
+    };
+    return handled != 0;
+}
+
+/// `randword quit`: closes the randword already running, like its window's close button or
+/// the tray's Quit, which a `headless` one has neither of.
+pub fn quit_running() {
+    let h_window = find_running();
+    if h_window == 0 {
+        crate::message_box(
+            "No randword is running.",
+            "randword",
+            win32::MB_OK | win32::MB_ICONINFORMATION,
+        );
+        return;
+    }
+    unsafe { win32::PostMessageW(h_window, win32::WM_CLOSE, 0, 0) };
+}
+
+/// Handles `WM_COPYDATA` from `send_to_running`, returning true if it was a list.
+pub fn receive(h_window: win32::HWND, lparam: win32::LPARAM) -> bool {
+    let data = unsafe { &*(lparam as *const win32::COPYDATASTRUCT) };
+    if data.dwData != COPYDATA_LIST {
+        return false;
+    }
+    let path =
+        unsafe { std::slice::from_raw_parts(data.lpData as *const u16, data.cbData as usize / 2) };
+    queue_list(h_window, String::from_utf16_lossy(path));
+    return true;
+}
+
+/// Has the main loop switch to `path`, as if it was opened from the Explorer menu.
+pub fn queue_list(h_window: win32::HWND, path: String) {
+    if let Some(app) = crate::app::get(h_window) {
+        *app.pending_list.borrow_mut() = Some(path);
+    }
+}
@@ -0,0 +1,53 @@
+use crate::win32;
+
+/// How long a lock key's light stays on for `signal=`.
+const FLASH_MS: u32 = 150;
+
+#[derive(Clone, Copy)]
+struct Signal {
+    vkey: u16,
+    /// lock keys are pressed twice, so their light blinks and the state stays as it was
+    flash: bool,
+}
+
+/// Set from `signal=`, the last one given winning; words are put on the clipboard where no
+/// options are at hand.
+static SIGNAL: std::sync::Mutex<Option<Signal>> = std::sync::Mutex::new(None);
+
+/// `signal=<key>` or `signal=<key>,toggle`: in clip mode a key press tells the hands that a new
+/// word is ready, without looking at the screen. Caps, Num and Scroll Lock blink their light
+/// by default; with `toggle` they are pressed once and stay switched. Any other key of `{...}`
+/// tokens, e.g. `signal=f24` for a macro pad, is pressed once.
+pub fn configure(text: &str) -> anyhow::Result<()> {
+    let (name, toggle) = match text.split_once(',') {
+        Some((name, "toggle")) => (name, true),
+        Some(_) => {
+            return Err(anyhow::anyhow!("signal: expected `<key>[,toggle]`, got `{}`", text))
+        }
+        None => (text, false),
+    };
+    let Some(vkey) = crate::tokens::key(name) else {
+        return Err(anyhow::anyhow!("signal: unknown key `{}`", name));
+    };
+    let is_lock = [win32::VK_CAPITAL, win32::VK_NUMLOCK, win32::VK_SCROLL].contains(&vkey);
+    *SIGNAL.lock().unwrap_or_else(|e| e.into_inner()) =
+        Some(Signal { vkey, flash: is_lock && !toggle });
+    return Ok(());
+}
+
+/// Forgets `signal=`, before the options are read again for another profile.
+pub fn reset() {
+    *SIGNAL.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Presses the `signal=` key after a word went on the clipboard, if one is set.
+pub fn send() {
+    let Some(signal) = *SIGNAL.lock().unwrap_or_else(|e| e.into_inner()) else {
+        return;
+    };
+    crate::press_key(signal.vkey);
+    if signal.flash {
+        unsafe { win32::Sleep(FLASH_MS) };
+        crate::press_key(signal.vkey);
+    }
+}
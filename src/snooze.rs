@@ -0,0 +1,53 @@
+use crate::win32;
+
+/// The id of the item in the window and tray menus; the system's own are 0xF000 and up, and
+/// it uses the low four bits of `WM_SYSCOMMAND`.
+pub const MENU_ID: usize = 0x0100;
+const MINUTES: u64 = 15;
+
+/// Adds "Snooze 15 min" to the menu of the window's caption: the hotkeys are unregistered
+/// until then, for meetings where even a registered hotkey gets in the way of other software.
+pub fn add_menu_item(h_window: win32::HWND) {
+    unsafe {
+        let menu = win32::GetSystemMenu(h_window, 0);
+        win32::AppendMenuW(menu, win32::MF_SEPARATOR, 0, std::ptr::null());
+        win32::AppendMenuW(menu, win32::MF_STRING, MENU_ID, win32::w!("Snooze 15 min"));
+    }
+}
+
+fn check(h_window: win32::HWND, snoozed: bool) {
+    let state = if snoozed { win32::MF_CHECKED } else { win32::MF_UNCHECKED };
+    let menu = unsafe { win32::GetSystemMenu(h_window, 0) };
+    unsafe { win32::CheckMenuItem(menu, MENU_ID as u32, win32::MF_BYCOMMAND | state) };
+}
+
+/// Starts a snooze, or ends the one going on early.
+pub fn toggle(h_window: win32::HWND) {
+    let Some(app) = crate::app::get(h_window) else {
+        return;
+    };
+    let until = match app.snoozed_until.get() {
+        Some(_) => None,
+        None => Some(std::time::Instant::now() + std::time::Duration::from_secs(MINUTES * 60)),
+    };
+    app.snoozed_until.set(until);
+    check(h_window, until.is_some());
+    let status = match until {
+        Some(_) => format!("Hotkeys snoozed for {} min", MINUTES),
+        None => "Hotkeys are back".to_owned(),
+    };
+    crate::paint::show_status(h_window, &status);
+}
+
+/// Whether the hotkeys are snoozed; a snooze that ran out is ended here.
+pub fn is_on(h_window: win32::HWND) -> bool {
+    let Some(app) = crate::app::get(h_window) else {
+        return false;
+    };
+    if app.snoozed_until.get().is_some_and(|u| std::time::Instant::now() >= u) {
+        app.snoozed_until.set(None);
+        check(h_window, false);
+        crate::paint::show_status(h_window, "Hotkeys are back");
+    }
+    return app.snoozed_until.get().is_some();
+}
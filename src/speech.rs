@@ -0,0 +1,53 @@
+use std::io::Write;
+
+use crate::rules::Mode;
+
+/// The letters A to Z as the NATO spelling alphabet reads them.
+const NATO: [&str; 26] = [
+    "Alfa", "Bravo", "Charlie", "Delta", "Echo", "Foxtrot", "Golf", "Hotel", "India", "Juliett",
+    "Kilo", "Lima", "Mike", "November", "Oscar", "Papa", "Quebec", "Romeo", "Sierra", "Tango",
+    "Uniform", "Victor", "Whiskey", "X-ray", "Yankee", "Zulu",
+];
+
+/// Reads the text from stdin as UTF-8, since the console code page can't hold every word.
+const SCRIPT: &str = "[Console]::InputEncoding = [Text.Encoding]::UTF8; \
+    Add-Type -AssemblyName System.Speech; \
+    (New-Object System.Speech.Synthesis.SpeechSynthesizer).Speak([Console]::In.ReadToEnd())";
+
+/// What the voice reads for `text`: the text itself for `speak`; for `spell` one letter at a
+/// time with its accents, saying which letters are capitals; for `nato` with the NATO alphabet
+/// for A to Z.
+fn script(text: &str, mode: Mode) -> String {
+    if mode == Mode::Speak {
+        return text.to_owned();
+    }
+    let names: Vec<String> = crate::grapheme::clusters(text)
+        .into_iter()
+        .map(|letter| match letter.chars().next() {
+            Some(' ') => "space".to_owned(),
+            Some(c) if mode == Mode::Nato && c.is_ascii_alphabetic() && letter.len() == 1 => {
+                NATO[(c.to_ascii_lowercase() as u8 - b'a') as usize].to_owned()
+            }
+            Some(c) if c.is_uppercase() => format!("capital {}", letter),
+            _ => letter.to_owned(),
+        })
+        .collect();
+    // the pauses keep the voice from reading the letters as a word
+    return names.join(", ");
+}
+
+/// Reads `text` aloud with the Windows voice in the background, as `mode` says. PowerShell
+/// takes a moment to start, the word is not waited for.
+pub fn say(text: &str, mode: Mode) -> std::io::Result<()> {
+    use std::os::windows::process::CommandExt;
+
+    let mut child = std::process::Command::new("powershell.exe")
+        .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+        .stdin(std::process::Stdio::piped())
+        .creation_flags(crate::CREATE_NO_WINDOW)
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(script(text, mode).as_bytes())?;
+    }
+    return Ok(());
+}
@@ -0,0 +1,385 @@
+use std::io::{BufRead, Write};
+
+/// How many words the `randword stats` view lists.
+const TOP_COUNT: usize = 15;
+
+#[derive(Default)]
+pub struct WordStats {
+    pub times: u32,
+    /// local time of the last dispense, empty if never
+    pub last: String,
+    /// set with Ctrl+Alt+Plus / Ctrl+Alt+Minus right after the word was dispensed
+    pub rating: i32,
+    /// listed in banned.txt: never dispensed
+    pub banned: bool,
+    /// listed in favorites.txt
+    pub favorite: bool,
+}
+
+impl WordStats {
+    fn flags(&self) -> &'static str {
+        return match (self.banned, self.favorite) {
+            (true, true) => "bf",
+            (true, false) => "b",
+            (false, true) => "f",
+            (false, false) => "-",
+        };
+    }
+
+    /// Folds in the same word's row from another copy of the table: counters take the higher
+    /// value, the rating comes from whichever copy saw the word last and flags are combined.
+    fn merge(&mut self, other: WordStats) {
+        self.times = self.times.max(other.times);
+        if other.last > self.last {
+            self.last = other.last;
+            self.rating = other.rating;
+        }
+        self.banned |= other.banned;
+        self.favorite |= other.favorite;
+    }
+}
+
+type Table = std::collections::HashMap<String, WordStats>;
+
+/// Reads one table file; malformed rows are skipped.
+fn read_table(path: &std::path::Path) -> std::io::Result<Table> {
+    let mut words = Table::new();
+    let file = std::fs::File::open(path)?;
+    for line in std::io::BufReader::new(file).lines() {
+        let line = line?;
+        let mut fields = line.splitn(5, '\t');
+        let (Some(times), Some(last), Some(rating), Some(flags), Some(word)) =
+            (fields.next(), fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let (Ok(times), Ok(rating)) = (times.parse(), rating.parse()) else {
+            continue;
+        };
+        let entry = WordStats {
+            times,
+            last: last.to_owned(),
+            rating,
+            banned: flags.contains('b'),
+            favorite: flags.contains('f'),
+        };
+        words.insert(word.to_owned(), entry);
+    }
+    return Ok(words);
+}
+
+/// Per-word counters kept across sessions in a tab separated sidecar file,
+/// one `times\tlast\trating\tflags\tword` row per word. The word goes last since it may
+/// contain tabs.
+pub struct Stats {
+    path: String,
+    words: Table,
+    dirty: bool,
+    /// write time of the file when it was read, see `save`
+    loaded: Option<std::time::SystemTime>,
+    merged_copies: Vec<std::path::PathBuf>,
+    /// every change is also written here right away, see `attach`
+    journal: Option<crate::journal::Journal>,
+}
+
+impl Stats {
+    /// A missing file is an empty table. Conflicted copies left by a sync client are merged
+    /// in, and deleted once the merged table is saved.
+    pub fn load(path: &str) -> std::io::Result<Self> {
+        let words = match read_table(std::path::Path::new(path)) {
+            Ok(w) => w,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Table::new(),
+            Err(e) => return Err(e),
+        };
+        let mut stats = Self {
+            path: path.to_owned(),
+            words,
+            dirty: false,
+            loaded: crate::conflict::modified(path),
+            merged_copies: Vec::new(),
+            journal: None,
+        };
+        for copy in crate::conflict::conflicted_copies(path) {
+            // a copy that cannot be read is left alone rather than deleted
+            let Ok(words) = read_table(&copy) else {
+                continue;
+            };
+            stats.merge(words);
+            stats.merged_copies.push(copy);
+        }
+        if !stats.merged_copies.is_empty() {
+            stats.dirty = true;
+        }
+        return Ok(stats);
+    }
+
+    fn merge(&mut self, words: Table) {
+        for (word, other) in words {
+            match self.words.get_mut(&word) {
+                Some(entry) => entry.merge(other),
+                None => {
+                    self.words.insert(word, other);
+                }
+            }
+        }
+    }
+
+    /// Journals every change from now on, until the next `checkpoint`.
+    pub fn attach(&mut self, journal: crate::journal::Journal) {
+        self.journal = Some(journal);
+    }
+
+    /// Journals where `list` is now; the position is kept outside the table.
+    pub fn note_position(&mut self, list: &crate::list::WordList) {
+        if let Some(journal) = &mut self.journal {
+            journal.position(list);
+        }
+    }
+
+    /// Empties the journal once the table and the positions are saved.
+    pub fn checkpoint(&mut self) -> std::io::Result<()> {
+        return match &mut self.journal {
+            Some(journal) => journal.checkpoint(),
+            None => Ok(()),
+        };
+    }
+
+    pub fn record(&mut self, word: &str, timestamp: &str) {
+        if let Some(journal) = &mut self.journal {
+            journal.record(word, timestamp);
+        }
+        let entry = self.words.entry(word.to_owned()).or_default();
+        entry.times += 1;
+        entry.last = timestamp.to_owned();
+        self.dirty = true;
+    }
+
+    /// How many different words were last dispensed on `date`, a `YYYY-MM-DD`.
+    pub fn drilled_on(&self, date: &str) -> usize {
+        return self.words.values().filter(|s| s.last.starts_with(date)).count();
+    }
+
+    /// Takes back a `record`; the last dispense time stays.
+    pub fn unrecord(&mut self, word: &str) {
+        if let Some(journal) = &mut self.journal {
+            journal.unrecord(word);
+        }
+        if let Some(entry) = self.words.get_mut(word) {
+            entry.times = entry.times.saturating_sub(1);
+            self.dirty = true;
+        }
+    }
+
+    pub fn rate(&mut self, word: &str, delta: i32) -> i32 {
+        if let Some(journal) = &mut self.journal {
+            journal.rate(word, delta);
+        }
+        let entry = self.words.entry(word.to_owned()).or_default();
+        entry.rating += delta;
+        self.dirty = true;
+        return entry.rating;
+    }
+
+    /// How often `word` was dispensed, over all sessions.
+    pub fn times(&self, word: &str) -> u32 {
+        return self.words.get(word).map_or(0, |s| s.times);
+    }
+
+    pub fn is_banned(&self, word: &str) -> bool {
+        return self.words.get(word).is_some_and(|s| s.banned);
+    }
+
+    /// Makes the banned (or favorite) flags match the words listed one per line in `path`.
+    /// The text file is the source of truth, so words removed from it lose the flag again.
+    /// Without the file the flags are left as they are.
+    pub fn sync_flags(&mut self, path: &str, banned: bool) -> std::io::Result<()> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(t) => t,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        let text = merge_list_copies(path, text)?;
+        if text.is_empty() && !std::path::Path::new(path).exists() {
+            return Ok(());
+        }
+        let listed: std::collections::HashSet<&str> =
+            text.lines().map(str::trim_end).filter(|l| !l.is_empty()).collect();
+
+        for (word, stats) in self.words.iter_mut() {
+            let flag = if banned { &mut stats.banned } else { &mut stats.favorite };
+            let should = listed.contains(word.as_str());
+            if *flag != should {
+                *flag = should;
+                self.dirty = true;
+            }
+        }
+        for word in &listed {
+            if !self.words.contains_key(*word) {
+                let entry = self.words.entry((*word).to_owned()).or_default();
+                if banned {
+                    entry.banned = true;
+                } else {
+                    entry.favorite = true;
+                }
+                self.dirty = true;
+            }
+        }
+        return Ok(());
+    }
+
+    /// Writes to a temporary file first so a crash mid-write keeps the old table.
+    /// If the file changed since it was read, e.g. a sync client brought in another
+    /// machine's session, that version is merged in instead of being overwritten.
+    pub fn save(&mut self) -> std::io::Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        if crate::conflict::modified(&self.path) != self.loaded {
+            match read_table(std::path::Path::new(&self.path)) {
+                Ok(words) => self.merge(words),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+        }
+        let tmp_path = format!("{}.tmp", self.path);
+        {
+            let mut out = std::io::BufWriter::new(std::fs::File::create(&tmp_path)?);
+            for (word, stats) in &self.words {
+                writeln!(
+                    out,
+                    "{}\t{}\t{}\t{}\t{}",
+                    stats.times,
+                    stats.last,
+                    stats.rating,
+                    stats.flags(),
+                    word
+                )?;
+            }
+            out.flush()?;
+        }
+        std::fs::rename(&tmp_path, &self.path)?;
+        crate::conflict::remove(&self.merged_copies);
+        self.merged_copies.clear();
+        self.loaded = crate::conflict::modified(&self.path);
+        self.dirty = false;
+        return Ok(());
+    }
+
+    /// Words ordered by how often they were dispensed, most drilled first.
+    pub fn most_drilled(&self) -> Vec<(&str, &WordStats)> {
+        let mut words: Vec<_> = self.words.iter().map(|(w, s)| (w.as_str(), s)).collect();
+        words.sort_by(|a, b| b.1.times.cmp(&a.1.times).then_with(|| a.0.cmp(b.0)));
+        return words;
+    }
+}
+
+/// The seconds spent per list in `path`, one `seconds\tlist` row each; malformed rows are
+/// skipped and a missing file is empty.
+fn read_list_times(path: &str) -> std::io::Result<Vec<(u64, String)>> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(t) => t,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let times = text
+        .lines()
+        .filter_map(|line| {
+            let (seconds, list) = line.split_once('\t')?;
+            Some((seconds.parse().ok()?, list.to_owned()))
+        })
+        .collect();
+    return Ok(times);
+}
+
+/// Adds the time of this session to the running total of each list in `path`.
+pub fn add_list_times<'a>(
+    path: &str,
+    session: impl Iterator<Item = (&'a str, std::time::Duration)>,
+) -> std::io::Result<()> {
+    let mut times = read_list_times(path)?;
+    let mut changed = false;
+    for (list, time) in session.filter(|(_, time)| time.as_secs() > 0) {
+        match times.iter_mut().find(|(_, l)| l == list) {
+            Some((seconds, _)) => *seconds += time.as_secs(),
+            None => times.push((time.as_secs(), list.to_owned())),
+        }
+        changed = true;
+    }
+    if !changed {
+        return Ok(());
+    }
+    let mut out = std::io::BufWriter::new(std::fs::File::create(path)?);
+    for (seconds, list) in &times {
+        writeln!(out, "{}\t{}", seconds, list)?;
+    }
+    return out.flush();
+}
+
+/// Adds the words of conflicted copies of a banned/favorites list to `text`, writes the
+/// union back to `path` and deletes the copies, so a word added on either machine stays.
+fn merge_list_copies(path: &str, mut text: String) -> std::io::Result<String> {
+    let copies = crate::conflict::conflicted_copies(path);
+    if copies.is_empty() {
+        return Ok(text);
+    }
+    let mut listed: std::collections::HashSet<String> =
+        text.lines().map(|l| l.trim_end().to_owned()).collect();
+    for copy in &copies {
+        for line in std::fs::read_to_string(copy)?.lines() {
+            let word = line.trim_end();
+            if !word.is_empty() && listed.insert(word.to_owned()) {
+                if !text.is_empty() && !text.ends_with('\n') {
+                    text.push('\n');
+                }
+                text.push_str(word);
+                text.push('\n');
+            }
+        }
+    }
+    std::fs::write(path, &text)?;
+    crate::conflict::remove(&copies);
+    return Ok(text);
+}
+
+/// `randword stats`: the most drilled words and the time spent per list, instead of starting
+/// a session.
+pub fn run(path: &str, list_times_path: &str) {
+    let stats = match Stats::load(path) {
+        Ok(s) => s,
+        Err(e) => {
+            crate::message_box(
+                &format!("{}: {}", path, e),
+                "File Read Error",
+                crate::win32::MB_OK | crate::win32::MB_ICONEXCLAMATION,
+            );
+            return;
+        }
+    };
+
+    let words = stats.most_drilled();
+    let mut out = format!("{} different words dispensed\n", words.len());
+    for (word, s) in words.iter().take(TOP_COUNT) {
+        let star = if s.favorite { "★ " } else { "" };
+        out.push_str(&format!("\n{:>5}x  {}{}", s.times, star, word));
+        if s.rating != 0 {
+            out.push_str(&format!("  ({:+})", s.rating));
+        }
+        out.push_str(&format!("  last {}", s.last));
+    }
+    match read_list_times(list_times_path) {
+        Ok(times) if !times.is_empty() => {
+            out.push_str("\n\nTime spent per list");
+            for (seconds, list) in times {
+                let (hours, minutes) = (seconds / 3600, seconds / 60 % 60);
+                out.push_str(&format!("\n{:>4}h {:02}m  {}", hours, minutes, list));
+            }
+        }
+        Ok(_) => {}
+        Err(e) => out.push_str(&format!("\n\n{}: {}", list_times_path, e)),
+    }
+    crate::message_box(
+        &out,
+        "Most Drilled Words",
+        crate::win32::MB_OK | crate::win32::MB_ICONINFORMATION,
+    );
+}
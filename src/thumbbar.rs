@@ -0,0 +1,234 @@
+use crate::win32;
+
+/// Ids of the buttons, as they come back in `WM_COMMAND`.
+const NEXT: usize = 1;
+const PREVIOUS: usize = 2;
+const PAUSE: usize = 3;
+
+/// `ITaskbarList3` and the class implementing it; windows-sys has neither.
+const CLSID_TASKBAR_LIST: win32::GUID =
+    win32::GUID::from_u128(0x56fdf344_fd6d_11d0_958a_006097c9a090);
+const IID_TASKBAR_LIST3: win32::GUID =
+    win32::GUID::from_u128(0xea1afb91_9e28_4b86_90e9_9e9f8a5eefaf);
+
+/// The methods of `ITaskbarList3` up to `ThumbBarAddButtons`, in vtable order; the ones not
+/// called are only counted.
+#[repr(C)]
+struct TaskbarListVtbl {
+    _query_interface: usize,
+    _add_ref: usize,
+    release: unsafe extern "system" fn(*mut TaskbarList) -> u32,
+    hr_init: unsafe extern "system" fn(*mut TaskbarList) -> win32::HRESULT,
+    // AddTab to SetActiveAlt, MarkFullscreenWindow, then SetProgressValue to SetTabActive
+    _other: [usize; 11],
+    thumb_bar_add_buttons: unsafe extern "system" fn(
+        *mut TaskbarList,
+        win32::HWND,
+        u32,
+        *const win32::THUMBBUTTON,
+    ) -> win32::HRESULT,
+}
+
+#[repr(C)]
+struct TaskbarList {
+    vtbl: *const TaskbarListVtbl,
+}
+
+/// The message the taskbar sends once the window's button is there, and again after Explorer
+/// restarts; buttons added before it are lost.
+pub fn created_message() -> u32 {
+    static MESSAGE: std::sync::OnceLock<u32> = std::sync::OnceLock::new();
+    return *MESSAGE.get_or_init(|| unsafe {
+        win32::RegisterWindowMessageW(win32::w!("TaskbarButtonCreated"))
+    });
+}
+
+/// A small icon of `glyph`, white on the taskbar, for a button.
+fn glyph_icon(glyph: &str) -> win32::HICON {
+    let size = unsafe { win32::GetSystemMetrics(win32::SM_CXSMICON) };
+    let text: Vec<u16> = glyph.encode_utf16().collect();
+    let format = win32::DT_SINGLELINE | win32::DT_VCENTER | win32::DT_CENTER | win32::DT_NOPREFIX;
+    let mut rect = win32::RECT { left: 0, top: 0, right: size, bottom: size };
+    unsafe {
+        let screen = win32::GetDC(0);
+        let dc = win32::CreateCompatibleDC(screen);
+        let color = win32::CreateCompatibleBitmap(screen, size, size);
+        let mask = win32::CreateBitmap(size, size, 1, 1, std::ptr::null());
+        win32::ReleaseDC(0, screen);
+        win32::SelectObject(dc, win32::GetStockObject(win32::DEFAULT_GUI_FONT));
+        win32::SetBkMode(dc, win32::TRANSPARENT as i32);
+        // the mask is white where the taskbar shows through and black under the glyph
+        let old = win32::SelectObject(dc, mask);
+        win32::FillRect(dc, &rect, win32::GetStockObject(win32::WHITE_BRUSH));
+        win32::SetTextColor(dc, 0);
+        win32::DrawTextW(dc, text.as_ptr(), text.len() as i32, &mut rect, format);
+        win32::SelectObject(dc, color);
+        win32::FillRect(dc, &rect, win32::GetStockObject(win32::BLACK_BRUSH));
+        win32::SetTextColor(dc, 0xFFFFFF);
+        win32::DrawTextW(dc, text.as_ptr(), text.len() as i32, &mut rect, format);
+        win32::SelectObject(dc, old);
+        win32::DeleteDC(dc);
+        let info =
+            win32::ICONINFO { fIcon: 1, xHotspot: 0, yHotspot: 0, hbmMask: mask, hbmColor: color };
+        let icon = win32::CreateIconIndirect(&info);
+        win32::DeleteObject(mask);
+        win32::DeleteObject(color);
+        return icon;
+    }
+}
+
+fn button(id: usize, glyph: &str, tip: &str) -> win32::THUMBBUTTON {
+    let mut button: win32::THUMBBUTTON = unsafe { std::mem::zeroed() };
+    button.dwMask = win32::THB_ICON | win32::THB_TOOLTIP | win32::THB_FLAGS;
+    button.iId = id as u32;
+    button.hIcon = glyph_icon(glyph);
+    button.dwFlags = win32::THBF_ENABLED;
+    // the last char stays 0
+    for (to, from) in button.szTip.iter_mut().zip(tip.encode_utf16().take(259)) {
+        *to = from;
+    }
+    return button;
+}
+
+/// Adds Next, Previous and Pause to the window's preview on the taskbar: words can be had with
+/// the mouse alone, where every hotkey is taken by other software. Without them, because COM
+/// or the taskbar failed, the hotkeys work as before.
+pub fn add_buttons(h_window: win32::HWND) {
+    let buttons = [
+        button(NEXT, ">", "Next word"),
+        button(PREVIOUS, "<", "Previous word"),
+        button(PAUSE, "II", "Snooze 15 min, or end the snooze"),
+    ];
+    unsafe {
+        // fails harmlessly if it was done before
+        win32::CoInitializeEx(std::ptr::null(), win32::COINIT_APARTMENTTHREADED as u32);
+        let mut taskbar: *mut TaskbarList = std::ptr::null_mut();
+        let hr = win32::CoCreateInstance(
+            &CLSID_TASKBAR_LIST,
+            std::ptr::null_mut(),
+            win32::CLSCTX_INPROC_SERVER,
+            &IID_TASKBAR_LIST3,
+            &mut taskbar as *mut *mut TaskbarList as *mut *mut std::ffi::c_void,
+        );
+        if hr >= 0 && !taskbar.is_null() {
+            let vtbl = &*(*taskbar).vtbl;
+            if (vtbl.hr_init)(taskbar) >= 0 {
+                (vtbl.thumb_bar_add_buttons)(
+                    taskbar,
+                    h_window,
+                    buttons.len() as u32,
+                    buttons.as_ptr(),
+                );
+            }
+            (vtbl.release)(taskbar);
+        }
+        // the taskbar keeps copies of the icons
+        for button in &buttons {
+            win32::DestroyIcon(button.hIcon);
+        }
+    }
+}
+
+/// The main window, for `foreground_changed`, which is called without one.
+static OWNER: std::sync::atomic::AtomicIsize = std::sync::atomic::AtomicIsize::new(0);
+
+/// Remembers `window`, come to the front, as the one the words go to, unless it is the taskbar:
+/// it takes the focus for the click on a button.
+fn note_foreground(h_window: win32::HWND, window: win32::HWND) {
+    if window == 0 {
+        return;
+    }
+    let mut class = [0u16; 64];
+    let len = unsafe { win32::GetClassNameW(window, class.as_mut_ptr(), class.len() as i32) };
+    let class = String::from_utf16_lossy(&class[..len.max(0) as usize]);
+    if matches!(class.as_str(), "Shell_TrayWnd" | "Shell_SecondaryTrayWnd" | "TaskListThumbnailWnd")
+    {
+        return;
+    }
+    if let Some(app) = crate::app::get(h_window) {
+        app.target.set(window);
+    }
+}
+
+unsafe extern "system" fn foreground_changed(
+    _hook: win32::HWINEVENTHOOK,
+    _event: u32,
+    window: win32::HWND,
+    _object: i32,
+    _child: i32,
+    _thread: u32,
+    _time: u32,
+) {
+    note_foreground(OWNER.load(std::sync::atomic::Ordering::Relaxed), window);
+}
+
+/// Keeps `App::target` on the window in front, told by the system as it changes, windows of
+/// randword left out. Unhooked when dropped.
+pub struct ForegroundHook(win32::HWINEVENTHOOK);
+
+impl ForegroundHook {
+    pub fn set(h_window: win32::HWND) -> Self {
+        OWNER.store(h_window, std::sync::atomic::Ordering::Relaxed);
+        let hook = unsafe {
+            win32::SetWinEventHook(
+                win32::EVENT_SYSTEM_FOREGROUND,
+                win32::EVENT_SYSTEM_FOREGROUND,
+                0,
+                Some(foreground_changed),
+                0,
+                0,
+                win32::WINEVENT_OUTOFCONTEXT | win32::WINEVENT_SKIPOWNPROCESS,
+            )
+        };
+        // the window in front before randword started
+        let window = unsafe { win32::GetForegroundWindow() };
+        let mut process = 0;
+        unsafe { win32::GetWindowThreadProcessId(window, &mut process) };
+        if process != std::process::id() {
+            note_foreground(h_window, window);
+        }
+        return Self(hook);
+    }
+}
+
+impl Drop for ForegroundHook {
+    fn drop(&mut self) {
+        if self.0 != 0 {
+            unsafe { win32::UnhookWinEvent(self.0) };
+        }
+    }
+}
+
+/// A click on button `id`, in `wndproc`: Next and Previous hand the focus back to the window
+/// the words go to and go on as their hotkeys do; Pause snoozes, which pauses the buttons too,
+/// as quiet hours do.
+pub fn clicked(h_window: win32::HWND, id: usize) {
+    let hotkey = match id {
+        NEXT => crate::HOTKEY_DISPENSE,
+        PREVIOUS => crate::HOTKEY_PREVIOUS,
+        PAUSE => {
+            crate::snooze::toggle(h_window);
+            return;
+        }
+        _ => return,
+    };
+    if crate::snooze::is_on(h_window) {
+        crate::paint::show_status(h_window, "Snoozed; Pause again to end it");
+        return;
+    }
+    let Some(app) = crate::app::get(h_window) else {
+        return;
+    };
+    // the hotkeys are unregistered for quiet hours, the buttons don't get around that
+    if app.paused.get() {
+        crate::paint::show_status(h_window, "Quiet hours; the buttons are back after them");
+        return;
+    }
+    let target = app.target.get();
+    unsafe {
+        if target != 0 {
+            win32::SetForegroundWindow(target);
+        }
+        win32::PostMessageW(h_window, win32::WM_HOTKEY, hotkey as usize, 0);
+    }
+}
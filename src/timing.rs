@@ -0,0 +1,53 @@
+use crate::win32;
+
+/// What is left of a wait after the timer is spun away, to make up for the timer firing late.
+const SPIN: std::time::Duration = std::time::Duration::from_millis(1);
+
+/// Waits between typed chars to a few microseconds. `Sleep` rounds up to the scheduler tick,
+/// 15.6 ms by default, so the bulk of the wait goes to a high-resolution waitable timer and
+/// the last bit is spun on `Instant`, which reads the performance counter.
+pub struct Timer {
+    /// 0 before Windows 10 1803, which lacks high-resolution timers; `Sleep` is used then
+    handle: win32::HANDLE,
+}
+
+impl Timer {
+    pub fn new() -> Self {
+        let handle = unsafe {
+            win32::CreateWaitableTimerExW(
+                std::ptr::null(),
+                std::ptr::null(),
+                win32::CREATE_WAITABLE_TIMER_HIGH_RESOLUTION,
+                win32::TIMER_ALL_ACCESS,
+            )
+        };
+        return Self { handle };
+    }
+
+    pub fn wait(&self, duration: std::time::Duration) {
+        let deadline = std::time::Instant::now() + duration;
+        if let Some(coarse) = duration.checked_sub(SPIN) {
+            // negative due times are relative, in 100 ns units
+            let due = -((coarse.as_nanos() / 100) as i64);
+            let waited = self.handle != 0
+                && unsafe {
+                    win32::SetWaitableTimer(self.handle, &due, 0, None, std::ptr::null(), 0) != 0
+                        && win32::WaitForSingleObject(self.handle, win32::INFINITE) == 0
+                };
+            if !waited {
+                unsafe { win32::Sleep(coarse.as_millis() as u32) };
+            }
+        }
+        while std::time::Instant::now() < deadline {
+            std::hint::spin_loop();
+        }
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        if self.handle != 0 {
+            unsafe { win32::CloseHandle(self.handle) };
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use crate::win32;
+
+/// A piece of a line for the typing engine.
+pub enum Token<'a> {
+    Text(&'a str),
+    /// `{pause:500}`: wait this many milliseconds before typing on, at most `MAX_DELAY_MS`
+    Pause(u32),
+    /// `{TAB}`, `{ENTER}`, `{F5}` and the like: a press of that virtual key
+    Key {
+        vkey: u16,
+        name: &'a str,
+    },
+}
+
+/// Key names for `{...}` tokens and `hotkey=`, besides F1 to F24.
+const KEY_NAMES: &[(&str, u16)] = &[
+    ("TAB", win32::VK_TAB),
+    ("ENTER", win32::VK_RETURN),
+    ("ESC", win32::VK_ESCAPE),
+    ("SPACE", win32::VK_SPACE),
+    ("BACKSPACE", win32::VK_BACK),
+    ("BS", win32::VK_BACK),
+    ("DELETE", win32::VK_DELETE),
+    ("DEL", win32::VK_DELETE),
+    ("INSERT", win32::VK_INSERT),
+    ("INS", win32::VK_INSERT),
+    ("HOME", win32::VK_HOME),
+    ("END", win32::VK_END),
+    ("PGUP", win32::VK_PRIOR),
+    ("PGDN", win32::VK_NEXT),
+    ("UP", win32::VK_UP),
+    ("DOWN", win32::VK_DOWN),
+    ("LEFT", win32::VK_LEFT),
+    ("RIGHT", win32::VK_RIGHT),
+    ("CAPSLOCK", win32::VK_CAPITAL),
+    ("NUMLOCK", win32::VK_NUMLOCK),
+    ("SCROLLLOCK", win32::VK_SCROLL),
+];
+
+/// The virtual key of a key name like `TAB` or `F5`, ignoring case.
+pub fn key(name: &str) -> Option<u16> {
+    let name = name.to_ascii_uppercase();
+    if let Some(n) = name.strip_prefix('F').and_then(|n| n.parse::<u16>().ok()) {
+        return (1..=24).contains(&n).then(|| win32::VK_F1 + n - 1);
+    }
+    return KEY_NAMES.iter().find(|(n, _)| *n == name).map(|&(_, vkey)| vkey);
+}
+
+/// The first name of `vkey` in the table, e.g. `TAB`, for messages.
+pub fn key_name(vkey: u16) -> Option<&'static str> {
+    return KEY_NAMES.iter().find(|&&(_, k)| k == vkey).map(|&(name, _)| name);
+}
+
+fn parse(name: &str) -> Option<Token<'_>> {
+    if let Some(millis) = name.strip_prefix("pause:") {
+        // a typo like `{pause:50000}` would otherwise hang the typing for minutes
+        return millis.parse().ok().map(|ms: u32| Token::Pause(ms.min(crate::MAX_DELAY_MS)));
+    }
+    return key(name).map(|vkey| Token::Key { vkey, name });
+}
+
+/// Splits `line` at the `{...}` tokens it contains. Braces that don't form a known token are
+/// kept as text.
+pub fn tokenize(line: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut text_start = 0;
+    let mut search = 0;
+    while let Some(open) = line[search..].find('{').map(|i| search + i) {
+        let Some(close) = line[open..].find('}').map(|i| open + i) else {
+            break;
+        };
+        match parse(&line[open + 1..close]) {
+            Some(token) => {
+                if text_start < open {
+                    tokens.push(Token::Text(&line[text_start..open]));
+                }
+                tokens.push(token);
+                text_start = close + 1;
+                search = close + 1;
+            }
+            None => search = open + 1,
+        }
+    }
+    if text_start < line.len() {
+        tokens.push(Token::Text(&line[text_start..]));
+    }
+    return tokens;
+}
+
+/// The names of the key tokens in `line`, in order.
+pub fn keys(line: &str) -> Vec<&str> {
+    return tokenize(line)
+        .into_iter()
+        .filter_map(|t| match t {
+            Token::Key { name, .. } => Some(name),
+            _ => None,
+        })
+        .collect();
+}
+
+/// `line` without its tokens, for outputs that don't type.
+pub fn plain(line: &str) -> String {
+    let mut text = String::with_capacity(line.len());
+    for token in tokenize(line) {
+        if let Token::Text(t) = token {
+            text.push_str(t);
+        }
+    }
+    return text;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_at_tokens() {
+        let tokens = tokenize("a{TAB}b{pause:20}c");
+        assert_eq!(tokens.len(), 5);
+        assert!(matches!(tokens[0], Token::Text("a")));
+        assert!(matches!(tokens[1], Token::Key { vkey, name: "TAB" } if vkey == win32::VK_TAB));
+        assert!(matches!(tokens[2], Token::Text("b")));
+        assert!(matches!(tokens[3], Token::Pause(20)));
+        assert!(matches!(tokens[4], Token::Text("c")));
+    }
+
+    #[test]
+    fn keeps_unknown_braces_as_text() {
+        let tokens = tokenize("{x} {F25} {pause:soon} {");
+        assert_eq!(tokens.len(), 1);
+        assert!(matches!(tokens[0], Token::Text("{x} {F25} {pause:soon} {")));
+        assert_eq!(keys("{enter}{f5}{x}"), ["enter", "f5"]);
+        assert_eq!(plain("a{ENTER}b{c}"), "ab{c}");
+    }
+
+    #[test]
+    fn caps_pauses() {
+        let tokens = tokenize("{pause:999999}");
+        assert!(matches!(tokens[..], [Token::Pause(ms)] if ms == crate::MAX_DELAY_MS));
+    }
+}
@@ -0,0 +1,137 @@
+use crate::win32;
+
+/// The message the icon sends to the window, with the mouse message in `lParam`.
+pub const CALLBACK: u32 = win32::WM_APP + 1;
+const ICON_ID: u32 = 1;
+/// Ids of the menu items; "Default", for no profile, comes first.
+const PROFILE_FIRST: usize = 0x0200;
+const QUIT: usize = 0x0300;
+/// The items the main loop picks up, see `App::pending_command`.
+pub const COPY_AGAIN: usize = 0x0301;
+pub const SKIP: usize = 0x0302;
+pub const STAR: usize = 0x0303;
+
+fn icon_data(h_window: win32::HWND) -> win32::NOTIFYICONDATAW {
+    let mut data: win32::NOTIFYICONDATAW = unsafe { std::mem::zeroed() };
+    data.cbSize = std::mem::size_of::<win32::NOTIFYICONDATAW>() as u32;
+    data.hWnd = h_window;
+    data.uID = ICON_ID;
+    return data;
+}
+
+/// The message Explorer broadcasts once the taskbar is back after a restart, which takes the
+/// icons with it.
+pub fn created_message() -> u32 {
+    static MESSAGE: std::sync::OnceLock<u32> = std::sync::OnceLock::new();
+    return *MESSAGE
+        .get_or_init(|| unsafe { win32::RegisterWindowMessageW(win32::w!("TaskbarCreated")) });
+}
+
+/// Puts the icon in the notification area with the tip kept in the app, adding it or
+/// changing the one there. Without it, because the shell refused, the hotkeys work as before.
+fn put(h_window: win32::HWND, message: u32) {
+    let Some(app) = crate::app::get(h_window) else {
+        return;
+    };
+    let mut data = icon_data(h_window);
+    data.uFlags = win32::NIF_MESSAGE | win32::NIF_ICON | win32::NIF_TIP;
+    data.uCallbackMessage = CALLBACK;
+    data.hIcon = unsafe { win32::LoadIconW(0, win32::IDI_APPLICATION) };
+    // the last char stays 0
+    for (to, from) in data.szTip.iter_mut().zip(app.tray_tip.borrow().encode_utf16().take(127)) {
+        *to = from;
+    }
+    unsafe { win32::Shell_NotifyIconW(message, &data) };
+}
+
+/// Adds the icon, or changes the tip of the one there to `tip`, shown when it is hovered.
+pub fn show(h_window: win32::HWND, tip: &str) {
+    let Some(app) = crate::app::get(h_window) else {
+        return;
+    };
+    let added = !app.tray_tip.borrow().is_empty();
+    *app.tray_tip.borrow_mut() = tip.to_owned();
+    put(h_window, if added { win32::NIM_MODIFY } else { win32::NIM_ADD });
+}
+
+/// Adds the icon again after Explorer restarted, on `created_message`.
+pub fn restore(h_window: win32::HWND) {
+    put(h_window, win32::NIM_ADD);
+}
+
+pub fn remove(h_window: win32::HWND) {
+    unsafe { win32::Shell_NotifyIconW(win32::NIM_DELETE, &icon_data(h_window)) };
+}
+
+/// A mouse message from the icon, in `wndproc`: a right click opens the menu.
+pub fn handle(h_window: win32::HWND, lparam: win32::LPARAM) {
+    let message = lparam as u32 & 0xFFFF;
+    if message == win32::WM_RBUTTONUP || message == win32::WM_CONTEXTMENU {
+        show_menu(h_window);
+    }
+}
+
+fn wide(text: &str) -> Vec<u16> {
+    return text.encode_utf16().chain(std::iter::once(0)).collect();
+}
+
+/// The icon's menu: Copy again, Skip and Star for quick corrections, a Profile submenu with
+/// the files in `profiles\`, the current one checked, Snooze 15 min and Quit. Picking a
+/// profile or a correction has the main loop do it.
+fn show_menu(h_window: win32::HWND) {
+    let Some(app) = crate::app::get(h_window) else {
+        return;
+    };
+    let names = crate::config::profile_names();
+    let current = app.profile.borrow().as_ref().and_then(|p| names.iter().position(|n| n == p));
+    let current = PROFILE_FIRST + current.map_or(0, |i| i + 1);
+    let mut cursor = win32::POINT { x: 0, y: 0 };
+    let command = unsafe {
+        let menu = win32::CreatePopupMenu();
+        win32::AppendMenuW(menu, win32::MF_STRING, COPY_AGAIN, win32::w!("Copy again"));
+        win32::AppendMenuW(menu, win32::MF_STRING, SKIP, win32::w!("Skip"));
+        win32::AppendMenuW(menu, win32::MF_STRING, STAR, win32::w!("Star"));
+        win32::AppendMenuW(menu, win32::MF_SEPARATOR, 0, std::ptr::null());
+        let profiles = win32::CreatePopupMenu();
+        win32::AppendMenuW(profiles, win32::MF_STRING, PROFILE_FIRST, win32::w!("Default"));
+        for (i, name) in names.iter().enumerate() {
+            let item = PROFILE_FIRST + 1 + i;
+            win32::AppendMenuW(profiles, win32::MF_STRING, item, wide(name).as_ptr());
+        }
+        let last = PROFILE_FIRST + names.len();
+        win32::CheckMenuRadioItem(
+            profiles,
+            PROFILE_FIRST as u32,
+            last as u32,
+            current as u32,
+            win32::MF_BYCOMMAND,
+        );
+        win32::AppendMenuW(menu, win32::MF_POPUP, profiles as usize, win32::w!("Profile"));
+        let snoozed = if crate::snooze::is_on(h_window) { win32::MF_CHECKED } else { 0 };
+        let item = win32::MF_STRING | snoozed;
+        win32::AppendMenuW(menu, item, crate::snooze::MENU_ID, win32::w!("Snooze 15 min"));
+        win32::AppendMenuW(menu, win32::MF_SEPARATOR, 0, std::ptr::null());
+        win32::AppendMenuW(menu, win32::MF_STRING, QUIT, win32::w!("Quit"));
+
+        win32::GetCursorPos(&mut cursor);
+        // the menu only closes on a click elsewhere while the window is in front
+        win32::SetForegroundWindow(h_window);
+        let flags = win32::TPM_RETURNCMD | win32::TPM_RIGHTBUTTON;
+        let command =
+            win32::TrackPopupMenu(menu, flags, cursor.x, cursor.y, 0, h_window, std::ptr::null());
+        win32::PostMessageW(h_window, win32::WM_NULL, 0, 0);
+        // the submenu goes with it
+        win32::DestroyMenu(menu);
+        command as usize
+    };
+    if command == crate::snooze::MENU_ID {
+        crate::snooze::toggle(h_window);
+    } else if command == QUIT {
+        unsafe { win32::PostMessageW(h_window, win32::WM_CLOSE, 0, 0) };
+    } else if matches!(command, COPY_AGAIN | SKIP | STAR) {
+        app.pending_command.set(Some(command));
+    } else if (PROFILE_FIRST..=PROFILE_FIRST + names.len()).contains(&command) {
+        let profile = (command > PROFILE_FIRST).then(|| names[command - PROFILE_FIRST - 1].clone());
+        *app.pending_profile.borrow_mut() = Some(profile);
+    }
+}
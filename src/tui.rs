@@ -0,0 +1,129 @@
+use std::io::{BufRead, Write};
+use std::os::windows::io::AsRawHandle;
+
+use crate::win32;
+
+/// How many of the last words stay on screen.
+const HISTORY_COUNT: usize = 10;
+
+/// The console of the shell that started us, or a new one when started from Explorer.
+fn open_console() -> std::io::Result<(std::fs::File, std::fs::File)> {
+    unsafe {
+        if win32::AttachConsole(win32::ATTACH_PARENT_PROCESS) == 0 {
+            win32::AllocConsole();
+        }
+    }
+    let output = std::fs::OpenOptions::new().read(true).write(true).open("CONOUT$")?;
+    let input = std::fs::OpenOptions::new().read(true).write(true).open("CONIN$")?;
+    // for the escape sequence that clears the screen; terminals over SSH have it on already
+    let handle = output.as_raw_handle() as win32::HANDLE;
+    let mut mode = 0;
+    unsafe {
+        if win32::GetConsoleMode(handle, &mut mode) != 0 {
+            win32::SetConsoleMode(handle, mode | win32::ENABLE_VIRTUAL_TERMINAL_PROCESSING);
+        }
+    }
+    return Ok((output, input));
+}
+
+fn draw(
+    out: &mut impl Write,
+    list: &crate::list::WordList,
+    session: &crate::session::Session,
+    status: &str,
+) -> std::io::Result<()> {
+    write!(out, "\x1b[2J\x1b[H")?;
+    writeln!(out, "randword  {}/{}  {} this session\n", list.position, list.total, session.len())?;
+    let recent = session.recent(HISTORY_COUNT);
+    for (i, entry) in recent.iter().enumerate() {
+        let marker = if i + 1 == recent.len() { '>' } else { ' ' };
+        // just the time of `YYYY-MM-DD HH:MM:SS`
+        writeln!(out, "{} {}  {}", marker, &entry.timestamp[11..], entry.word)?;
+    }
+    writeln!(out, "\n{}", status)?;
+    write!(out, "Enter next, + / - rate, e export, q quit: ")?;
+    return out.flush();
+}
+
+/// `randword tui`: the session in a console instead of a window, for use over SSH or inside a
+/// terminal multiplexer. Words are shown rather than typed; the list position, stats, hooks
+/// and session export work as in the window.
+pub fn run(
+    list: &mut crate::list::WordList,
+    options: &crate::Options,
+    session: &mut crate::session::Session,
+    stats: &mut crate::stats::Stats,
+) {
+    let (mut out, input) = match open_console() {
+        Ok(c) => c,
+        Err(e) => {
+            crate::message_box(
+                &e.to_string(),
+                "Console Error",
+                win32::MB_OK | win32::MB_ICONEXCLAMATION,
+            );
+            return;
+        }
+    };
+    let mut status = String::new();
+    let mut lines = std::io::BufReader::new(input).lines();
+    loop {
+        if draw(&mut out, list, session, &status).is_err() {
+            return;
+        }
+        let Some(Ok(command)) = lines.next() else {
+            return;
+        };
+        status.clear();
+        match command.trim() {
+            "" => match crate::next_line(list, session, stats) {
+                Ok(true) => {
+                    session.track(&list.path);
+                    let line = list.line();
+                    let entry = session.record(line, &list.path, "tui", String::new());
+                    stats.record(&entry.word, &entry.timestamp);
+                    stats.note_position(list);
+                    if let Some(hook) = &options.before_hook {
+                        if let Err(e) = hook.run(line, list.position, entry.mode) {
+                            status = format!("Hook error: {}", e);
+                        }
+                    }
+                    if let Some(hook) = &options.after_hook {
+                        if let Err(e) = hook.start(line, list.position, entry.mode) {
+                            status = format!("Hook error: {}", e);
+                        }
+                    }
+                }
+                Ok(false) => {
+                    let text = format!("Finished {}", list.path);
+                    crate::output::log_event("list_completed", &text);
+                    crate::webhook::notify("list_completed", &text);
+                    status = "No more words".to_owned();
+                }
+                Err(e) => {
+                    let text = format!("Shared state error: {}", e);
+                    crate::output::log_event("error", &text);
+                    crate::webhook::notify("error", &text);
+                    status = format!("Shared state error: {}", e);
+                }
+            },
+            delta @ ("+" | "-") => {
+                if let Some(entry) = session.last() {
+                    let rating = stats.rate(&entry.word, if delta == "+" { 1 } else { -1 });
+                    status = format!("{} rated {:+}", entry.word, rating);
+                    crate::output::log_event("rate", &status);
+                }
+            }
+            "e" => {
+                status = match session.export_csv(crate::SESSION_CSV_PATH) {
+                    Ok(()) => {
+                        format!("Exported {} words to {}", session.len(), crate::SESSION_CSV_PATH)
+                    }
+                    Err(e) => format!("Session export error: {}", e),
+                }
+            }
+            "q" => return,
+            other => status = format!("Unknown command `{}`", other),
+        }
+    }
+}
@@ -0,0 +1,200 @@
+/// A semver `major.minor.patch`, e.g. `1.4.0` or `v1.4.0`. A pre-release like `1.4.0-rc.1`
+/// comes before the release; build metadata after `+` is ignored.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    numbers: [u64; 3],
+    /// false for a pre-release, so it sorts first
+    release: bool,
+    /// compared dot-separated part by part, numbers as numbers
+    pre: Vec<(u64, String)>,
+}
+
+impl Version {
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let invalid = || anyhow::anyhow!("`{}` is not a major.minor.patch version", text);
+        let core = text.strip_prefix('v').unwrap_or(text);
+        let core = core.split_once('+').map_or(core, |(core, _)| core);
+        let (core, pre) = match core.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (core, None),
+        };
+        let mut numbers = [0; 3];
+        let mut parts = core.split('.');
+        for number in &mut numbers {
+            *number = parts.next().and_then(|p| p.parse().ok()).ok_or_else(invalid)?;
+        }
+        if parts.next().is_some() || pre.is_some_and(str::is_empty) {
+            return Err(invalid());
+        }
+        // a part that isn't a number sorts after the numbers, as in semver
+        let parts = pre.into_iter().flat_map(|pre| pre.split('.'));
+        return Ok(Self {
+            numbers,
+            release: pre.is_none(),
+            pre: parts.map(|p| (p.parse().unwrap_or(u64::MAX), p.to_owned())).collect(),
+        });
+    }
+}
+
+/// The release manifest is three lines: the version, the https URL of the new randword.exe
+/// and its SHA-256 in hex. It has to come over https too, since the hash is only as
+/// trustworthy as the manifest.
+struct Manifest {
+    version: String,
+    url: String,
+    sha256: String,
+}
+
+impl Manifest {
+    fn parse(text: &str) -> anyhow::Result<Self> {
+        let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+        let (Some(version), Some(url), Some(sha256)) = (lines.next(), lines.next(), lines.next())
+        else {
+            return Err(anyhow::anyhow!("update manifest: expected version, url and sha256 lines"));
+        };
+        Version::parse(version).map_err(|e| anyhow::anyhow!("update manifest: {}", e))?;
+        if !crate::remote::is_https(url) {
+            return Err(anyhow::anyhow!("update manifest: `{}` is not an https URL", url));
+        }
+        if sha256.len() != 64 || !sha256.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(anyhow::anyhow!("update manifest: `{}` is not a SHA-256", sha256));
+        }
+        return Ok(Self {
+            version: version.to_owned(),
+            url: url.to_owned(),
+            sha256: sha256.to_ascii_lowercase(),
+        });
+    }
+}
+
+fn sibling(exe: &std::path::Path, suffix: &str) -> std::path::PathBuf {
+    let mut path = exe.as_os_str().to_owned();
+    path.push(suffix);
+    return path.into();
+}
+
+/// Downloads with the curl.exe that ships with Windows 10 and later. A redirect may only go
+/// to another https URL.
+pub fn download(url: &str, path: &std::path::Path) -> anyhow::Result<()> {
+    use std::os::windows::process::CommandExt;
+
+    let status = std::process::Command::new("curl.exe")
+        .args(["--fail", "--silent", "--show-error", "--location", "--proto-redir", "=https"])
+        .arg("--output")
+        .arg(path)
+        .arg(url)
+        .creation_flags(crate::CREATE_NO_WINDOW)
+        .status()
+        .map_err(|e| anyhow::anyhow!("curl.exe: {}", e))?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("downloading {} failed ({})", url, status));
+    }
+    return Ok(());
+}
+
+/// Stages a newer release next to the running exe as `randword.exe.new`, returning its
+/// version, or None when already up to date.
+fn stage(manifest_url: &str) -> anyhow::Result<Option<String>> {
+    // over http, whoever is on the way could hand out another binary with its hash
+    if !crate::remote::is_https(manifest_url) {
+        return Err(anyhow::anyhow!("`{}` is not an https URL", manifest_url));
+    }
+    let exe = std::env::current_exe()?;
+    let manifest_path = sibling(&exe, ".manifest");
+    download(manifest_url, &manifest_path)?;
+    let manifest = Manifest::parse(&std::fs::read_to_string(&manifest_path)?);
+    let _ = std::fs::remove_file(&manifest_path);
+    let manifest = manifest?;
+    let current = env!("CARGO_PKG_VERSION");
+    match Version::parse(&manifest.version)?.cmp(&Version::parse(current)?) {
+        std::cmp::Ordering::Greater => {}
+        std::cmp::Ordering::Equal => return Ok(None),
+        // a stale or tampered manifest must not roll back a fix
+        std::cmp::Ordering::Less => {
+            return Err(anyhow::anyhow!(
+                "the manifest offers {}, older than this randword {}; not downgrading",
+                manifest.version,
+                current
+            ));
+        }
+    }
+
+    let download_path = sibling(&exe, ".download");
+    download(&manifest.url, &download_path)?;
+    let hash = {
+        use sha2::Digest;
+        format!("{:x}", sha2::Sha256::digest(std::fs::read(&download_path)?))
+    };
+    if hash != manifest.sha256 {
+        let _ = std::fs::remove_file(&download_path);
+        return Err(anyhow::anyhow!(
+            "{} does not match the manifest: got SHA-256 {}, expected {}",
+            manifest.url,
+            hash,
+            manifest.sha256
+        ));
+    }
+    // only a verified binary ever gets the name apply_staged looks for
+    std::fs::rename(&download_path, sibling(&exe, ".new"))?;
+    return Ok(Some(manifest.version));
+}
+
+/// `randword update <manifest url>`, instead of starting a session.
+pub fn run(manifest_url: Option<&str>) {
+    let Some(manifest_url) = manifest_url else {
+        crate::message_box(
+            "Usage: randword update <manifest url>",
+            "Update",
+            crate::win32::MB_OK | crate::win32::MB_ICONEXCLAMATION,
+        );
+        return;
+    };
+    match stage(manifest_url) {
+        Ok(Some(version)) => crate::message_box(
+            &format!("randword {} will be installed the next time it starts.", version),
+            "Update",
+            crate::win32::MB_OK | crate::win32::MB_ICONINFORMATION,
+        ),
+        Ok(None) => crate::message_box(
+            &format!("randword {} is up to date.", env!("CARGO_PKG_VERSION")),
+            "Update",
+            crate::win32::MB_OK | crate::win32::MB_ICONINFORMATION,
+        ),
+        Err(e) => crate::message_box(
+            &e.to_string(),
+            "Update Error",
+            crate::win32::MB_OK | crate::win32::MB_ICONEXCLAMATION,
+        ),
+    }
+}
+
+/// Swaps in a release staged by `randword update` and starts it with the same arguments.
+/// Returns true when that happened and this process should exit.
+///
+/// A running exe can be renamed but not deleted, so the current one is moved to
+/// `randword.exe.old` and removed on the start after.
+pub fn apply_staged() -> bool {
+    let Ok(exe) = std::env::current_exe() else {
+        return false;
+    };
+    let old = sibling(&exe, ".old");
+    let _ = std::fs::remove_file(&old);
+    let staged = sibling(&exe, ".new");
+    if !staged.exists() {
+        return false;
+    }
+
+    if std::fs::rename(&exe, &old).is_err() {
+        return false;
+    }
+    if let Err(e) = std::fs::rename(&staged, &exe) {
+        let _ = std::fs::rename(&old, &exe);
+        crate::message_box(
+            &format!("Installing the update failed: {}", e),
+            "Update Error",
+            crate::win32::MB_OK | crate::win32::MB_ICONEXCLAMATION,
+        );
+        return false;
+    }
+    return std::process::Command::new(&exe).args(std::env::args_os().skip(1)).spawn().is_ok();
+}
@@ -0,0 +1,83 @@
+use crate::win32;
+
+/// Change notifications on the folders of the open lists, so the main loop sleeps until a file
+/// in one of them is written instead of checking the lists every second.
+#[derive(Default)]
+pub struct ListWatch {
+    /// the folder and its notification, INVALID_HANDLE_VALUE where none could be had, e.g. on
+    /// some network drives
+    folders: Vec<(std::path::PathBuf, win32::HANDLE)>,
+}
+
+fn folder_of(list: &str) -> std::path::PathBuf {
+    let folder = std::path::Path::new(list).parent().unwrap_or(std::path::Path::new(""));
+    if folder.as_os_str().is_empty() {
+        return std::path::PathBuf::from(".");
+    }
+    return folder.to_path_buf();
+}
+
+fn close(handle: win32::HANDLE) {
+    if handle != win32::INVALID_HANDLE_VALUE {
+        unsafe { win32::FindCloseChangeNotification(handle) };
+    }
+}
+
+impl ListWatch {
+    /// Watches the folders of `lists` and stops watching the others.
+    pub fn watch<'a>(&mut self, lists: impl Iterator<Item = &'a str>) {
+        let wanted: Vec<std::path::PathBuf> = lists.map(folder_of).collect();
+        self.folders.retain(|(folder, handle)| {
+            let keep = wanted.contains(folder);
+            if !keep {
+                close(*handle);
+            }
+            return keep;
+        });
+        for folder in wanted {
+            if self.folders.iter().any(|(f, _)| *f == folder) {
+                continue;
+            }
+            let path: Vec<u16> =
+                folder.display().to_string().encode_utf16().chain(std::iter::once(0)).collect();
+            let filter = win32::FILE_NOTIFY_CHANGE_LAST_WRITE | win32::FILE_NOTIFY_CHANGE_FILE_NAME;
+            let handle = unsafe { win32::FindFirstChangeNotificationW(path.as_ptr(), 0, filter) };
+            self.folders.push((folder, handle));
+        }
+    }
+
+    /// The notifications to wait on, signaled once something in their folder was written.
+    pub fn handles(&self) -> Vec<win32::HANDLE> {
+        return self
+            .folders
+            .iter()
+            .map(|&(_, handle)| handle)
+            .filter(|&handle| handle != win32::INVALID_HANDLE_VALUE)
+            .collect();
+    }
+
+    /// Whether every folder is watched; the others have to be checked every now and then.
+    pub fn is_complete(&self) -> bool {
+        return self.folders.iter().all(|&(_, handle)| handle != win32::INVALID_HANDLE_VALUE);
+    }
+
+    /// Whether a folder was written since the last call, waiting for the next write to it.
+    pub fn take(&mut self) -> bool {
+        let mut written = false;
+        for handle in self.handles() {
+            if unsafe { win32::WaitForSingleObject(handle, 0) } == win32::WAIT_OBJECT_0 {
+                unsafe { win32::FindNextChangeNotification(handle) };
+                written = true;
+            }
+        }
+        return written;
+    }
+}
+
+impl Drop for ListWatch {
+    fn drop(&mut self) {
+        for &(_, handle) in &self.folders {
+            close(handle);
+        }
+    }
+}
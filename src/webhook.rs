@@ -0,0 +1,31 @@
+/// Set from `webhook=`; errors are reported from places that don't see the options.
+static URL: std::sync::Mutex<Option<String>> = std::sync::Mutex::new(None);
+
+/// Sets the `webhook=` URL, or drops it with None after a profile switch.
+pub fn configure(url: Option<&str>) {
+    *URL.lock().unwrap_or_else(|e| e.into_inner()) = url.map(str::to_owned);
+}
+
+/// POSTs `{"event": ..., "text": ...}` to the `webhook=` URL, if any. `content` repeats the
+/// text so a Discord or Slack-style incoming webhook shows it as is. curl.exe runs in the
+/// background and isn't waited for; a failed notification is not worth interrupting for.
+pub fn notify(event: &str, text: &str) {
+    use std::os::windows::process::CommandExt;
+
+    let Some(url) = URL.lock().unwrap_or_else(|e| e.into_inner()).clone() else {
+        return;
+    };
+    let text = crate::http::json_string(text);
+    let payload = format!(
+        "{{\"event\":{},\"text\":{},\"content\":{}}}",
+        crate::http::json_string(event),
+        text,
+        text
+    );
+    let _ = std::process::Command::new("curl.exe")
+        .args(["--fail", "--silent", "--max-time", "10"])
+        .args(["--header", "Content-Type: application/json", "--data-binary", &payload])
+        .arg(&url)
+        .creation_flags(crate::CREATE_NO_WINDOW)
+        .spawn();
+}